@@ -11,12 +11,14 @@ use serenity::async_trait;
 
 use songbird::tracks::{PlayMode, TrackHandle};
 
-use symphonia::core::codecs;
-use symphonia::core::formats::FormatOptions;
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{self, DecoderOptions};
+use symphonia::core::formats::{FormatOptions, FormatReader};
 use symphonia::core::io::MediaSourceStream;
 use symphonia::core::meta::MetadataOptions;
 use symphonia::core::probe::Hint;
 
+use crate::charts;
 use crate::commands::PoiseError;
 use crate::common::LogResult;
 use crate::helpers::{self};
@@ -85,7 +87,8 @@ impl AudioFileValidator {
         self
     }
 
-    pub fn validate(&self, path: impl AsRef<path::Path>) -> Result<(), PoiseError> {
+    /// Validates audio file, returning its probed track info
+    pub fn validate(&self, path: impl AsRef<path::Path>) -> Result<AudioTrackInfo, PoiseError> {
         let path = path.as_ref();
         log::info!("Validating audio file: {}", path.to_string_lossy());
 
@@ -117,7 +120,7 @@ impl AudioFileValidator {
             return Err(format!("Audio track is {track_dur:.2}s long. This exceeds the max duration of {max_dur:.2}s").into()).log_err();
         }
 
-        Ok(())
+        Ok(track_info)
     }
 }
 
@@ -152,6 +155,42 @@ impl AudioFile {
                 self.0.to_string_lossy()
             ))
             .ok();
+
+        let waveform = self.waveform_path();
+        if waveform.exists() {
+            std::fs::remove_file(&waveform)
+                .log_err_msg(format!(
+                    "Failed to delete waveform file {}",
+                    waveform.to_string_lossy()
+                ))
+                .ok();
+        }
+    }
+
+    /// Waveform preview image, stored alongside the audio file
+    pub fn waveform_path(&self) -> path::PathBuf {
+        self.0.with_extension("png")
+    }
+
+    /// Decodes audio file & writes its waveform preview image
+    pub fn write_waveform(&self) -> Result<path::PathBuf, PoiseError> {
+        let peaks = decode_waveform_peaks(&self.0, charts::WAVEFORM_WIDTH as usize)?;
+        let waveform = self.waveform_path();
+
+        log::info!("Writing waveform: {}", waveform.to_string_lossy());
+        fs::write(&waveform, charts::waveform(&peaks).to_png()?).log_err()?;
+        Ok(waveform)
+    }
+
+    /// Reads waveform preview image. None if it hasn't been rendered yet.
+    /// **note**: Never renders it, since decoding blocks. See `write_waveform`
+    pub fn read_waveform(&self) -> Result<Option<Vec<u8>>, PoiseError> {
+        let waveform = self.waveform_path();
+        if !waveform.exists() {
+            return Ok(None);
+        }
+
+        Ok(Some(fs::read(waveform).log_err()?))
     }
 
     pub fn as_path_buf(&self) -> path::PathBuf {
@@ -198,11 +237,7 @@ pub struct AudioTrackInfo {
     pub duration: std::time::Duration,
 }
 
-pub fn probe_audio_track(audio_file: impl AsRef<path::Path>) -> Result<AudioTrackInfo, PoiseError> {
-    let path = audio_file.as_ref();
-
-    log::info!("Probing audio-track: {}", path.to_string_lossy());
-
+fn open_audio_format(path: &path::Path) -> Result<Box<dyn FormatReader>, PoiseError> {
     let file: fs::File = std::fs::File::open(path).log_err()?;
     let mss = MediaSourceStream::new(Box::new(file), Default::default());
     let mut hint = Hint::default();
@@ -218,8 +253,16 @@ pub fn probe_audio_track(audio_file: impl AsRef<path::Path>) -> Result<AudioTrac
         )
         .log_err_msg("Failed to probe format")?;
 
+    Ok(probed.format)
+}
+
+pub fn probe_audio_track(audio_file: impl AsRef<path::Path>) -> Result<AudioTrackInfo, PoiseError> {
+    let path = audio_file.as_ref();
+
+    log::info!("Probing audio-track: {}", path.to_string_lossy());
+
     // Get the format reader
-    let format = probed.format;
+    let format = open_audio_format(path)?;
 
     // Get the default track
     let track = format
@@ -255,6 +298,112 @@ pub fn probe_audio_track(audio_file: impl AsRef<path::Path>) -> Result<AudioTrac
     })
 }
 
+/// Loudest sample per bucket of audio frames, for drawing waveforms
+struct PeakBuckets {
+    frames_per_bucket: u64,
+    frame: u64,
+    peaks: Vec<f32>,
+}
+
+impl PeakBuckets {
+    fn new(total_frames: u64, buckets: usize) -> Self {
+        Self {
+            frames_per_bucket: total_frames.div_ceil(buckets.max(1) as u64).max(1),
+            frame: 0,
+            peaks: vec![0.0; buckets.max(1)],
+        }
+    }
+
+    /// Adds next frame's loudest sample across channels
+    fn push(&mut self, frame: &[f32]) {
+        let bucket = ((self.frame / self.frames_per_bucket) as usize).min(self.peaks.len() - 1);
+        let peak = frame
+            .iter()
+            .fold(0.0_f32, |peak, sample| peak.max(sample.abs()));
+
+        self.peaks[bucket] = self.peaks[bucket].max(peak);
+        self.frame += 1;
+    }
+
+    /// Peaks scaled so the loudest is 1.0
+    fn finish(self) -> Vec<f32> {
+        let max = self.peaks.iter().copied().fold(0.0_f32, f32::max);
+
+        if max > 0.0 {
+            self.peaks.into_iter().map(|peak| peak / max).collect()
+        } else {
+            self.peaks
+        }
+    }
+}
+
+/// Decodes audio track, returning `buckets` normalized peaks spread evenly over its duration
+pub fn decode_waveform_peaks(
+    audio_file: impl AsRef<path::Path>,
+    buckets: usize,
+) -> Result<Vec<f32>, PoiseError> {
+    let path = audio_file.as_ref();
+
+    log::info!("Decoding audio-track waveform: {}", path.to_string_lossy());
+
+    let mut format = open_audio_format(path)?;
+    let track = format
+        .default_track()
+        .ok_or("No audio track found")
+        .log_err()?;
+    let track_id = track.id;
+    let total_frames = track
+        .codec_params
+        .n_frames
+        .ok_or("Couldn't get number of frames for audio track")?;
+
+    let mut decoder = symphonia::default::get_codecs()
+        .make(&track.codec_params, &DecoderOptions::default())
+        .log_err_msg("Failed to create audio decoder")?;
+
+    let mut peaks = PeakBuckets::new(total_frames, buckets);
+    let mut sample_buf: Option<SampleBuffer<f32>> = None;
+
+    loop {
+        let packet = match format.next_packet() {
+            Ok(packet) => packet,
+            Err(symphonia::core::errors::Error::IoError(err))
+                if err.kind() == std::io::ErrorKind::UnexpectedEof =>
+            {
+                break
+            }
+            Err(err) => return Err(err.into()).log_err(),
+        };
+
+        if packet.track_id() != track_id {
+            continue;
+        }
+
+        let decoded = match decoder.decode(&packet) {
+            Ok(decoded) => decoded,
+            Err(symphonia::core::errors::Error::DecodeError(err)) => {
+                log::warn!("Skipping undecodable audio packet - {err}");
+                continue;
+            }
+            Err(err) => return Err(err.into()).log_err(),
+        };
+
+        let spec = *decoded.spec();
+        let channels = spec.channels.count().max(1);
+        let samples = match sample_buf.as_mut() {
+            Some(buf) if buf.capacity() >= decoded.capacity() * channels => buf,
+            _ => sample_buf.insert(SampleBuffer::new(decoded.capacity() as u64, spec)),
+        };
+
+        samples.copy_interleaved_ref(decoded);
+        for frame in samples.samples().chunks(channels) {
+            peaks.push(frame);
+        }
+    }
+
+    Ok(peaks.finish())
+}
+
 pub const MAX_AUDIO_FILE_LENGTH_BYTES: u64 = 2_000_000; //2MB
 
 /// download audio url to temp dir (audio file is uuid4 name)
@@ -330,3 +479,33 @@ pub async fn download_audio_url_temp(url: impl AsRef<str>) -> Result<path::PathB
 
     Ok(audio_file_path)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn peak_buckets_test() {
+        let mut peaks = PeakBuckets::new(6, 3);
+        for frame in [
+            [0.1, -0.2],
+            [0.0, 0.0],
+            [-0.4, 0.1],
+            [0.2, 0.2],
+            [0.0, 0.0],
+            [0.0, 0.0],
+        ] {
+            peaks.push(&frame);
+        }
+        assert_eq!(peaks.finish(), vec![0.5, 1.0, 0.0]);
+
+        // frames beyond the expected total land in the last bucket
+        let mut peaks = PeakBuckets::new(2, 2);
+        for frame in [[0.0], [0.0], [0.5]] {
+            peaks.push(&frame);
+        }
+        assert_eq!(peaks.finish(), vec![0.0, 1.0]);
+
+        assert_eq!(PeakBuckets::new(0, 2).finish(), vec![0.0, 0.0]);
+    }
+}
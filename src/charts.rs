@@ -0,0 +1,351 @@
+use crate::commands::PoiseError;
+
+pub const CHART_WIDTH: u32 = 640;
+pub const CHART_HEIGHT: u32 = 240;
+pub const WAVEFORM_WIDTH: u32 = 400;
+pub const WAVEFORM_HEIGHT: u32 = 80;
+const CHART_PADDING: u32 = 16;
+const CHART_GRID_LINES: u32 = 4;
+
+const BACKGROUND_COLOR: Rgba = [43, 45, 49, 255];
+const GRID_COLOR: Rgba = [64, 66, 73, 255];
+const AXIS_COLOR: Rgba = [148, 155, 164, 255];
+const SERIES_COLOR: Rgba = [88, 101, 242, 255];
+
+pub type Rgba = [u8; 4];
+
+/// RGBA pixel buffer charts are drawn onto
+pub struct Canvas {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    pub fn new(width: u32, height: u32, background: Rgba) -> Self {
+        let pixels = background
+            .iter()
+            .copied()
+            .cycle()
+            .take((width * height * 4) as usize)
+            .collect();
+
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    #[allow(dead_code)]
+    pub fn pixel(&self, x: u32, y: u32) -> Option<Rgba> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+
+        let i = ((y * self.width + x) * 4) as usize;
+        self.pixels[i..i + 4].try_into().ok()
+    }
+
+    pub fn set_pixel(&mut self, x: i64, y: i64, color: Rgba) {
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+            return;
+        }
+
+        let i = ((y as u32 * self.width + x as u32) * 4) as usize;
+        self.pixels[i..i + 4].copy_from_slice(&color);
+    }
+
+    /// Fills rect spanning `[x0, x1)` by `[y0, y1)`, clipped to canvas
+    pub fn fill_rect(&mut self, x0: i64, y0: i64, x1: i64, y1: i64, color: Rgba) {
+        for y in y0.max(0)..y1.min(self.height as i64) {
+            for x in x0.max(0)..x1.min(self.width as i64) {
+                self.set_pixel(x, y, color);
+            }
+        }
+    }
+
+    /// Draws a line `thickness` pixels wide between two points
+    pub fn draw_line(&mut self, from: (i64, i64), to: (i64, i64), thickness: i64, color: Rgba) {
+        let (mut x, mut y) = from;
+        let (dx, dy) = ((to.0 - x).abs(), -(to.1 - y).abs());
+        let (sx, sy) = (if x < to.0 { 1 } else { -1 }, if y < to.1 { 1 } else { -1 });
+        let mut err = dx + dy;
+        let half = thickness / 2;
+
+        loop {
+            self.fill_rect(
+                x - half,
+                y - half,
+                x - half + thickness,
+                y - half + thickness,
+                color,
+            );
+
+            if (x, y) == to {
+                break;
+            }
+
+            let err2 = err * 2;
+            if err2 >= dy {
+                err += dy;
+                x += sx;
+            }
+            if err2 <= dx {
+                err += dx;
+                y += sy;
+            }
+        }
+    }
+
+    /// Encodes canvas as a PNG image
+    pub fn to_png(&self) -> Result<Vec<u8>, PoiseError> {
+        let mut buf = Vec::new();
+
+        let mut encoder = png::Encoder::new(&mut buf, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.pixels)?;
+        writer.finish()?;
+
+        Ok(buf)
+    }
+}
+
+/// Plot area of a chart, inside the padding
+struct PlotArea {
+    left: i64,
+    top: i64,
+    right: i64,
+    bottom: i64,
+}
+
+impl PlotArea {
+    fn new(canvas: &Canvas) -> Self {
+        Self {
+            left: CHART_PADDING as i64,
+            top: CHART_PADDING as i64,
+            right: (canvas.width() - CHART_PADDING) as i64,
+            bottom: (canvas.height() - CHART_PADDING) as i64,
+        }
+    }
+
+    fn width(&self) -> i64 {
+        self.right - self.left
+    }
+
+    fn height(&self) -> i64 {
+        self.bottom - self.top
+    }
+
+    /// Y coordinate of `value` scaled so `max` touches the top of the plot area
+    fn value_y(&self, value: u64, max: u64) -> i64 {
+        match max {
+            0 => self.bottom,
+            _ => self.bottom - (value as i64 * self.height()) / max as i64,
+        }
+    }
+
+    fn draw_grid(&self, canvas: &mut Canvas) {
+        for i in 0..CHART_GRID_LINES {
+            let y = self.top + (self.height() * i as i64) / CHART_GRID_LINES as i64;
+            canvas.draw_line((self.left, y), (self.right, y), 1, GRID_COLOR);
+        }
+
+        canvas.draw_line(
+            (self.left, self.bottom),
+            (self.right, self.bottom),
+            2,
+            AXIS_COLOR,
+        );
+        canvas.draw_line(
+            (self.left, self.top),
+            (self.left, self.bottom),
+            2,
+            AXIS_COLOR,
+        );
+    }
+}
+
+/// Vertical bar chart with one bar per value, scaled to the largest value
+pub fn bar_chart(values: &[u64]) -> Canvas {
+    let mut canvas = Canvas::new(CHART_WIDTH, CHART_HEIGHT, BACKGROUND_COLOR);
+    let area = PlotArea::new(&canvas);
+    area.draw_grid(&mut canvas);
+
+    let max = values.iter().max().copied().unwrap_or(0);
+    let slot = area.width() / values.len().max(1) as i64;
+    let gap = (slot / 5).max(1);
+
+    for (i, value) in values.iter().enumerate() {
+        let x0 = area.left + slot * i as i64 + gap;
+        canvas.fill_rect(
+            x0,
+            area.value_y(*value, max),
+            x0 + slot - gap * 2,
+            area.bottom,
+            SERIES_COLOR,
+        );
+    }
+
+    canvas
+}
+
+/// Line chart through values spread evenly across the x axis, scaled to the largest value
+pub fn line_chart(values: &[u64]) -> Canvas {
+    let mut canvas = Canvas::new(CHART_WIDTH, CHART_HEIGHT, BACKGROUND_COLOR);
+    let area = PlotArea::new(&canvas);
+    area.draw_grid(&mut canvas);
+
+    let max = values.iter().max().copied().unwrap_or(0);
+    let step = area.width() / (values.len() as i64 - 1).max(1);
+
+    let points: Vec<_> = values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| (area.left + step * i as i64, area.value_y(*value, max)))
+        .collect();
+
+    for pair in points.windows(2) {
+        canvas.draw_line(pair[0], pair[1], 3, SERIES_COLOR);
+    }
+    for (x, y) in points.iter() {
+        canvas.fill_rect(x - 3, y - 3, x + 4, y + 4, SERIES_COLOR);
+    }
+
+    canvas
+}
+
+/// Waveform with one column per peak, mirrored around the center line. Peaks range `0.0..=1.0`
+pub fn waveform(peaks: &[f32]) -> Canvas {
+    let mut canvas = Canvas::new(WAVEFORM_WIDTH, WAVEFORM_HEIGHT, BACKGROUND_COLOR);
+    let center = (WAVEFORM_HEIGHT / 2) as i64;
+    let column = (WAVEFORM_WIDTH as f32 / peaks.len().max(1) as f32).max(1.0);
+
+    canvas.draw_line((0, center), (WAVEFORM_WIDTH as i64, center), 1, GRID_COLOR);
+
+    for (i, peak) in peaks.iter().enumerate() {
+        let x0 = (i as f32 * column) as i64;
+        let x1 = ((i + 1) as f32 * column) as i64;
+        let half_height = (peak.clamp(0.0, 1.0) * (center - 2) as f32) as i64;
+
+        canvas.fill_rect(
+            x0,
+            center - half_height,
+            x1.max(x0 + 1),
+            center + half_height + 1,
+            SERIES_COLOR,
+        );
+    }
+
+    canvas
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode_png(buf: &[u8]) -> (png::OutputInfo, Vec<u8>) {
+        let mut reader = png::Decoder::new(buf).read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+
+        (info, pixels)
+    }
+
+    #[test]
+    fn canvas_png_test() {
+        let mut canvas = Canvas::new(4, 2, BACKGROUND_COLOR);
+        canvas.set_pixel(1, 1, SERIES_COLOR);
+        canvas.set_pixel(10, 10, SERIES_COLOR); // clipped
+
+        let (info, pixels) = decode_png(&canvas.to_png().unwrap());
+        assert_eq!((info.width, info.height), (4, 2));
+        assert_eq!(info.color_type, png::ColorType::Rgba);
+        assert_eq!(&pixels[0..4], &BACKGROUND_COLOR);
+        assert_eq!(&pixels[20..24], &SERIES_COLOR);
+    }
+
+    #[test]
+    fn bar_chart_test() {
+        let canvas = bar_chart(&[10, 5, 0]);
+        let area = PlotArea::new(&canvas);
+        let slot = area.width() / 3;
+        let center_x = |i: i64| (area.left + slot * i + slot / 2) as u32;
+
+        // tallest bar reaches the top, half bar reaches half way, empty bar is not drawn
+        assert_eq!(
+            canvas.pixel(center_x(0), area.top as u32 + 2),
+            Some(SERIES_COLOR)
+        );
+        assert_ne!(
+            canvas.pixel(center_x(1), area.top as u32 + 2),
+            Some(SERIES_COLOR)
+        );
+        assert_eq!(
+            canvas.pixel(center_x(1), (area.bottom - area.height() / 2 + 2) as u32),
+            Some(SERIES_COLOR)
+        );
+        assert_ne!(
+            canvas.pixel(center_x(2), area.bottom as u32 - 4),
+            Some(SERIES_COLOR)
+        );
+
+        let (info, _) = decode_png(&canvas.to_png().unwrap());
+        assert_eq!((info.width, info.height), (CHART_WIDTH, CHART_HEIGHT));
+    }
+
+    #[test]
+    fn waveform_test() {
+        let canvas = waveform(&[1.0, 0.0]);
+        let center = WAVEFORM_HEIGHT / 2;
+
+        assert_eq!(
+            (canvas.width(), canvas.height()),
+            (WAVEFORM_WIDTH, WAVEFORM_HEIGHT)
+        );
+        assert_eq!(canvas.pixel(10, 3), Some(SERIES_COLOR));
+        assert_eq!(canvas.pixel(10, WAVEFORM_HEIGHT - 3), Some(SERIES_COLOR));
+        assert_eq!(
+            canvas.pixel(WAVEFORM_WIDTH - 10, center),
+            Some(SERIES_COLOR)
+        );
+        assert_ne!(canvas.pixel(WAVEFORM_WIDTH - 10, 3), Some(SERIES_COLOR));
+    }
+
+    #[test]
+    fn line_chart_test() {
+        let canvas = line_chart(&[0, 4, 2]);
+        let area = PlotArea::new(&canvas);
+        let step = area.width() / 2;
+
+        assert_eq!(
+            canvas.pixel((area.left + step) as u32, area.top as u32),
+            Some(SERIES_COLOR)
+        );
+        assert_eq!(
+            canvas.pixel(
+                (area.left + step * 2) as u32,
+                (area.bottom - area.height() / 2) as u32
+            ),
+            Some(SERIES_COLOR)
+        );
+
+        // degenerate inputs still render
+        assert!(waveform(&[]).to_png().is_ok());
+        for values in [vec![], vec![0], vec![0, 0]] {
+            assert!(line_chart(&values).to_png().is_ok());
+            assert!(bar_chart(&values).to_png().is_ok());
+        }
+    }
+}
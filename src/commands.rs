@@ -1,13 +1,14 @@
-use poise::Modal;
+use poise::{CreateReply, Modal};
 use serenity::async_trait;
-use songbird::{Event, EventContext, EventHandler as VoiceEventHandler, TrackEvent};
+use songbird::{Event, EventContext, EventHandler as VoiceEventHandler};
 
 use crate::{
     audio,
     common::{LogResult, UserData},
-    db::{self, audio_table::AudioTableRowInsertBuilder, Tags},
+    db::{self, audio_table::AudioTableRowInsertBuilder, AuditAction, AuditLogEntry, Tags},
     helpers::{self, poise_check_msg, PoiseContextHelper, SongbirdHelper},
-    vars,
+    quotas::{self, QuotaUsage},
+    scheduler, sound_info, stats, triggers, vars,
 };
 
 pub type GenericError = Box<dyn std::error::Error + Send + Sync>;
@@ -22,54 +23,37 @@ pub async fn ping(ctx: PoiseContext<'_>) -> PoiseResult {
     Ok(())
 }
 
-#[poise::command(prefix_command, guild_only)]
-pub async fn join(ctx: PoiseContext<'_>) -> PoiseResult {
+#[poise::command(slash_command, prefix_command, guild_only)]
+pub async fn join(
+    ctx: PoiseContext<'_>,
+    #[description = "Voice channel to join. Your voice channel by default"]
+    #[channel_types("Voice", "Stage")]
+    channel: Option<serenity::all::GuildChannel>,
+) -> PoiseResult {
     log::info!("Bot joining voice channel...");
-    let (guild_id, connect_to) = helpers::get_author_voice_channel(&ctx)?;
-
-    log::info!("Bot will join Guild ID: {guild_id}, Voice Channel: {connect_to}");
-    let manager = helpers::poise_songbird_get(&ctx).await;
-
-    match manager.join(guild_id, connect_to).await {
-        Ok(handler_lock) => {
-            // Attach an event handler to see notifications of all track errors
-            let mut handler = handler_lock.lock().await;
-            handler.add_global_event(TrackEvent::Error.into(), TrackErrorNotifier);
-            log::info!("Bot joined Guild ID: {guild_id}, Voice Channel ID: {connect_to}");
-        }
-        Err(err) => {
-            log::error!(
-                "Bot failed to join Guild ID: {guild_id}, Voice Channel ID: {connect_to} - {}",
-                err.to_string()
-            )
-        }
-    }
-
-    if let Ok(settings) = ctx.data().settings_table().get_settings().log_err() {
-        if let Some(ref join_audio) = settings.join_audio {
-            log::info!("Detected join audio: {join_audio}. Attempting to play.");
-            match ctx
-                .data()
-                .audio_table()
-                .find_audio_row(db::UniqueAudioTableCol::Name(join_audio.clone()))
-            {
-                Some(row) => {
-                    log::debug!("bot join audio playing: {}", row.name);
-                    manager
-                        .play_audio(guild_id, connect_to, &row.audio_file)
-                        .await
-                        .log_err()
-                        .ok();
-                }
-                None => log::error!("Couldn't locate join audio"),
-            }
-        }
-    }
+    let (guild_id, connect_to) = match channel {
+        Some(channel) => (channel.guild_id, channel.id),
+        None => helpers::get_author_voice_channel(&ctx)?,
+    };
+
+    ctx.defer()
+        .await
+        .log_err_msg("Failed deferring `join`")
+        .ok();
+    helpers::join_voice_channel(
+        ctx.serenity_context(),
+        ctx.data(),
+        guild_id,
+        connect_to,
+        Some(ctx.author().id),
+    )
+    .await?;
 
+    poise_check_msg(ctx.say(format!("Joined <#{connect_to}>")).await);
     Ok(())
 }
 
-#[poise::command(prefix_command, guild_only)]
+#[poise::command(slash_command, prefix_command, guild_only)]
 pub async fn leave(ctx: PoiseContext<'_>) -> PoiseResult {
     let manager = helpers::poise_songbird_get(&ctx).await;
     let guild_id = ctx
@@ -81,29 +65,12 @@ pub async fn leave(ctx: PoiseContext<'_>) -> PoiseResult {
 
     match handler {
         Some(_handler) => {
-            // if leave audio set, play exit audio track
-            if let Ok(settings) = ctx.data().settings_table().get_settings().log_err() {
-                if let Some(ref leave_audio) = settings.leave_audio {
-                    log::info!("Detected leave audio: {leave_audio}. Attempting to play.");
-                    match ctx
-                        .data()
-                        .audio_table()
-                        .find_audio_row(db::UniqueAudioTableCol::Name(leave_audio.clone()))
-                    {
-                        Some(row) => {
-                            log::debug!("bot leave audio playing: {}", row.name);
-                            manager
-                                .play_audio_to_end(guild_id, channel_id, &row.audio_file)
-                                .await
-                                .log_err()
-                                .ok();
-                        }
-                        None => log::error!("Couldn't locate leave audio"),
-                    }
-                }
-            }
-
-            match manager.remove(guild_id).await {
+            // leave audio plays to its end before leaving
+            ctx.defer()
+                .await
+                .log_err_msg("Failed deferring `leave`")
+                .ok();
+            match helpers::leave_voice_channel(&manager, ctx.data(), guild_id, channel_id).await {
                 Ok(_) => poise_check_msg(ctx.say("Left voice channel").await),
                 Err(e) => poise_check_msg(ctx.say(format!("Failed {:?}", e)).await),
             }
@@ -114,6 +81,28 @@ pub async fn leave(ctx: PoiseContext<'_>) -> PoiseResult {
     Ok(())
 }
 
+#[poise::command(slash_command, guild_only, rename = "move")]
+pub async fn move_to(
+    ctx: PoiseContext<'_>,
+    #[description = "Voice channel to move to"]
+    #[channel_types("Voice", "Stage")]
+    channel: serenity::all::GuildChannel,
+) -> PoiseResult {
+    log::info!("Bot moving to voice channel: {}", channel.id);
+    let guild_id = ctx.guild_id().ok_or("No guild id found")?;
+
+    if !helpers::is_bot_in_voice_channel(ctx.serenity_context(), guild_id).await {
+        poise_check_msg(ctx.reply("Not in a voice channel").await);
+        return Ok(());
+    }
+
+    helpers::connect_voice_channel(ctx.serenity_context(), ctx.data(), guild_id, channel.id)
+        .await?;
+
+    poise_check_msg(ctx.reply(format!("Moved to <#{}>", channel.id)).await);
+    Ok(())
+}
+
 #[poise::command(slash_command, prefix_command, guild_only)]
 pub async fn play(
     ctx: PoiseContext<'_>,
@@ -130,45 +119,1369 @@ pub async fn play(
     let manager = ctx.songbird().await;
 
     let row = table.find_audio_row(db::UniqueAudioTableCol::Name(audio_track_name.clone()));
-    match row {
-        Some(row) => {
-            poise_check_msg(
-                ctx.reply(format!("Playing track `{audio_track_name}`"))
+    let (row, reply) = match row {
+        Some(row) => (row, format!("Playing track `{audio_track_name}`")),
+        None => {
+            let mut matches =
+                table.fuzzy_find_audio_rows(&audio_track_name, vars::FUZZY_MIN_SCORE, 5)?;
+            let close_match_count = matches
+                .iter()
+                .filter(|(_, score)| *score >= vars::FUZZY_PLAY_MIN_SCORE)
+                .count();
+
+            // only play the closest match when it's unambiguous
+            if close_match_count == 1 {
+                let (row, _) = matches.remove(0);
+                let reply = format!(
+                    "Audio Track '{audio_track_name}' not found. Playing closest match `{}`",
+                    row.name
+                );
+                (row, reply)
+            } else if !matches.is_empty() {
+                let rows: Vec<_> = matches.into_iter().map(|(row, _)| row).collect();
+                poise_check_msg(
+                    ctx.send(
+                        CreateReply::default()
+                            .content(format!(
+                                "Audio Track '{audio_track_name}' not found. Did you mean..."
+                            ))
+                            .components(vec![helpers::make_action_row(
+                                &ctx.data().db_connection(),
+                                guild_id,
+                                &rows,
+                            )]),
+                    )
                     .await,
-            );
+                );
+                return Ok(());
+            } else {
+                poise_check_msg(
+                    ctx.reply(format!("Audio Track '{audio_track_name}' not found"))
+                        .await,
+                );
+                return Ok(());
+            }
+        }
+    };
+
+    ctx.defer()
+        .await
+        .log_err_msg("Failed deferring `play`")
+        .ok();
+    helpers::auto_join_user_voice_channel(
+        ctx.serenity_context(),
+        ctx.data(),
+        guild_id,
+        ctx.author().id,
+    )
+    .await?;
+    let voice_channel_id =
+        helpers::get_bot_voice_channel_id(ctx.serenity_context(), guild_id).await;
 
-            manager
-                .play_audio(guild_id, channel_id, &row.audio_file)
-                .await?;
-            table.increment_play_count(row.id).log_err()?;
+    manager
+        .play_audio(guild_id, channel_id, &row.audio_file)
+        .await?;
+    // only replied once playing, so join & playback errors aren't preceded by it
+    poise_check_msg(ctx.reply(reply).await);
+    ctx.data()
+        .record_play(
+            db::PlayHistoryEntry::new(row.id, guild_id, db::PlaySource::Slash)
+                .user_id(Some(ctx.author().id))
+                .channel_id(voice_channel_id),
+        )
+        .log_err()?;
+
+    Ok(())
+}
+
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    subcommands(
+        "add_sound",
+        "remove_sound",
+        "display_sounds",
+        "edit_sound",
+        "set_join_audio",
+        "set_leave_audio",
+        "display_help",
+        "pin_sound",
+        "unpin_sound",
+        "display_quota",
+        "restore_sound",
+        "sound_info",
+        "favorite_sound",
+        "unfavorite_sound",
+        "bind_emoji",
+        "unbind_emoji"
+    )
+)]
+pub async fn sounds(_ctx: PoiseContext<'_>) -> PoiseResult {
+    log::warn!("/sounds command shouldn't be invoked direclty. It should just house sub commands");
+    Ok(())
+}
+
+#[poise::command(
+    slash_command,
+    guild_only,
+    subcommands(
+        "create_collection",
+        "add_to_collection",
+        "remove_from_collection",
+        "delete_collection",
+        "list_collections",
+        "display_collection",
+        "play_collection"
+    )
+)]
+pub async fn collections(_ctx: PoiseContext<'_>) -> PoiseResult {
+    log::warn!(
+        "/collections command shouldn't be invoked direclty. It should just house sub commands"
+    );
+    Ok(())
+}
+
+/// Finds collection of command's guild by name, erroring if it doesn't exist
+fn find_collection(ctx: &PoiseContext<'_>, name: &str) -> Result<db::CollectionRow, PoiseError> {
+    let guild_id = ctx.guild_id().ok_or("No guild id found")?;
+
+    ctx.data()
+        .collections_table()
+        .find_collection(guild_id.into(), name)
+        .ok_or(format!("Unable to locate collection '{name}'").into())
+}
+
+#[poise::command(slash_command, guild_only, rename = "create")]
+pub async fn create_collection(
+    ctx: PoiseContext<'_>,
+    #[description = "Collection name. ie: Star Wars"]
+    #[min_length = 1]
+    #[max_length = 32]
+    name: String,
+) -> PoiseResult {
+    log::info!("Creating collection - {name}");
+
+    let guild_id = ctx.guild_id().ok_or("No guild id found")?;
+    let table = ctx.data().collections_table();
+
+    // collection names are embedded in button custom ids, which are `::` delimited
+    if name.contains(':') {
+        return Err("Collection names can't contain `:`".into());
+    }
+
+    if table.find_collection(guild_id.into(), &name).is_some() {
+        return Err(format!("Collection `{name}` already exists").into());
+    }
+
+    table.create_collection(guild_id.into(), &name, Some(ctx.author().id.into()))?;
+
+    helpers::write_audit_log(
+        ctx.http(),
+        ctx.data(),
+        AuditLogEntry::new(guild_id, ctx.author(), AuditAction::CollectionChange, &name)
+            .after(Some("created")),
+    )
+    .await;
+
+    poise_check_msg(
+        ctx.reply(format!(
+            "Created collection `{name}`. Add sounds with `/collections add`"
+        ))
+        .await,
+    );
+
+    Ok(())
+}
+
+#[poise::command(slash_command, guild_only, rename = "add")]
+pub async fn add_to_collection(
+    ctx: PoiseContext<'_>,
+    #[description = "Collection to add sound to"]
+    #[autocomplete = "helpers::autocomplete_collection_name"]
+    collection: String,
+    #[rename = "track"]
+    #[description = "Audio track to add. Plays after sounds already in the collection"]
+    #[autocomplete = "helpers::autocomplete_audio_track_name"]
+    audio_track_name: String,
+) -> PoiseResult {
+    log::info!("Adding audio track {audio_track_name} to collection {collection}");
+
+    let guild_id = ctx.guild_id().ok_or("No guild id found")?;
+    let collection = find_collection(&ctx, &collection)?;
+    let row = ctx
+        .data()
+        .audio_table()
+        .find_audio_row(db::UniqueAudioTableCol::Name(audio_track_name.clone()))
+        .ok_or(format!("Unable to locate audio track '{audio_track_name}'"))
+        .log_err()?;
+
+    let content = match ctx
+        .data()
+        .collections_table()
+        .add_audio(collection.id, row.id)?
+    {
+        true => {
+            helpers::write_audit_log(
+                ctx.http(),
+                ctx.data(),
+                AuditLogEntry::new(
+                    guild_id,
+                    ctx.author(),
+                    AuditAction::CollectionChange,
+                    &collection.name,
+                )
+                .after(Some(format!("+ {}", row.name))),
+            )
+            .await;
+
+            format!(
+                "Added `{audio_track_name}` to collection `{}`",
+                collection.name
+            )
         }
-        None => poise_check_msg(
-            ctx.reply(format!("Audio Track '{audio_track_name}' not found"))
-                .await,
+        false => format!(
+            "`{audio_track_name}` is already in collection `{}`",
+            collection.name
         ),
+    };
+
+    poise_check_msg(ctx.reply(content).await);
+
+    Ok(())
+}
+
+#[poise::command(slash_command, guild_only, rename = "remove")]
+pub async fn remove_from_collection(
+    ctx: PoiseContext<'_>,
+    #[description = "Collection to remove sound from"]
+    #[autocomplete = "helpers::autocomplete_collection_name"]
+    collection: String,
+    #[rename = "track"]
+    #[description = "Audio track to remove"]
+    #[autocomplete = "helpers::autocomplete_audio_track_name"]
+    audio_track_name: String,
+) -> PoiseResult {
+    log::info!("Removing audio track {audio_track_name} from collection {collection}");
+
+    let guild_id = ctx.guild_id().ok_or("No guild id found")?;
+    let collection = find_collection(&ctx, &collection)?;
+    let row = ctx
+        .data()
+        .audio_table()
+        .find_audio_row(db::UniqueAudioTableCol::Name(audio_track_name.clone()))
+        .ok_or(format!("Unable to locate audio track '{audio_track_name}'"))
+        .log_err()?;
+
+    let content = match ctx
+        .data()
+        .collections_table()
+        .remove_audio(collection.id, row.id)?
+    {
+        true => {
+            helpers::write_audit_log(
+                ctx.http(),
+                ctx.data(),
+                AuditLogEntry::new(
+                    guild_id,
+                    ctx.author(),
+                    AuditAction::CollectionChange,
+                    &collection.name,
+                )
+                .after(Some(format!("- {}", row.name))),
+            )
+            .await;
+
+            format!(
+                "Removed `{audio_track_name}` from collection `{}`",
+                collection.name
+            )
+        }
+        false => format!(
+            "`{audio_track_name}` isn't in collection `{}`",
+            collection.name
+        ),
+    };
+
+    poise_check_msg(ctx.reply(content).await);
+
+    Ok(())
+}
+
+#[poise::command(slash_command, guild_only, rename = "delete")]
+pub async fn delete_collection(
+    ctx: PoiseContext<'_>,
+    #[description = "Collection to delete. Its sounds are kept"]
+    #[autocomplete = "helpers::autocomplete_collection_name"]
+    collection: String,
+) -> PoiseResult {
+    log::info!("Deleting collection - {collection}");
+
+    let guild_id = ctx.guild_id().ok_or("No guild id found")?;
+    let collection = find_collection(&ctx, &collection)?;
+    ctx.data()
+        .collections_table()
+        .delete_collection(collection.id)?;
+
+    helpers::write_audit_log(
+        ctx.http(),
+        ctx.data(),
+        AuditLogEntry::new(
+            guild_id,
+            ctx.author(),
+            AuditAction::CollectionChange,
+            &collection.name,
+        )
+        .after(Some("deleted")),
+    )
+    .await;
+
+    poise_check_msg(
+        ctx.reply(format!("Deleted collection `{}`", collection.name))
+            .await,
+    );
+
+    Ok(())
+}
+
+#[poise::command(slash_command, guild_only, rename = "list")]
+pub async fn list_collections(ctx: PoiseContext<'_>) -> PoiseResult {
+    log::info!("`/collections list` slash command received");
+
+    let guild_id = ctx.guild_id().ok_or("No guild id found")?;
+    let collections = ctx
+        .data()
+        .collections_table()
+        .list_collections(guild_id.into())?;
+
+    let mut content = String::from("### Collections\n");
+    if collections.is_empty() {
+        content.push_str("No collections yet. Create one with `/collections create`");
+    }
+
+    for (collection, count) in collections.iter() {
+        let line = format!("- `{}` - {count} sounds\n", collection.name);
+
+        // discord message content limit
+        if content.len() + line.len() > 2000 {
+            break;
+        }
+        content.push_str(&line);
+    }
+
+    poise_check_msg(ctx.reply(content).await);
+
+    Ok(())
+}
+
+#[poise::command(slash_command, guild_only, rename = "display")]
+pub async fn display_collection(
+    ctx: PoiseContext<'_>,
+    #[description = "Collection to display"]
+    #[autocomplete = "helpers::autocomplete_collection_name"]
+    collection: String,
+) -> PoiseResult {
+    log::info!("`/collections display` slash command received");
+
+    let guild_id = ctx.guild_id().ok_or("No guild id found")?;
+    let collection = find_collection(&ctx, &collection)?;
+    let mut paginator = db::AudioTablePaginatorBuilder::collection_template(
+        ctx.data().db_connection(),
+        collection.id,
+    )
+    .page_limit(ctx.data().config.max_page_size)
+    .build();
+
+    let reply_msg = helpers::make_display_message(
+        &mut paginator,
+        guild_id,
+        helpers::DisplayType::Collection,
+        Some(collection.name.clone()),
+        ctx.data().config.enable_ephemeral_controls,
+    )?;
+
+    ctx.send(reply_msg.into()).await.log_err_msg(format!(
+        "Failed replying `/collections display: {}`",
+        collection.name
+    ))?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, guild_only, rename = "play")]
+pub async fn play_collection(
+    ctx: PoiseContext<'_>,
+    #[description = "Collection to play, in order"]
+    #[autocomplete = "helpers::autocomplete_collection_name"]
+    collection: String,
+) -> PoiseResult {
+    log::info!("Playing collection {collection}...");
+
+    let guild_id = ctx.guild_id().ok_or("No guild id found")?;
+    let collection = find_collection(&ctx, &collection)?;
+
+    poise_check_msg(
+        ctx.reply(format!("Playing collection `{}`", collection.name))
+            .await,
+    );
+
+    helpers::play_collection(
+        ctx.serenity_context(),
+        ctx.data(),
+        guild_id,
+        &collection,
+        ctx.author().id,
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[poise::command(
+    slash_command,
+    guild_only,
+    subcommands("list_tags", "rename_tag", "merge_tags")
+)]
+pub async fn tags(_ctx: PoiseContext<'_>) -> PoiseResult {
+    log::warn!("/tags command shouldn't be invoked direclty. It should just house sub commands");
+    Ok(())
+}
+
+#[poise::command(slash_command, guild_only, rename = "list")]
+pub async fn list_tags(ctx: PoiseContext<'_>) -> PoiseResult {
+    log::info!("`/tags list` slash command received");
+
+    let tags = ctx.data().tags_table().list_tags(None)?;
+
+    let mut content = String::from("### Tags\n");
+    if tags.is_empty() {
+        content.push_str("No tags yet. Tag sounds with `/sounds edit`");
+    }
+
+    for (tag, count) in tags.iter() {
+        let line = format!("- `{tag}` - {count} sounds\n");
+
+        // discord message content limit
+        if content.len() + line.len() > 2000 {
+            break;
+        }
+        content.push_str(&line);
+    }
+
+    poise_check_msg(ctx.reply(content).await);
+
+    Ok(())
+}
+
+#[poise::command(slash_command, guild_only, rename = "rename")]
+pub async fn rename_tag(
+    ctx: PoiseContext<'_>,
+    #[description = "Tag to rename"]
+    #[autocomplete = "helpers::autocomplete_tag_name"]
+    old: String,
+    #[description = "New tag name"] new: String,
+) -> PoiseResult {
+    log::info!("Renaming tag {old} to {new}");
+
+    let guild_id = ctx.guild_id().ok_or("No guild id found")?;
+    let count = ctx.data().tags_table().rename_tag(&old, &new)?;
+
+    helpers::write_audit_log(
+        ctx.http(),
+        ctx.data(),
+        AuditLogEntry::new(guild_id, ctx.author(), AuditAction::TagChange, &old)
+            .before(Some(&old))
+            .after(Some(&new)),
+    )
+    .await;
+
+    poise_check_msg(
+        ctx.reply(format!("Renamed tag `{old}` to `{new}` on {count} sounds"))
+            .await,
+    );
+
+    Ok(())
+}
+
+#[poise::command(slash_command, guild_only, rename = "merge")]
+pub async fn merge_tags(
+    ctx: PoiseContext<'_>,
+    #[description = "Tag to merge. It's removed once merged"]
+    #[autocomplete = "helpers::autocomplete_tag_name"]
+    from: String,
+    #[description = "Tag to merge into"]
+    #[autocomplete = "helpers::autocomplete_tag_name"]
+    into: String,
+) -> PoiseResult {
+    log::info!("Merging tag {from} into {into}");
+
+    let guild_id = ctx.guild_id().ok_or("No guild id found")?;
+    let count = ctx.data().tags_table().merge_tags(&from, &into)?;
+
+    helpers::write_audit_log(
+        ctx.http(),
+        ctx.data(),
+        AuditLogEntry::new(guild_id, ctx.author(), AuditAction::TagChange, &from)
+            .before(Some(&from))
+            .after(Some(&into)),
+    )
+    .await;
+
+    poise_check_msg(
+        ctx.reply(format!(
+            "Merged tag `{from}` into `{into}` on {count} sounds"
+        ))
+        .await,
+    );
+
+    Ok(())
+}
+
+#[poise::command(
+    slash_command,
+    guild_only,
+    subcommands("set_intro", "clear_intro", "intro_settings")
+)]
+pub async fn intro(_ctx: PoiseContext<'_>) -> PoiseResult {
+    log::warn!("/intro command shouldn't be invoked direclty. It should just house sub commands");
+    Ok(())
+}
+
+#[poise::command(slash_command, guild_only, rename = "set")]
+pub async fn set_intro(
+    ctx: PoiseContext<'_>,
+    #[description = "Audio track played when you join the bot's voice channel"]
+    #[rename = "track"]
+    #[autocomplete = "helpers::autocomplete_audio_track_name"]
+    audio_track_name: String,
+) -> PoiseResult {
+    set_member_sound(ctx, db::MemberSound::Intro, audio_track_name).await
+}
+
+#[poise::command(slash_command, guild_only, rename = "clear")]
+pub async fn clear_intro(ctx: PoiseContext<'_>) -> PoiseResult {
+    clear_member_sound(ctx, db::MemberSound::Intro).await
+}
+
+#[poise::command(slash_command, guild_only, subcommands("set_outro", "clear_outro"))]
+pub async fn outro(_ctx: PoiseContext<'_>) -> PoiseResult {
+    log::warn!("/outro command shouldn't be invoked direclty. It should just house sub commands");
+    Ok(())
+}
+
+#[poise::command(slash_command, guild_only, rename = "set")]
+pub async fn set_outro(
+    ctx: PoiseContext<'_>,
+    #[description = "Audio track played when you leave the bot's voice channel"]
+    #[rename = "track"]
+    #[autocomplete = "helpers::autocomplete_audio_track_name"]
+    audio_track_name: String,
+) -> PoiseResult {
+    set_member_sound(ctx, db::MemberSound::Outro, audio_track_name).await
+}
+
+#[poise::command(slash_command, guild_only, rename = "clear")]
+pub async fn clear_outro(ctx: PoiseContext<'_>) -> PoiseResult {
+    clear_member_sound(ctx, db::MemberSound::Outro).await
+}
+
+/// Name of author's current intro or outro audio track, if any
+fn find_member_sound_name(
+    ctx: &PoiseContext<'_>,
+    guild_id: serenity::all::GuildId,
+    sound: db::MemberSound,
+) -> Option<String> {
+    let audio_id = ctx
+        .data()
+        .member_sounds_table()
+        .find_member_sounds(guild_id.into(), ctx.author().id.into())
+        .ok()??
+        .audio_id(sound)?;
+
+    ctx.data()
+        .audio_table()
+        .find_audio_row(db::UniqueAudioTableCol::Id(audio_id))
+        .map(|row| row.name)
+}
+
+/// Sets author's intro or outro, if the track fits the guild's max intro duration
+async fn set_member_sound(
+    ctx: PoiseContext<'_>,
+    sound: db::MemberSound,
+    audio_track_name: String,
+) -> PoiseResult {
+    let kind = sound.as_str();
+    log::info!("Setting {kind}: {audio_track_name}");
+
+    let guild_id = ctx.guild_id().ok_or("No guild id found")?;
+    let row = ctx
+        .data()
+        .audio_table()
+        .find_audio_row(db::UniqueAudioTableCol::Name(audio_track_name.clone()))
+        .ok_or(format!("Unable to locate audio track '{audio_track_name}'"))
+        .log_err()?;
+
+    let settings = ctx
+        .data()
+        .guild_settings_table()
+        .get_guild_settings(guild_id.into())?;
+
+    // tracks added before durations were recorded may not have been backfilled yet
+    let duration_ms = match row.duration_ms {
+        Some(duration_ms) => duration_ms,
+        None => {
+            let track_info = audio::probe_audio_track(row.audio_file.as_path_buf())?;
+            ctx.data()
+                .audio_table()
+                .update_audio_row_duration(row.id, track_info.duration)?;
+            track_info.duration.as_millis() as u64
+        }
+    };
+
+    let max_duration = settings.max_intro_duration();
+    if duration_ms > settings.max_intro_duration_ms {
+        poise_check_msg(
+            ctx.reply(format!(
+                "`{}` is too long for an {kind}. It can be up to {}",
+                row.name,
+                stats::format_duration(max_duration)
+            ))
+            .await,
+        );
+        return Ok(());
+    }
+
+    let before = find_member_sound_name(&ctx, guild_id, sound);
+    ctx.data().member_sounds_table().set_member_sound(
+        sound,
+        guild_id.into(),
+        ctx.author().id.into(),
+        Some(row.id),
+    )?;
+
+    helpers::write_audit_log(
+        ctx.http(),
+        ctx.data(),
+        AuditLogEntry::new(
+            guild_id,
+            ctx.author(),
+            AuditAction::SettingsChange,
+            format!("member {kind}"),
+        )
+        .before(before)
+        .after(Some(&row.name)),
+    )
+    .await;
+
+    let mut content = format!("Your {kind} is set to `{}`", row.name);
+    if !settings.is_member_sound_enabled(sound) {
+        content.push_str(&format!(
+            ". It won't play until {kind}s are enabled on this server"
+        ));
+    }
+    poise_check_msg(ctx.reply(content).await);
+
+    Ok(())
+}
+
+async fn clear_member_sound(ctx: PoiseContext<'_>, sound: db::MemberSound) -> PoiseResult {
+    let kind = sound.as_str();
+    log::info!("Clearing {kind}");
+
+    let guild_id = ctx.guild_id().ok_or("No guild id found")?;
+    let before = find_member_sound_name(&ctx, guild_id, sound);
+    ctx.data().member_sounds_table().set_member_sound(
+        sound,
+        guild_id.into(),
+        ctx.author().id.into(),
+        None,
+    )?;
+
+    if before.is_some() {
+        helpers::write_audit_log(
+            ctx.http(),
+            ctx.data(),
+            AuditLogEntry::new(
+                guild_id,
+                ctx.author(),
+                AuditAction::SettingsChange,
+                format!("member {kind}"),
+            )
+            .before(before),
+        )
+        .await;
+    }
+
+    poise_check_msg(ctx.reply(format!("Your {kind} is cleared")).await);
+
+    Ok(())
+}
+
+#[poise::command(slash_command, guild_only, rename = "settings")]
+pub async fn intro_settings(
+    ctx: PoiseContext<'_>,
+    #[description = "Play members' intros when they join the bot's voice channel"] enabled: Option<
+        bool,
+    >,
+    #[description = "Play members' outros when they leave the bot's voice channel"]
+    #[rename = "outros-enabled"]
+    outros_enabled: Option<bool>,
+    #[description = "Longest an intro or outro plays for, in seconds"]
+    #[rename = "max-duration"]
+    #[min = 1]
+    #[max = 60]
+    max_duration_secs: Option<u64>,
+    #[description = "Least time between a member's intros, or outros, in seconds"]
+    #[rename = "cooldown"]
+    #[max = 86400]
+    cooldown_secs: Option<u64>,
+) -> PoiseResult {
+    log::info!("`/intro settings` slash command received");
+
+    let guild_id = ctx.guild_id().ok_or("No guild id found")?;
+    let table = ctx.data().guild_settings_table();
+    let mut settings = table.get_guild_settings(guild_id.into())?;
+    let before = settings.clone();
+
+    if let Some(enabled) = enabled {
+        settings.intros_enabled = enabled;
+    }
+    if let Some(enabled) = outros_enabled {
+        settings.outros_enabled = enabled;
+    }
+    if let Some(secs) = max_duration_secs {
+        settings.max_intro_duration_ms = secs * 1000;
+    }
+    if let Some(secs) = cooldown_secs {
+        settings.intro_cooldown_secs = secs;
+    }
+
+    let format_settings = |settings: &db::GuildSettingsRow| {
+        format!(
+            "enabled: {}, outros enabled: {}, max duration: {}, cooldown: {}",
+            settings.intros_enabled,
+            settings.outros_enabled,
+            stats::format_duration(settings.max_intro_duration()),
+            stats::format_duration(std::time::Duration::from_secs(settings.intro_cooldown_secs))
+        )
+    };
+
+    if settings != before {
+        table.update_guild_settings(&settings)?;
+
+        helpers::write_audit_log(
+            ctx.http(),
+            ctx.data(),
+            AuditLogEntry::new(guild_id, ctx.author(), AuditAction::SettingsChange, "intro")
+                .before(Some(format_settings(&before)))
+                .after(Some(format_settings(&settings))),
+        )
+        .await;
+    }
+
+    poise_check_msg(
+        ctx.reply(format!("Intro settings - {}", format_settings(&settings)))
+            .await,
+    );
+
+    Ok(())
+}
+
+#[poise::command(slash_command, guild_only, rename = "auto-join")]
+pub async fn auto_join(
+    ctx: PoiseContext<'_>,
+    #[description = "Join voice channels when the first member enters"] enabled: Option<bool>,
+    #[description = "Only auto join this voice channel, and others allowed"]
+    #[channel_types("Voice", "Stage")]
+    allow: Option<serenity::all::GuildChannel>,
+    #[description = "Stop auto joining this voice channel"]
+    #[channel_types("Voice", "Stage")]
+    disallow: Option<serenity::all::GuildChannel>,
+) -> PoiseResult {
+    log::info!("`/auto-join` slash command received");
+
+    let guild_id = ctx.guild_id().ok_or("No guild id found")?;
+    let table = ctx.data().guild_settings_table();
+    let mut settings = table.get_guild_settings(guild_id.into())?;
+    let before = settings.clone();
+
+    if let Some(enabled) = enabled {
+        settings.auto_join_enabled = enabled;
+    }
+    if let Some(channel) = allow {
+        if !settings.auto_join_channel_ids.contains(&channel.id.get()) {
+            settings.auto_join_channel_ids.push(channel.id.get());
+        }
+    }
+    if let Some(channel) = disallow {
+        settings
+            .auto_join_channel_ids
+            .retain(|id| *id != channel.id.get());
+    }
+
+    let format_settings = |settings: &db::GuildSettingsRow| {
+        let channels = match settings.auto_join_channel_ids.is_empty() {
+            true => "any".to_string(),
+            false => settings
+                .auto_join_channel_ids
+                .iter()
+                .map(|id| format!("<#{id}>"))
+                .collect::<Vec<_>>()
+                .join(", "),
+        };
+        format!(
+            "enabled: {}, channels: {channels}",
+            settings.auto_join_enabled
+        )
+    };
+
+    if settings != before {
+        table.update_guild_settings(&settings)?;
+
+        helpers::write_audit_log(
+            ctx.http(),
+            ctx.data(),
+            AuditLogEntry::new(
+                guild_id,
+                ctx.author(),
+                AuditAction::SettingsChange,
+                "auto-join",
+            )
+            .before(Some(format_settings(&before)))
+            .after(Some(format_settings(&settings))),
+        )
+        .await;
+    }
+
+    poise_check_msg(
+        ctx.reply(format!(
+            "Auto join settings - {}",
+            format_settings(&settings)
+        ))
+        .await,
+    );
+
+    Ok(())
+}
+
+#[poise::command(slash_command, guild_only, rename = "idle-timeout")]
+pub async fn idle_timeout(
+    ctx: PoiseContext<'_>,
+    #[description = "Leave voice channel after nothing has played for this many minutes. 0 never leaves"]
+    #[max = 1440]
+    minutes: Option<u64>,
+) -> PoiseResult {
+    log::info!("`/idle-timeout` slash command received");
+
+    let guild_id = ctx.guild_id().ok_or("No guild id found")?;
+    let table = ctx.data().guild_settings_table();
+    let mut settings = table.get_guild_settings(guild_id.into())?;
+    let before = settings.clone();
+
+    if let Some(minutes) = minutes {
+        settings.idle_timeout_secs = match minutes {
+            0 => None,
+            minutes => Some(minutes * 60),
+        };
+    }
+
+    let format_settings = |settings: &db::GuildSettingsRow| match settings.idle_timeout_secs {
+        Some(secs) => stats::format_duration(std::time::Duration::from_secs(secs)),
+        None => "never".to_string(),
+    };
+
+    if settings != before {
+        table.update_guild_settings(&settings)?;
+
+        helpers::write_audit_log(
+            ctx.http(),
+            ctx.data(),
+            AuditLogEntry::new(
+                guild_id,
+                ctx.author(),
+                AuditAction::SettingsChange,
+                "idle-timeout",
+            )
+            .before(Some(format_settings(&before)))
+            .after(Some(format_settings(&settings))),
+        )
+        .await;
+    }
+
+    poise_check_msg(
+        ctx.reply(format!("Idle timeout - {}", format_settings(&settings)))
+            .await,
+    );
+
+    Ok(())
+}
+
+#[poise::command(
+    slash_command,
+    guild_only,
+    subcommands("add_schedule", "list_schedules", "remove_schedule")
+)]
+pub async fn schedule(_ctx: PoiseContext<'_>) -> PoiseResult {
+    log::warn!(
+        "/schedule command shouldn't be invoked direclty. It should just house sub commands"
+    );
+    Ok(())
+}
+
+/// Formats when cron expression is next due, as a discord timestamp
+fn format_next_run(cron: &str) -> String {
+    match scheduler::next_run(cron, chrono::Utc::now()) {
+        Ok(Some(next_run)) => format!("<t:{}:f>", next_run.timestamp()),
+        _ => "never".to_string(),
+    }
+}
+
+/// Describes schedule for audit log entries
+fn format_schedule_audit(cron: &str, audio_name: &str, channel_id: Option<u64>) -> String {
+    let channel = match channel_id {
+        Some(channel_id) => format!("channel {channel_id}"),
+        None => "bot's voice channel".to_string(),
+    };
+    format!("{cron} {audio_name} in {channel}")
+}
+
+#[poise::command(slash_command, guild_only, rename = "add")]
+pub async fn add_schedule(
+    ctx: PoiseContext<'_>,
+    #[description = "UTC cron: minute hour day-of-month month day-of-week. ie: 0 21 * * FRI"]
+    #[max_length = 100]
+    cron: String,
+    #[rename = "track"]
+    #[description = "Audio track to play"]
+    #[autocomplete = "helpers::autocomplete_audio_track_name"]
+    audio_track_name: String,
+    #[description = "Voice channel to play in. Whichever voice channel the bot is in by default"]
+    #[channel_types("Voice", "Stage")]
+    channel: Option<serenity::all::GuildChannel>,
+) -> PoiseResult {
+    log::info!("Adding schedule - {cron} {audio_track_name}");
+
+    let guild_id = ctx.guild_id().ok_or("No guild id found")?;
+    let cron = cron.split_whitespace().collect::<Vec<_>>().join(" ");
+    scheduler::parse_cron(&cron)?;
+
+    let row = ctx
+        .data()
+        .audio_table()
+        .find_audio_row(db::UniqueAudioTableCol::Name(audio_track_name.clone()))
+        .ok_or(format!("Unable to locate audio track '{audio_track_name}'"))
+        .log_err()?;
+
+    let channel_id = channel.map(|channel| channel.id);
+    let schedule_id = ctx.data().schedules_table().insert_schedule(
+        guild_id.into(),
+        row.id,
+        &cron,
+        channel_id.map(|channel_id| channel_id.into()),
+        Some(ctx.author().id.into()),
+    )?;
+
+    helpers::write_audit_log(
+        ctx.http(),
+        ctx.data(),
+        AuditLogEntry::new(
+            guild_id,
+            ctx.author(),
+            AuditAction::SettingsChange,
+            format!("schedule #{schedule_id}"),
+        )
+        .after(Some(format_schedule_audit(
+            &cron,
+            &row.name,
+            channel_id.map(|channel_id| channel_id.into()),
+        ))),
+    )
+    .await;
+
+    let channel = match channel_id {
+        Some(channel_id) => format!("<#{channel_id}>"),
+        None => "the bot's voice channel".to_string(),
+    };
+    poise_check_msg(
+        ctx.reply(format!(
+            "Scheduled `{}` as #{schedule_id} `{cron}` in {channel}. Next plays {}",
+            row.name,
+            format_next_run(&cron)
+        ))
+        .await,
+    );
+
+    Ok(())
+}
+
+#[poise::command(slash_command, guild_only, rename = "list")]
+pub async fn list_schedules(ctx: PoiseContext<'_>) -> PoiseResult {
+    log::info!("`/schedule list` slash command received");
+
+    let guild_id = ctx.guild_id().ok_or("No guild id found")?;
+    let schedules = ctx
+        .data()
+        .schedules_table()
+        .list_schedules(guild_id.into())?;
+
+    let mut content = String::from("### Schedules\n");
+    if schedules.is_empty() {
+        content.push_str("No schedules yet. Add one with `/schedule add`");
+    }
+
+    for (schedule, audio_name) in schedules.iter() {
+        let channel = match schedule.channel_id {
+            Some(channel_id) => format!("<#{channel_id}>"),
+            None => "bot's voice channel".to_string(),
+        };
+        let line = format!(
+            "- #{} `{}` `{audio_name}` in {channel} - next {}\n",
+            schedule.id,
+            schedule.cron,
+            format_next_run(&schedule.cron)
+        );
+
+        // discord message content limit
+        if content.len() + line.len() > 2000 {
+            break;
+        }
+        content.push_str(&line);
+    }
+
+    poise_check_msg(ctx.reply(content).await);
+
+    Ok(())
+}
+
+#[poise::command(slash_command, guild_only, rename = "remove")]
+pub async fn remove_schedule(
+    ctx: PoiseContext<'_>,
+    #[description = "Schedule number, as listed by /schedule list"] id: i64,
+) -> PoiseResult {
+    log::info!("Removing schedule - {id}");
+
+    let guild_id = ctx.guild_id().ok_or("No guild id found")?;
+    let table = ctx.data().schedules_table();
+    let schedule = table
+        .list_schedules(guild_id.into())?
+        .into_iter()
+        .find(|(schedule, _)| schedule.id == id);
+
+    let content = match table.delete_schedule(guild_id.into(), id)? {
+        true => {
+            helpers::write_audit_log(
+                ctx.http(),
+                ctx.data(),
+                AuditLogEntry::new(
+                    guild_id,
+                    ctx.author(),
+                    AuditAction::SettingsChange,
+                    format!("schedule #{id}"),
+                )
+                .before(schedule.map(|(schedule, audio_name)| {
+                    format_schedule_audit(&schedule.cron, &audio_name, schedule.channel_id)
+                })),
+            )
+            .await;
+
+            format!("Removed schedule #{id}")
+        }
+        false => format!("Unable to locate schedule #{id}"),
+    };
+
+    poise_check_msg(ctx.reply(content).await);
+
+    Ok(())
+}
+
+#[poise::command(
+    slash_command,
+    guild_only,
+    subcommands("add_trigger", "list_triggers", "remove_trigger")
+)]
+pub async fn triggers(_ctx: PoiseContext<'_>) -> PoiseResult {
+    log::warn!(
+        "/triggers command shouldn't be invoked direclty. It should just house sub commands"
+    );
+    Ok(())
+}
+
+/// Describes trigger for audit log entries
+fn format_trigger_audit(
+    kind: db::TriggerKind,
+    pattern: &str,
+    channel_id: u64,
+    audio_name: &str,
+    cooldown_secs: u64,
+) -> String {
+    format!(
+        "{} {pattern} in channel {channel_id} plays {audio_name} - {cooldown_secs}s cooldown",
+        kind.as_str()
+    )
+}
+
+#[poise::command(slash_command, guild_only, rename = "add")]
+pub async fn add_trigger(
+    ctx: PoiseContext<'_>,
+    #[description = "Text channel whose messages are matched"]
+    #[channel_types("Text")]
+    channel: serenity::all::GuildChannel,
+    #[description = "Match a whole word, a phrase anywhere in the message, or a regex. Ignoring case"]
+    kind: db::TriggerKind,
+    #[description = "Word, phrase or regex to match"]
+    #[max_length = 100]
+    pattern: String,
+    #[rename = "track"]
+    #[description = "Audio track to play"]
+    #[autocomplete = "helpers::autocomplete_audio_track_name"]
+    audio_track_name: String,
+    #[description = "Least seconds between plays. 30 by default"]
+    #[max = 86400]
+    cooldown: Option<u64>,
+) -> PoiseResult {
+    log::info!("Adding trigger - {pattern} {audio_track_name}");
+
+    if !ctx.data().config.enable_text_triggers {
+        // without the message content intent, discord sends empty message content
+        return Err("Text triggers are disabled. Set `DISCORD_BOT_ENABLE_TEXT_TRIGGERS=1` to match message content".into());
+    }
+
+    let guild_id = ctx.guild_id().ok_or("No guild id found")?;
+    let pattern = pattern.trim().to_string();
+    triggers::compile_pattern(kind, &pattern)?;
+
+    let row = ctx
+        .data()
+        .audio_table()
+        .find_audio_row(db::UniqueAudioTableCol::Name(audio_track_name.clone()))
+        .ok_or(format!("Unable to locate audio track '{audio_track_name}'"))
+        .log_err()?;
+
+    let cooldown_secs = cooldown.unwrap_or(vars::DEFAULT_TRIGGER_COOLDOWN_SECS);
+    let trigger_id = ctx
+        .data()
+        .triggers_table()
+        .insert_trigger(&db::TriggerInsert {
+            guild_id: guild_id.into(),
+            channel_id: channel.id.into(),
+            kind,
+            pattern: pattern.clone(),
+            audio_id: row.id,
+            cooldown_secs,
+            author_id: Some(ctx.author().id.into()),
+        })?;
+    ctx.data().trigger_regex_cache.remove(trigger_id);
+
+    helpers::write_audit_log(
+        ctx.http(),
+        ctx.data(),
+        AuditLogEntry::new(
+            guild_id,
+            ctx.author(),
+            AuditAction::SettingsChange,
+            format!("trigger #{trigger_id}"),
+        )
+        .after(Some(format_trigger_audit(
+            kind,
+            &pattern,
+            channel.id.into(),
+            &row.name,
+            cooldown_secs,
+        ))),
+    )
+    .await;
+
+    poise_check_msg(
+        ctx.reply(format!(
+            "Added trigger #{trigger_id}. {} `{pattern}` in <#{}> plays `{}`",
+            kind.as_str(),
+            channel.id,
+            row.name
+        ))
+        .await,
+    );
+
+    Ok(())
+}
+
+#[poise::command(slash_command, guild_only, rename = "list")]
+pub async fn list_triggers(
+    ctx: PoiseContext<'_>,
+    #[description = "Only list triggers of this text channel"]
+    #[channel_types("Text")]
+    channel: Option<serenity::all::GuildChannel>,
+) -> PoiseResult {
+    log::info!("`/triggers list` slash command received");
+
+    let guild_id = ctx.guild_id().ok_or("No guild id found")?;
+    let triggers = ctx
+        .data()
+        .triggers_table()
+        .list_triggers(guild_id.into(), channel.map(|channel| channel.id.into()))?;
+
+    let mut content = String::from("### Triggers\n");
+    if triggers.is_empty() {
+        content.push_str("No triggers yet. Add one with `/triggers add`");
+    }
+
+    for (trigger, audio_name) in triggers.iter() {
+        let line = format!(
+            "- #{} {} `{}` in <#{}> plays `{audio_name}` - {}s cooldown\n",
+            trigger.id,
+            trigger.kind.as_str(),
+            trigger.pattern,
+            trigger.channel_id,
+            trigger.cooldown_secs
+        );
+
+        // discord message content limit
+        if content.len() + line.len() > 2000 {
+            break;
+        }
+        content.push_str(&line);
+    }
+
+    poise_check_msg(ctx.reply(content).await);
+
+    Ok(())
+}
+
+#[poise::command(slash_command, guild_only, rename = "remove")]
+pub async fn remove_trigger(
+    ctx: PoiseContext<'_>,
+    #[description = "Trigger number, as listed by /triggers list"] id: i64,
+) -> PoiseResult {
+    log::info!("Removing trigger - {id}");
+
+    let guild_id = ctx.guild_id().ok_or("No guild id found")?;
+    let table = ctx.data().triggers_table();
+    let trigger = table
+        .list_triggers(guild_id.into(), None)?
+        .into_iter()
+        .find(|(trigger, _)| trigger.id == id);
+
+    let content = match table.delete_trigger(guild_id.into(), id)? {
+        true => {
+            ctx.data().trigger_regex_cache.remove(id);
+
+            helpers::write_audit_log(
+                ctx.http(),
+                ctx.data(),
+                AuditLogEntry::new(
+                    guild_id,
+                    ctx.author(),
+                    AuditAction::SettingsChange,
+                    format!("trigger #{id}"),
+                )
+                .before(trigger.map(|(trigger, audio_name)| {
+                    format_trigger_audit(
+                        trigger.kind,
+                        &trigger.pattern,
+                        trigger.channel_id,
+                        &audio_name,
+                        trigger.cooldown_secs,
+                    )
+                })),
+            )
+            .await;
+
+            format!("Removed trigger #{id}")
+        }
+        false => format!("Unable to locate trigger #{id}"),
+    };
+
+    poise_check_msg(ctx.reply(content).await);
+
+    Ok(())
+}
+
+#[poise::command(slash_command, guild_only, subcommands("create_panel", "remove_panel"))]
+pub async fn panel(_ctx: PoiseContext<'_>) -> PoiseResult {
+    log::warn!("/panel command shouldn't be invoked direclty. It should just house sub commands");
+    Ok(())
+}
+
+#[poise::command(slash_command, guild_only, rename = "create")]
+pub async fn create_panel(
+    ctx: PoiseContext<'_>,
+    #[description = "Text channel to post the panel in. This channel by default"]
+    #[channel_types("Text")]
+    channel: Option<serenity::all::GuildChannel>,
+) -> PoiseResult {
+    let guild_id = ctx.guild_id().ok_or("No guild id found")?;
+    let channel_id = channel
+        .map(|channel| channel.id)
+        .unwrap_or(ctx.channel_id());
+    log::info!("Creating soundboard panel in channel - {channel_id}");
+
+    let previous = ctx.data().panels_table().find_panel(guild_id.into())?;
+
+    helpers::post_sound_panel(
+        ctx.http(),
+        ctx.data(),
+        guild_id,
+        channel_id,
+        Some(ctx.author().id.into()),
+    )
+    .await?;
+
+    // guilds have one panel, so the previous one would otherwise go stale
+    if let Some(previous) = &previous {
+        serenity::all::ChannelId::new(previous.channel_id)
+            .delete_message(ctx.http(), previous.message_id)
+            .await
+            .log_err_msg("Failed deleting previous soundboard panel")
+            .ok();
     }
 
+    helpers::write_audit_log(
+        ctx.http(),
+        ctx.data(),
+        AuditLogEntry::new(guild_id, ctx.author(), AuditAction::SettingsChange, "panel")
+            .before(previous.map(|previous| format!("channel {}", previous.channel_id)))
+            .after(Some(format!("channel {channel_id}"))),
+    )
+    .await;
+
+    poise_check_msg(
+        ctx.send(
+            CreateReply::default()
+                .content(format!("Posted soundboard panel in <#{channel_id}>"))
+                .ephemeral(true),
+        )
+        .await,
+    );
+
     Ok(())
 }
 
-#[poise::command(
-    slash_command,
-    prefix_command,
-    guild_only,
-    subcommands(
-        "add_sound",
-        "remove_sound",
-        "display_sounds",
-        "edit_sound",
-        "set_join_audio",
-        "set_leave_audio",
-        "display_help",
-        "pin_sound",
-        "unpin_sound"
+#[poise::command(slash_command, guild_only, rename = "remove")]
+pub async fn remove_panel(ctx: PoiseContext<'_>) -> PoiseResult {
+    log::info!("Removing soundboard panel");
+
+    let guild_id = ctx.guild_id().ok_or("No guild id found")?;
+    let Some(panel) = ctx.data().panels_table().find_panel(guild_id.into())? else {
+        poise_check_msg(ctx.reply("This server has no soundboard panel").await);
+        return Ok(());
+    };
+
+    ctx.data().panels_table().delete_panel(guild_id.into())?;
+    serenity::all::ChannelId::new(panel.channel_id)
+        .delete_message(ctx.http(), panel.message_id)
+        .await
+        .log_err_msg("Failed deleting soundboard panel message")
+        .ok();
+
+    helpers::write_audit_log(
+        ctx.http(),
+        ctx.data(),
+        AuditLogEntry::new(guild_id, ctx.author(), AuditAction::SettingsChange, "panel")
+            .before(Some(format!("channel {}", panel.channel_id))),
     )
-)]
-pub async fn sounds(_ctx: PoiseContext<'_>) -> PoiseResult {
-    log::warn!("/sounds command shouldn't be invoked direclty. It should just house sub commands");
+    .await;
+
+    poise_check_msg(ctx.reply("Removed soundboard panel").await);
+
     Ok(())
 }
 
@@ -211,6 +1524,21 @@ struct AddSoundModal {
 
 #[poise::command(slash_command, guild_only, rename = "add")]
 pub async fn add_sound(ctx: PoiseAppContext<'_>) -> PoiseResult {
+    let guild_id = ctx.guild_id().ok_or("No guild id found")?;
+
+    // check quotas before user fills out form & audio download starts
+    if let Err(err) = ctx.data().check_upload_quota(guild_id, ctx.author().id, 0) {
+        poise_check_msg(
+            ctx.send(
+                CreateReply::default()
+                    .content(err.to_string())
+                    .ephemeral(true),
+            )
+            .await,
+        );
+        return Ok(());
+    }
+
     let data = AddSoundModal::execute(ctx)
         .await?
         .ok_or("AddSoundModal not set")
@@ -219,7 +1547,13 @@ pub async fn add_sound(ctx: PoiseAppContext<'_>) -> PoiseResult {
     log::info!("Adding sound. Name: {}, Url: {}", data.name, data.url);
 
     let table = ctx.data.audio_table();
-    let row = table.find_audio_row(db::UniqueAudioTableCol::Name(data.name.clone()));
+    let col = db::UniqueAudioTableCol::Name(data.name.clone());
+    if table.find_trashed_audio_row(&col).is_some() {
+        return Err("Can't add sound. It's in the trash, use `/sounds restore` instead".into())
+            .log_err();
+    }
+
+    let row = table.find_audio_row(&col);
 
     match row {
         Some(_) => {
@@ -228,14 +1562,40 @@ pub async fn add_sound(ctx: PoiseAppContext<'_>) -> PoiseResult {
         None => {
             let temp_audio_file = audio::download_audio_url_temp(&data.url).await?;
 
+            // re-check library size quota now the file's size is known
+            let incoming_bytes = std::fs::metadata(&temp_audio_file)
+                .log_err_msg("Failed reading downloaded audio file size")?
+                .len();
+            if let Err(err) =
+                ctx.data()
+                    .check_upload_quota(guild_id, ctx.author().id, incoming_bytes)
+            {
+                std::fs::remove_file(&temp_audio_file)
+                    .log_err_msg("Failed removing downloaded audio file")
+                    .ok();
+                poise_check_msg(
+                    ctx.send(
+                        CreateReply::default()
+                            .content(err.to_string())
+                            .ephemeral(true),
+                    )
+                    .await,
+                );
+                return Ok(());
+            }
+
             // validate audio track (codec type, length, etc)
-            audio::AudioFileValidator::default()
+            let track_info = audio::AudioFileValidator::default()
                 .max_audio_duration(ctx.data().config.max_audio_file_duration)
                 .reject_uuid_files(false)
                 .validate(&temp_audio_file)?;
 
             // move track to sounds dir
             let audio_file = ctx.data().move_file_to_audio_dir(&temp_audio_file)?;
+            audio_file
+                .write_waveform()
+                .log_err_msg("Failed to write waveform")
+                .ok();
             let tags: Tags = match data.tags {
                 Some(val) => Tags::from(val),
                 None => Tags::new(),
@@ -247,10 +1607,20 @@ pub async fn add_sound(ctx: PoiseAppContext<'_>) -> PoiseResult {
                         .author_global_name(ctx.author().global_name.clone())
                         .author_id(Some(ctx.author().id.into()))
                         .author_name(Some(ctx.author().name.clone()))
+                        .guild_id(Some(guild_id.into()))
+                        .duration(Some(track_info.duration))
+                        .file_size(Some(incoming_bytes))
                         .tags(tags)
                         .build(),
                 )
                 .log_err()?;
+
+            helpers::write_audit_log(
+                ctx.http(),
+                ctx.data(),
+                AuditLogEntry::new(guild_id, ctx.author(), AuditAction::Add, &data.name),
+            )
+            .await;
         }
     }
 
@@ -258,6 +1628,42 @@ pub async fn add_sound(ctx: PoiseAppContext<'_>) -> PoiseResult {
         ctx.reply(format!("Added sound `{}` to soundboard", data.name))
             .await,
     );
+    helpers::refresh_sound_panels(ctx.http(), ctx.data()).await;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, guild_only, rename = "quota")]
+pub async fn display_quota(ctx: PoiseContext<'_>) -> PoiseResult {
+    log::info!("Displaying upload quota for user: {}", ctx.author().name);
+
+    let guild_id = ctx.guild_id().ok_or("No guild id found")?;
+    let config = &ctx.data().config;
+    let usage = QuotaUsage::fetch(
+        &ctx.data().audio_table(),
+        guild_id.into(),
+        ctx.author().id.into(),
+    )
+    .log_err()?;
+
+    let text = format!(
+        "\
+### Upload Quota
+- Sounds added by you: `{sounds}`
+- Sounds added by you today: `{today}`
+- Soundboard library size: `{library}`",
+        sounds = quotas::format_usage(usage.user_sound_count, config.max_sounds_per_user),
+        today = quotas::format_usage(usage.user_uploads_today, config.max_uploads_per_day),
+        library = quotas::format_usage(
+            quotas::format_bytes(usage.guild_library_size),
+            config.max_library_bytes_per_guild.map(quotas::format_bytes)
+        ),
+    );
+
+    poise_check_msg(
+        ctx.send(CreateReply::default().content(text).ephemeral(true))
+            .await,
+    );
 
     Ok(())
 }
@@ -271,18 +1677,161 @@ pub async fn remove_sound(
     audio_track_name: String,
 ) -> PoiseResult {
     log::info!("Removing audio track - {audio_track_name}");
+    let guild_id = ctx.guild_id().ok_or("No guild id found")?;
     let table = ctx.data().audio_table();
 
-    table.delete_audio_row(db::UniqueAudioTableCol::Name(audio_track_name.clone()))?;
+    table.trash_audio_row(db::UniqueAudioTableCol::Name(audio_track_name.clone()))?;
+    helpers::write_audit_log(
+        ctx.http(),
+        ctx.data(),
+        AuditLogEntry::new(
+            guild_id,
+            ctx.author(),
+            AuditAction::Remove,
+            &audio_track_name,
+        ),
+    )
+    .await;
+
     poise_check_msg(
-        ctx.reply(format!("Removed audio track `{audio_track_name}`"))
-            .await,
+        ctx.reply(format!(
+            "Removed audio track `{audio_track_name}`. It can be restored for {} days with `/sounds restore`",
+            ctx.data().config.trash_retention_days
+        ))
+        .await,
     );
+    helpers::refresh_sound_panels(ctx.http(), ctx.data()).await;
 
     log::info!("Audio track removed {audio_track_name}");
     Ok(())
 }
 
+#[poise::command(slash_command, guild_only, rename = "restore")]
+pub async fn restore_sound(
+    ctx: PoiseContext<'_>,
+    #[rename = "track"]
+    #[description = "Removed audio track to restore"]
+    #[autocomplete = "helpers::autocomplete_trashed_audio_track_name"]
+    audio_track_name: String,
+) -> PoiseResult {
+    log::info!("Restoring audio track - {audio_track_name}");
+    let guild_id = ctx.guild_id().ok_or("No guild id found")?;
+    let table = ctx.data().audio_table();
+
+    table.restore_audio_row(db::UniqueAudioTableCol::Name(audio_track_name.clone()))?;
+    helpers::write_audit_log(
+        ctx.http(),
+        ctx.data(),
+        AuditLogEntry::new(
+            guild_id,
+            ctx.author(),
+            AuditAction::Restore,
+            &audio_track_name,
+        ),
+    )
+    .await;
+
+    poise_check_msg(
+        ctx.reply(format!("Restored audio track `{audio_track_name}`"))
+            .await,
+    );
+    helpers::refresh_sound_panels(ctx.http(), ctx.data()).await;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, guild_only, rename = "info")]
+pub async fn sound_info(
+    ctx: PoiseContext<'_>,
+    #[rename = "track"]
+    #[description = "Audio track to show info for"]
+    #[autocomplete = "helpers::autocomplete_audio_track_name"]
+    audio_track_name: String,
+) -> PoiseResult {
+    log::info!("Displaying audio track info - {audio_track_name}");
+
+    let row = ctx
+        .data()
+        .audio_table()
+        .find_audio_row(db::UniqueAudioTableCol::Name(audio_track_name.clone()))
+        .ok_or(format!("Unable to locate audio track '{audio_track_name}'"))
+        .log_err()?;
+
+    ctx.send(sound_info::make_sound_info_message(&row).into())
+        .await
+        .log_err_msg("Failed replying `/sounds info`")?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, guild_only, rename = "favorite")]
+pub async fn favorite_sound(
+    ctx: PoiseContext<'_>,
+    #[rename = "track"]
+    #[description = "Audio track to add to your favorites"]
+    #[autocomplete = "helpers::autocomplete_audio_track_name"]
+    audio_track_name: String,
+) -> PoiseResult {
+    log::info!("Favoriting audio track - {audio_track_name}");
+
+    let row = ctx
+        .data()
+        .audio_table()
+        .find_audio_row(db::UniqueAudioTableCol::Name(audio_track_name.clone()))
+        .ok_or(format!("Unable to locate audio track '{audio_track_name}'"))
+        .log_err()?;
+
+    let content = match ctx
+        .data()
+        .favorites_table()
+        .add_favorite(ctx.author().id.into(), row.id)?
+    {
+        true => format!("Added `{audio_track_name}` to your favorites"),
+        false => format!("`{audio_track_name}` is already one of your favorites"),
+    };
+
+    poise_check_msg(
+        ctx.send(CreateReply::default().content(content).ephemeral(true))
+            .await,
+    );
+
+    Ok(())
+}
+
+#[poise::command(slash_command, guild_only, rename = "unfavorite")]
+pub async fn unfavorite_sound(
+    ctx: PoiseContext<'_>,
+    #[rename = "track"]
+    #[description = "Audio track to remove from your favorites"]
+    #[autocomplete = "helpers::autocomplete_favorite_audio_track_name"]
+    audio_track_name: String,
+) -> PoiseResult {
+    log::info!("Unfavoriting audio track - {audio_track_name}");
+
+    let row = ctx
+        .data()
+        .audio_table()
+        .find_audio_row(db::UniqueAudioTableCol::Name(audio_track_name.clone()))
+        .ok_or(format!("Unable to locate audio track '{audio_track_name}'"))
+        .log_err()?;
+
+    let content = match ctx
+        .data()
+        .favorites_table()
+        .remove_favorite(ctx.author().id.into(), row.id)?
+    {
+        true => format!("Removed `{audio_track_name}` from your favorites"),
+        false => format!("`{audio_track_name}` isn't one of your favorites"),
+    };
+
+    poise_check_msg(
+        ctx.send(CreateReply::default().content(content).ephemeral(true))
+            .await,
+    );
+
+    Ok(())
+}
+
 #[poise::command(slash_command, guild_only, rename = "pin")]
 pub async fn pin_sound(
     ctx: PoiseContext<'_>,
@@ -293,13 +1842,22 @@ pub async fn pin_sound(
 ) -> PoiseResult {
     log::info!("Pinning audio track - {audio_track_name}");
 
+    let guild_id = ctx.guild_id().ok_or("No guild id found")?;
     let table = ctx.data().audio_table();
     table
         .update_audio_row_pin_by_name(&audio_track_name, true)
         .log_err()?;
 
+    helpers::write_audit_log(
+        ctx.http(),
+        ctx.data(),
+        AuditLogEntry::new(guild_id, ctx.author(), AuditAction::Pin, &audio_track_name),
+    )
+    .await;
+
     ctx.reply(format!("Pinned audio track `{audio_track_name}`"))
         .await?;
+    helpers::refresh_sound_panels(ctx.http(), ctx.data()).await;
 
     Ok(())
 }
@@ -314,13 +1872,154 @@ pub async fn unpin_sound(
 ) -> PoiseResult {
     log::info!("Unpinning audio track - {audio_track_name}");
 
+    let guild_id = ctx.guild_id().ok_or("No guild id found")?;
     let table = ctx.data().audio_table();
     table
         .update_audio_row_pin_by_name(&audio_track_name, false)
         .log_err()?;
 
+    helpers::write_audit_log(
+        ctx.http(),
+        ctx.data(),
+        AuditLogEntry::new(
+            guild_id,
+            ctx.author(),
+            AuditAction::Unpin,
+            &audio_track_name,
+        ),
+    )
+    .await;
+
     ctx.reply(format!("Unpinned audio track `{audio_track_name}`"))
         .await?;
+    helpers::refresh_sound_panels(ctx.http(), ctx.data()).await;
+
+    Ok(())
+}
+
+/// Errors if emoji is already bound to another audio track, rather than silently moving it
+fn check_emoji_unbound(
+    data: &UserData,
+    guild_id: serenity::all::GuildId,
+    emoji: &serenity::all::ReactionType,
+    audio_id: i64,
+) -> Result<(), PoiseError> {
+    match data
+        .emoji_bindings_table()
+        .find_audio_id(guild_id.into(), emoji)?
+    {
+        Some(bound_id) if bound_id != audio_id => {
+            let bound_name = data
+                .audio_table()
+                .find_audio_row(db::UniqueAudioTableCol::Id(bound_id))
+                .map(|row| row.name)
+                .unwrap_or(bound_id.to_string());
+            Err(format!(
+                "Emoji {emoji} is already bound to `{bound_name}`. Unbind it first with `/sounds unbind-emoji`"
+            )
+            .into())
+        }
+        _ => Ok(()),
+    }
+}
+
+#[poise::command(slash_command, guild_only, rename = "bind-emoji")]
+pub async fn bind_emoji(
+    ctx: PoiseContext<'_>,
+    #[rename = "track"]
+    #[description = "Audio track to bind emoji to"]
+    #[autocomplete = "helpers::autocomplete_audio_track_name"]
+    audio_track_name: String,
+    #[description = "Emoji shown on the sound's button. Reacting to messages with it plays the sound"]
+    #[max_length = 100]
+    emoji: String,
+) -> PoiseResult {
+    log::info!("Binding emoji {emoji} to audio track - {audio_track_name}");
+
+    let guild_id = ctx.guild_id().ok_or("No guild id found")?;
+    let emoji = helpers::parse_emoji(&emoji)?;
+    let row = ctx
+        .data()
+        .audio_table()
+        .find_audio_row(db::UniqueAudioTableCol::Name(audio_track_name.clone()))
+        .ok_or(format!("Unable to locate audio track '{audio_track_name}'"))
+        .log_err()?;
+
+    check_emoji_unbound(ctx.data(), guild_id, &emoji, row.id)?;
+    let old_emoji = ctx
+        .data()
+        .emoji_bindings_table()
+        .find_emoji(guild_id.into(), row.id)?;
+    ctx.data()
+        .emoji_bindings_table()
+        .bind_emoji(guild_id.into(), &emoji, row.id)?;
+
+    helpers::write_audit_log(
+        ctx.http(),
+        ctx.data(),
+        AuditLogEntry::new(guild_id, ctx.author(), AuditAction::Edit, &row.name)
+            .before(old_emoji)
+            .after(Some(emoji.to_string())),
+    )
+    .await;
+
+    poise_check_msg(
+        ctx.reply(format!("Bound {emoji} to audio track `{}`", row.name))
+            .await,
+    );
+    helpers::refresh_sound_panels(ctx.http(), ctx.data()).await;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, guild_only, rename = "unbind-emoji")]
+pub async fn unbind_emoji(
+    ctx: PoiseContext<'_>,
+    #[rename = "track"]
+    #[description = "Audio track to unbind emoji from"]
+    #[autocomplete = "helpers::autocomplete_audio_track_name"]
+    audio_track_name: String,
+) -> PoiseResult {
+    log::info!("Unbinding emoji from audio track - {audio_track_name}");
+
+    let guild_id = ctx.guild_id().ok_or("No guild id found")?;
+    let row = ctx
+        .data()
+        .audio_table()
+        .find_audio_row(db::UniqueAudioTableCol::Name(audio_track_name.clone()))
+        .ok_or(format!("Unable to locate audio track '{audio_track_name}'"))
+        .log_err()?;
+
+    let Some(emoji) = ctx
+        .data()
+        .emoji_bindings_table()
+        .find_emoji(guild_id.into(), row.id)?
+    else {
+        poise_check_msg(
+            ctx.reply(format!("Audio track `{}` has no emoji", row.name))
+                .await,
+        );
+        return Ok(());
+    };
+
+    ctx.data()
+        .emoji_bindings_table()
+        .unbind_emoji(guild_id.into(), row.id)?;
+
+    helpers::write_audit_log(
+        ctx.http(),
+        ctx.data(),
+        AuditLogEntry::new(guild_id, ctx.author(), AuditAction::Edit, &row.name)
+            .before(Some(emoji.clone()))
+            .after(None::<String>),
+    )
+    .await;
+
+    poise_check_msg(
+        ctx.reply(format!("Unbound {emoji} from audio track `{}`", row.name))
+            .await,
+    );
+    helpers::refresh_sound_panels(ctx.http(), ctx.data()).await;
 
     Ok(())
 }
@@ -328,19 +2027,24 @@ pub async fn unpin_sound(
 #[poise::command(slash_command, guild_only, rename = "display")]
 pub async fn display_sounds(
     ctx: PoiseContext<'_>,
-    #[description = "Filter displayed sounds by names & tags"] search: Option<String>,
+    #[description = "Filter by names & tags. ie: tag:starwars -vader by:@user duration:<3s pinned:true"]
+    search: Option<String>,
 ) -> PoiseResult {
     log::info!("`/sounds display` slash command received");
 
+    let guild_id = ctx.guild_id().ok_or("No guild id found")?;
     match search.as_ref() {
         Some(search) => {
-            let mut paginator =
-                db::AudioTablePaginatorBuilder::search_template(ctx.data().db_connection(), search)
-                    .page_limit(ctx.data().config.max_page_size)
-                    .build();
+            let mut paginator = db::AudioTablePaginatorBuilder::search_template(
+                ctx.data().db_connection(),
+                search,
+            )?
+            .page_limit(ctx.data().config.max_page_size)
+            .build();
 
             let reply_msg = helpers::make_display_message(
                 &mut paginator,
+                guild_id,
                 helpers::DisplayType::Search,
                 Some(search.clone()),
                 ctx.data().config.enable_ephemeral_controls,
@@ -359,6 +2063,7 @@ pub async fn display_sounds(
 
             let reply_msg = helpers::make_display_message(
                 &mut paginator,
+                guild_id,
                 helpers::DisplayType::RecentlyAdded,
                 None,
                 ctx.data().config.enable_ephemeral_controls,
@@ -370,11 +2075,9 @@ pub async fn display_sounds(
         }
     }
 
-    ctx.send(
-        helpers::make_sound_controls_message(ctx.data().config.enable_ephemeral_controls).into(),
-    )
-    .await
-    .log_err_msg(format!("`/sounds display` failed sending sound controls"))?;
+    ctx.send(helpers::make_sound_controls_message(ctx.data()).into())
+        .await
+        .log_err_msg("`/sounds display` failed sending sound controls")?;
 
     Ok(())
 }
@@ -389,6 +2092,17 @@ struct EditSoundModal {
     #[name = "Tags"]
     #[max_length = 1024]
     tags: Option<String>,
+    #[name = "Aliases (comma separated)"]
+    #[max_length = 1024]
+    aliases: Option<String>,
+    #[name = "Emoji"]
+    #[placeholder = "📯 or <:name:id>"]
+    #[max_length = 100]
+    emoji: Option<String>,
+    #[name = "Button style"]
+    #[placeholder = "primary, secondary, success or danger"]
+    #[max_length = 10]
+    button_style: Option<String>,
 }
 
 #[poise::command(slash_command, guild_only, rename = "edit")]
@@ -401,18 +2115,26 @@ pub async fn edit_sound(
 ) -> PoiseResult {
     log::info!("Editing audio track - {audio_track_name}");
 
+    let guild_id = ctx.guild_id().ok_or("No guild id found")?;
     let table = ctx.data().audio_table();
 
     let mut row = table
         .find_audio_row(db::UniqueAudioTableCol::Name(audio_track_name.clone()))
         .ok_or(format!("Unable to locate audio track '{audio_track_name}'"))
         .log_err()?;
+    let old_emoji = ctx
+        .data()
+        .emoji_bindings_table()
+        .find_emoji(guild_id.into(), row.id)?;
 
     let data = EditSoundModal::execute_with_defaults(
         ctx,
         EditSoundModal {
-            name: audio_track_name.clone(),
+            name: row.name.clone(),
             tags: Some(row.tags.to_string()),
+            aliases: Some(row.aliases.join(", ")),
+            emoji: old_emoji.clone(),
+            button_style: row.button_style.map(|style| style.as_str().to_string()),
         },
     )
     .await?;
@@ -425,10 +2147,121 @@ pub async fn edit_sound(
                 None => Tags::new(),
             };
 
-            row.name = data.name.clone();
-            row.tags = tags;
+            let aliases: Vec<String> = data
+                .aliases
+                .unwrap_or_default()
+                .split(',')
+                .map(|alias| alias.trim().to_string())
+                .filter(|alias| !alias.is_empty())
+                .collect();
+
+            let emoji = match data.emoji.as_deref().map(str::trim) {
+                Some(val) if !val.is_empty() => Some(helpers::parse_emoji(val)?),
+                _ => None,
+            };
+
+            let button_style = match data.button_style.as_deref().map(str::trim) {
+                Some(val) if !val.is_empty() => Some(db::SoundButtonStyle::try_from(val)?),
+                _ => None,
+            };
+
+            if data.name != row.name {
+                let col = db::UniqueAudioTableCol::Name(data.name.clone());
+                if table.find_trashed_audio_row(&col).is_some() {
+                    return Err(format!(
+                        "Can't rename sound. Sound `{}` is in the trash, restore it with `/sounds restore` or wait for it to be purged",
+                        data.name
+                    )
+                    .into())
+                    .log_err();
+                }
+                if table.find_audio_row(&col).is_some() {
+                    return Err(format!(
+                        "Can't rename sound. Sound `{}` already exists",
+                        data.name
+                    )
+                    .into())
+                    .log_err();
+                }
+            }
+
+            if let Some(emoji) = &emoji {
+                check_emoji_unbound(ctx.data(), guild_id, emoji, row.id)?;
+            }
+
+            let old_name = std::mem::replace(&mut row.name, data.name.clone());
+            let old_tags = std::mem::replace(&mut row.tags, tags).to_string();
+            let old_aliases = std::mem::replace(&mut row.aliases, aliases).join(", ");
+            let old_button_style = std::mem::replace(&mut row.button_style, button_style);
 
             table.update_audio_row(&row).log_err()?;
+
+            if old_name != row.name {
+                helpers::write_audit_log(
+                    ctx.http(),
+                    ctx.data(),
+                    AuditLogEntry::new(guild_id, ctx.author(), AuditAction::Rename, &row.name)
+                        .before(Some(old_name))
+                        .after(Some(&row.name)),
+                )
+                .await;
+            }
+
+            if old_tags != row.tags.to_string() {
+                helpers::write_audit_log(
+                    ctx.http(),
+                    ctx.data(),
+                    AuditLogEntry::new(guild_id, ctx.author(), AuditAction::TagChange, &row.name)
+                        .before(Some(old_tags))
+                        .after(Some(row.tags.to_string())),
+                )
+                .await;
+            }
+
+            if old_aliases != row.aliases.join(", ") {
+                helpers::write_audit_log(
+                    ctx.http(),
+                    ctx.data(),
+                    AuditLogEntry::new(guild_id, ctx.author(), AuditAction::Edit, &row.name)
+                        .before(Some(old_aliases))
+                        .after(Some(row.aliases.join(", "))),
+                )
+                .await;
+            }
+
+            if old_button_style != row.button_style {
+                helpers::write_audit_log(
+                    ctx.http(),
+                    ctx.data(),
+                    AuditLogEntry::new(guild_id, ctx.author(), AuditAction::Edit, &row.name)
+                        .before(old_button_style.map(|style| style.as_str()))
+                        .after(row.button_style.map(|style| style.as_str())),
+                )
+                .await;
+            }
+
+            // after auditing the row update, so a failed bind can't leave it unaudited
+            let emoji_bindings_table = ctx.data().emoji_bindings_table();
+            match &emoji {
+                Some(emoji) => emoji_bindings_table.bind_emoji(guild_id.into(), emoji, row.id)?,
+                None => {
+                    emoji_bindings_table.unbind_emoji(guild_id.into(), row.id)?;
+                }
+            }
+            let new_emoji = emoji.map(|emoji| emoji.to_string());
+
+            if old_emoji != new_emoji {
+                helpers::write_audit_log(
+                    ctx.http(),
+                    ctx.data(),
+                    AuditLogEntry::new(guild_id, ctx.author(), AuditAction::Edit, &row.name)
+                        .before(old_emoji)
+                        .after(new_emoji),
+                )
+                .await;
+            }
+
+            helpers::refresh_sound_panels(ctx.http(), ctx.data()).await;
         }
         None => log::info!("No audo track to update"),
     }
@@ -446,8 +2279,10 @@ pub async fn set_join_audio(
 ) -> PoiseResult {
     log::info!("Setting join audio: {audio_track_name:?}");
 
+    let guild_id = ctx.guild_id().ok_or("No guild id found")?;
     let table = ctx.data().settings_table();
     let mut settings = table.get_settings().log_err()?;
+    let before = settings.join_audio.clone();
 
     match audio_track_name.as_str() {
         "NONE" => {
@@ -461,6 +2296,20 @@ pub async fn set_join_audio(
             poise_check_msg(ctx.reply(format!("Bot join audio set to {val}")).await);
         }
     }
+
+    helpers::write_audit_log(
+        ctx.http(),
+        ctx.data(),
+        AuditLogEntry::new(
+            guild_id,
+            ctx.author(),
+            AuditAction::SettingsChange,
+            "join-audio",
+        )
+        .before(before)
+        .after(settings.join_audio.clone()),
+    )
+    .await;
     Ok(())
 }
 
@@ -474,8 +2323,10 @@ pub async fn set_leave_audio(
 ) -> PoiseResult {
     log::info!("Setting leave audio: {audio_track_name:?}");
 
+    let guild_id = ctx.guild_id().ok_or("No guild id found")?;
     let table = ctx.data().settings_table();
     let mut settings = table.get_settings().log_err()?;
+    let before = settings.leave_audio.clone();
 
     match audio_track_name.as_str() {
         "NONE" => {
@@ -490,6 +2341,118 @@ pub async fn set_leave_audio(
         }
     }
 
+    helpers::write_audit_log(
+        ctx.http(),
+        ctx.data(),
+        AuditLogEntry::new(
+            guild_id,
+            ctx.author(),
+            AuditAction::SettingsChange,
+            "leave-audio",
+        )
+        .before(before)
+        .after(settings.leave_audio.clone()),
+    )
+    .await;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, guild_only)]
+pub async fn audit(
+    ctx: PoiseContext<'_>,
+    #[description = "Only show this kind of change"] action: Option<AuditAction>,
+    #[description = "Only show changes made by this user"] user: Option<serenity::all::User>,
+) -> PoiseResult {
+    log::info!("`/audit` slash command received");
+
+    let guild_id = ctx.guild_id().ok_or("No guild id found")?;
+    let filter = db::AuditLogFilter {
+        guild_id: guild_id.into(),
+        action,
+        actor_id: user.map(|user| user.id.into()),
+    };
+
+    let reply_msg = helpers::make_audit_log_message(ctx.data(), &filter, 0)?;
+    ctx.send(reply_msg.into())
+        .await
+        .log_err_msg("Failed replying `/audit`")?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, guild_only, rename = "audit-channel")]
+pub async fn audit_channel(
+    ctx: PoiseContext<'_>,
+    #[description = "Mirror audit log entries into this text channel"]
+    #[channel_types("Text")]
+    channel: Option<serenity::all::GuildChannel>,
+    #[description = "Stop mirroring audit log entries"] disable: Option<bool>,
+) -> PoiseResult {
+    log::info!("`/audit-channel` slash command received");
+
+    let guild_id = ctx.guild_id().ok_or("No guild id found")?;
+    let table = ctx.data().guild_settings_table();
+    let mut settings = table.get_guild_settings(guild_id.into())?;
+    let before = settings.clone();
+
+    if let Some(channel) = channel {
+        settings.audit_log_channel_id = Some(channel.id.get());
+    }
+    if disable == Some(true) {
+        settings.audit_log_channel_id = None;
+    }
+
+    let format_settings = |settings: &db::GuildSettingsRow| match settings.audit_log_channel_id {
+        Some(id) => format!("<#{id}>"),
+        None => "none".to_string(),
+    };
+
+    if settings != before {
+        table.update_guild_settings(&settings)?;
+
+        helpers::write_audit_log(
+            ctx.http(),
+            ctx.data(),
+            AuditLogEntry::new(
+                guild_id,
+                ctx.author(),
+                AuditAction::SettingsChange,
+                "audit-channel",
+            )
+            .before(Some(format_settings(&before)))
+            .after(Some(format_settings(&settings))),
+        )
+        .await;
+    }
+
+    poise_check_msg(
+        ctx.reply(format!(
+            "Audit log channel - {}",
+            format_settings(&settings)
+        ))
+        .await,
+    );
+
+    Ok(())
+}
+
+#[poise::command(slash_command, guild_only)]
+pub async fn stats(ctx: PoiseContext<'_>) -> PoiseResult {
+    log::info!("`/stats` slash command received");
+
+    let guild_id = ctx.guild_id().ok_or("No guild id found")?;
+    let guild_stats = stats::GuildStats::fetch(
+        ctx.data(),
+        guild_id.into(),
+        stats::StatsWindow::Week,
+        chrono::Utc::now(),
+    )?;
+
+    ctx.send(stats::make_stats_message(&guild_stats)?.into())
+        .await
+        .log_err_msg("Failed replying `/stats`")?;
+
     Ok(())
 }
 
@@ -503,17 +2466,62 @@ pub async fn display_help(ctx: PoiseContext<'_>) -> PoiseResult {
 [GitHub Repo](https://github.com/krywicki/discord-soundboard-bot)
 Bot for playing sounds in voice chat.
 ## Slash Commands
-- `/play {{track}}` - Play sound track in voice channel
+- `/join {{channel}}` - Have bot join the voice channel, your voice channel by default
+- `/leave` - Have bot leave the voice channel
+- `/move {{channel}}` - Move bot to another voice channel, without playing the join audio
+- `/play {{track}}` - Play sound track in voice channel, joining your voice channel if the bot isn't in one. Misspelled tracks play the closest match, or suggest similar sounds
 - `/sounds`
   - `/sounds add` - Opens form to add sounds
-  - `/sounds remove {{track}}` - Removes sound
-  - `/sounds edit {{track}}` - Opens form to edit sound track
+  - `/sounds remove {{track}}` - Removes sound to the trash
+  - `/sounds restore {{track}}` - Restores removed sound from the trash
+  - `/sounds info {{track}}` - Display sound's waveform, duration, uploader, tags & plays, with a button to preview it privately
+  - `/sounds favorite {{track}}` - Adds sound to your favorites, listed under \"My Favorites\" in the sounds display menu
+  - `/sounds unfavorite {{track}}` - Removes sound from your favorites
+  - `/sounds edit {{track}}` - Opens form to edit sound track name, tags, aliases, emoji & button style. Aliases also find & play the sound. Button style is primary, secondary, success or danger
   - `/sounds display` - Displays sound dispaly options
     - `/sounds display {{search}}` - Displays a button grid of sounds filtered by tags or names
+      - ie: `tag:starwars -vader`, `jaws OR shark`, `by:@user`, `duration:<3s`, `pinned:true`, `played:>10`
   - `/sounds pin {{track}}` - Pin sound track to top of sound list
   - `/sounds unpin {{track}}` - Unpin sound track from top of sound list
   - `/sounds join-audio {{track}}` - Set/Unset sound track to play when bot joins voice channel
   - `/sounds leave-audio {{track}}` - Set/Unset sound track to play when bot leaves voice channel
+  - `/sounds quota` - Display your upload usage against the upload quotas
+  - `/sounds bind-emoji {{track}} {{emoji}}` - Binds emoji to sound, replacing its previous emoji. Shown on its button, and reacting to messages with it plays the sound
+  - `/sounds unbind-emoji {{track}}` - Unbinds sound's emoji
+- `/audit {{action}} {{user}}` - Display log of sound library & settings changes, optionally filtered
+- `/stats` - Display top sounds, players & uploaders, plays per day, and library size, with charts
+- `/collections` - Named collections of sounds. ie: \"Star Wars\", \"Movie night\"
+  - `/collections create {{name}}` - Creates an empty collection
+  - `/collections add {{collection}} {{track}}` - Adds sound to end of collection
+  - `/collections remove {{collection}} {{track}}` - Removes sound from collection
+  - `/collections delete {{collection}}` - Deletes collection, keeping its sounds
+  - `/collections list` - Lists collections & their number of sounds
+  - `/collections display {{collection}}` - Displays a button grid of the collection's sounds
+  - `/collections play {{collection}}` - Plays every sound in the collection, in order
+- `/tags` - Tags of sounds. Sounds can also be displayed by tag from the soundbot controls
+  - `/tags list` - Lists tags & their number of sounds
+  - `/tags rename {{old}} {{new}}` - Renames tag on every sound
+  - `/tags merge {{from}} {{into}}` - Retags sounds tagged `from` with `into`, removing `from`
+- `/intro` - Your personal sound, played when you join the bot's voice channel
+  - `/intro set {{track}}` - Sets your intro
+  - `/intro clear` - Clears your intro
+  - `/intro settings {{enabled}} {{outros-enabled}} {{max-duration}} {{cooldown}}` - Display or change the server's intro & outro settings
+- `/outro` - Your personal sound, played when you leave the bot's voice channel
+  - `/outro set {{track}}` - Sets your outro
+  - `/outro clear` - Clears your outro
+- `/auto-join {{enabled}} {{allow}} {{disallow}}` - Display or change auto join settings. When enabled, the bot joins a voice channel when the first member enters. Allowed channels limit which voice channels it joins, any if none are allowed
+- `/idle-timeout {{minutes}}` - Display or change how long the bot stays in a voice channel nothing has played in. It plays the leave audio, then leaves. `0` never leaves
+- `/schedule` - Sounds played on a schedule, in the bot's voice channel or a given one
+  - `/schedule add {{cron}} {{track}} {{channel}}` - Schedules sound with a UTC cron expression: minute hour day-of-month month day-of-week. ie: `0 * * * *` every hour, `0 21 * * FRI` 9pm on Fridays
+  - `/schedule list` - Lists schedules & when they next play
+  - `/schedule remove {{id}}` - Removes schedule
+- `/triggers` - Sounds played in the bot's voice channel when a message in a text channel matches a word, phrase or regex. Needs text triggers enabled, for message content
+  - `/triggers add {{channel}} {{kind}} {{pattern}} {{track}} {{cooldown}}` - Adds trigger, ignoring case. Plays at most once per cooldown, 30 seconds by default
+  - `/triggers list {{channel}}` - Lists triggers, optionally of a single text channel
+  - `/triggers remove {{id}}` - Removes trigger
+- `/panel` - Permanent soundboard message, edited in place whenever sounds are added, removed, edited or pinned. Re-posted if its message is deleted
+  - `/panel create {{channel}}` - Posts the server's panel, in this channel by default. Replaces its previous panel
+  - `/panel remove` - Removes the server's panel
 ## Prefix Commands
 - `{prefix}join` - Have bot join the voice channel
 - `{prefix}leave` - Have bot leave the voice channel
@@ -534,7 +2542,7 @@ pub enum Date {
     DateReverse,
 }
 
-struct TrackErrorNotifier;
+pub struct TrackErrorNotifier;
 
 #[async_trait]
 impl VoiceEventHandler for TrackErrorNotifier {
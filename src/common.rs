@@ -3,11 +3,22 @@ use std::path;
 use crate::audio::AudioFile;
 use crate::commands::PoiseError;
 use crate::config::Config;
-use crate::db::{AudioTable, DbConnection, SettingsTable};
-
+use crate::db::{
+    AudioTable, AuditLogTable, CollectionsTable, DbConnection, EmojiBindingsTable, FavoritesTable,
+    GuildSettingsTable, MemberSoundsTable, PanelsTable, PlayHistoryEntry, PlayHistoryTable,
+    SchedulesTable, SettingsTable, TagsTable, TriggersTable,
+};
+use crate::idle::PlaybackActivity;
+use crate::popularity;
+use crate::quotas::QuotaUsage;
+use crate::triggers::TriggerRegexCache;
+
+#[derive(Clone)]
 pub struct UserData {
     pub config: Config,
     pub db_pool: r2d2::Pool<r2d2_sqlite::SqliteConnectionManager>,
+    pub playback_activity: PlaybackActivity,
+    pub trigger_regex_cache: TriggerRegexCache,
 }
 
 impl UserData {
@@ -25,6 +36,79 @@ impl UserData {
         SettingsTable::new(self.db_connection())
     }
 
+    pub fn audit_log_table(&self) -> AuditLogTable {
+        AuditLogTable::new(self.db_connection())
+    }
+
+    pub fn guild_settings_table(&self) -> GuildSettingsTable {
+        GuildSettingsTable::new(self.db_connection())
+    }
+
+    pub fn play_history_table(&self) -> PlayHistoryTable {
+        PlayHistoryTable::new(self.db_connection())
+    }
+
+    pub fn favorites_table(&self) -> FavoritesTable {
+        FavoritesTable::new(self.db_connection())
+    }
+
+    pub fn collections_table(&self) -> CollectionsTable {
+        CollectionsTable::new(self.db_connection())
+    }
+
+    pub fn tags_table(&self) -> TagsTable {
+        TagsTable::new(self.db_connection())
+    }
+
+    pub fn member_sounds_table(&self) -> MemberSoundsTable {
+        MemberSoundsTable::new(self.db_connection())
+    }
+
+    pub fn schedules_table(&self) -> SchedulesTable {
+        SchedulesTable::new(self.db_connection())
+    }
+
+    pub fn triggers_table(&self) -> TriggersTable {
+        TriggersTable::new(self.db_connection())
+    }
+
+    pub fn emoji_bindings_table(&self) -> EmojiBindingsTable {
+        EmojiBindingsTable::new(self.db_connection())
+    }
+
+    pub fn panels_table(&self) -> PanelsTable {
+        PanelsTable::new(self.db_connection())
+    }
+
+    /// Bumps audio track play count & popularity, and records who played it, where and how in play history
+    pub fn record_play(&self, entry: PlayHistoryEntry) -> Result<(), PoiseError> {
+        let audio_table = self.audio_table();
+        let history_table = self.play_history_table();
+
+        audio_table.increment_play_count(entry.audio_id)?;
+        history_table.insert_entry(&entry)?;
+        popularity::refresh_track_popularity(
+            &audio_table,
+            &history_table,
+            entry.audio_id,
+            chrono::Utc::now(),
+        )?;
+
+        Ok(())
+    }
+
+    /// Errors if user adding a sound of `incoming_bytes` size would exceed configured upload quotas
+    pub fn check_upload_quota(
+        &self,
+        guild_id: impl Into<u64>,
+        user_id: impl Into<u64>,
+        incoming_bytes: u64,
+    ) -> Result<(), PoiseError> {
+        let usage = QuotaUsage::fetch(&self.audio_table(), guild_id.into(), user_id.into())?;
+        usage.check(&self.config, incoming_bytes).log_err()?;
+        Ok(())
+    }
+
     /// Attempts to move file to audio dir. Will attempt copy if move fails
     /// Moves can fail if target file and destination audio directory are on separate partitions of file systems
     pub fn move_file_to_audio_dir(
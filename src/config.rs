@@ -21,6 +21,20 @@ pub struct Config {
     pub max_page_size: u64,
     #[serde(default = "default_enable_ephemeral_controls")]
     pub enable_ephemeral_controls: bool,
+    /// Disabling drops the privileged message content intent. Slash commands are registered at startup instead
+    #[serde(default = "default_enable_prefix_commands")]
+    pub enable_prefix_commands: bool,
+    /// Enabling requests the privileged message content intent, even with prefix commands disabled
+    #[serde(default = "default_enable_text_triggers")]
+    pub enable_text_triggers: bool,
+    #[serde(default)]
+    pub max_sounds_per_user: Option<u64>,
+    #[serde(default)]
+    pub max_library_bytes_per_guild: Option<u64>,
+    #[serde(default)]
+    pub max_uploads_per_day: Option<u64>,
+    #[serde(default = "default_trash_retention_days")]
+    pub trash_retention_days: u64,
 }
 
 impl Config {
@@ -84,6 +98,12 @@ impl Default for Config {
             max_audio_file_duration: default_max_audio_file_duration(),
             max_page_size: default_max_page_size(),
             enable_ephemeral_controls: default_enable_ephemeral_controls(),
+            enable_prefix_commands: default_enable_prefix_commands(),
+            enable_text_triggers: default_enable_text_triggers(),
+            max_sounds_per_user: None,
+            max_library_bytes_per_guild: None,
+            max_uploads_per_day: None,
+            trash_retention_days: default_trash_retention_days(),
         }
     }
 }
@@ -92,6 +112,18 @@ fn default_enable_ephemeral_controls() -> bool {
     true
 }
 
+fn default_enable_prefix_commands() -> bool {
+    true
+}
+
+fn default_enable_text_triggers() -> bool {
+    true
+}
+
+fn default_trash_retention_days() -> u64 {
+    30
+}
+
 fn default_max_page_size() -> u64 {
     20
 }
@@ -1,32 +1,98 @@
-use std::ops::Deref;
+use std::{collections::HashMap, ops::Deref};
 
 use regex::Regex;
 use rusqlite::{params, types::FromSql, ToSql};
 
-use crate::{audio, commands::PoiseError, common::LogResult, db::Order};
+use crate::{audio, commands::PoiseError, common::LogResult, db::Order, fuzzy, vars};
 
-use super::{DbConnection, Table};
+use super::{add_column_if_missing, DbConnection, Table};
 
 pub struct AudioTableRow {
     pub id: i64,
     pub name: String,
     pub tags: Tags,
     pub audio_file: audio::AudioFile,
-    #[allow(dead_code)]
     pub created_at: chrono::DateTime<chrono::Utc>,
-    #[allow(dead_code)]
     pub author_id: Option<u64>,
-    #[allow(dead_code)]
     pub author_name: Option<String>,
-    #[allow(dead_code)]
     pub author_global_name: Option<String>,
-    #[allow(dead_code)]
     pub play_count: i64,
     #[allow(dead_code)]
     pub last_played_at: Option<chrono::DateTime<chrono::Utc>>,
     #[allow(dead_code)]
     pub popularity: f64,
     pub pinned: bool,
+    #[allow(dead_code)]
+    pub guild_id: Option<u64>,
+    #[allow(dead_code)]
+    pub deleted_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub duration_ms: Option<u64>,
+    /// Size of the audio file in bytes, recorded when it's added
+    pub file_size: Option<u64>,
+    /// Alternative names the track can be played or found by
+    pub aliases: Vec<String>,
+    /// Style of the track's button. Pinned tracks are green, others blurple, if `None`
+    pub button_style: Option<SoundButtonStyle>,
+}
+
+/// Button colors a sound can be displayed with
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SoundButtonStyle {
+    Primary,
+    Secondary,
+    Success,
+    Danger,
+}
+
+impl SoundButtonStyle {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Primary => "primary",
+            Self::Secondary => "secondary",
+            Self::Success => "success",
+            Self::Danger => "danger",
+        }
+    }
+}
+
+impl TryFrom<&str> for SoundButtonStyle {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.trim().to_lowercase().as_str() {
+            "primary" => Ok(Self::Primary),
+            "secondary" => Ok(Self::Secondary),
+            "success" => Ok(Self::Success),
+            "danger" => Ok(Self::Danger),
+            val => Err(format!(
+                "Unknown button style: {val}. Expected primary, secondary, success or danger"
+            )),
+        }
+    }
+}
+
+impl ToSql for SoundButtonStyle {
+    fn to_sql(&self) -> rusqlite::Result<rusqlite::types::ToSqlOutput<'_>> {
+        self.as_str().to_sql()
+    }
+}
+
+impl FromSql for SoundButtonStyle {
+    fn column_result(value: rusqlite::types::ValueRef<'_>) -> rusqlite::types::FromSqlResult<Self> {
+        SoundButtonStyle::try_from(value.as_str()?)
+            .map_err(|err| rusqlite::types::FromSqlError::Other(err.into()))
+    }
+}
+
+impl From<SoundButtonStyle> for serenity::all::ButtonStyle {
+    fn from(value: SoundButtonStyle) -> Self {
+        match value {
+            SoundButtonStyle::Primary => Self::Primary,
+            SoundButtonStyle::Secondary => Self::Secondary,
+            SoundButtonStyle::Success => Self::Success,
+            SoundButtonStyle::Danger => Self::Danger,
+        }
+    }
 }
 
 pub struct Tags(Vec<String>);
@@ -141,6 +207,24 @@ impl TryFrom<&rusqlite::Row<'_>> for AudioTableRow {
                 .get("popularity")
                 .log_err_msg("From row.popularity fail")?,
             pinned: row.get("pinned").log_err_msg("From row.pinned fail")?,
+            guild_id: row.get("guild_id").log_err_msg("From row.guild_id fail")?,
+            deleted_at: row
+                .get("deleted_at")
+                .log_err_msg("From row.deleted_at fail")?,
+            duration_ms: row
+                .get("duration_ms")
+                .log_err_msg("From row.duration_ms fail")?,
+            file_size: row
+                .get("file_size")
+                .log_err_msg("From row.file_size fail")?,
+            aliases: row
+                .get::<_, Option<String>>("aliases")
+                .log_err_msg("From row.aliases fail")?
+                .map(|val| val.split('\n').map(|alias| alias.to_string()).collect())
+                .unwrap_or_default(),
+            button_style: row
+                .get("button_style")
+                .log_err_msg("From row.button_style fail")?,
         })
     }
 }
@@ -157,6 +241,9 @@ pub struct AudioTableRowInsert {
     pub last_played_at: Option<chrono::DateTime<chrono::Utc>>,
     pub popularity: f64,
     pub pinned: bool,
+    pub guild_id: Option<u64>,
+    pub duration_ms: Option<u64>,
+    pub file_size: Option<u64>,
 }
 
 pub struct AudioTableRowInsertBuilder {
@@ -178,6 +265,9 @@ impl AudioTableRowInsertBuilder {
                 last_played_at: None,
                 popularity: 0.0,
                 pinned: false,
+                guild_id: None,
+                duration_ms: None,
+                file_size: None,
             },
         }
     }
@@ -244,6 +334,21 @@ impl AudioTableRowInsertBuilder {
         self
     }
 
+    pub fn guild_id(mut self, guild_id: Option<u64>) -> Self {
+        self.row_insert.guild_id = guild_id;
+        self
+    }
+
+    pub fn duration(mut self, duration: Option<std::time::Duration>) -> Self {
+        self.row_insert.duration_ms = duration.map(|val| val.as_millis() as u64);
+        self
+    }
+
+    pub fn file_size(mut self, file_size: Option<u64>) -> Self {
+        self.row_insert.file_size = file_size;
+        self
+    }
+
     pub fn build(self) -> AudioTableRowInsert {
         self.row_insert
     }
@@ -283,7 +388,10 @@ impl UniqueAudioTableCol {
     pub fn sql_condition(&self) -> String {
         match self {
             Self::Id(_) => format!("id = ? "),
-            Self::Name(_) => format!("name = ? "),
+            Self::Name(_) => format!(
+                "(name = ?1 OR id IN (SELECT audio_id FROM {} WHERE alias = ?1)) ",
+                AudioTable::ALIASES_TABLE_NAME
+            ),
             Self::AudioFile(_) => format!("audio_file = ? "),
         }
     }
@@ -296,6 +404,7 @@ pub struct AudioTable {
 impl AudioTable {
     pub const TABLE_NAME: &'static str = "audio";
     pub const FTS5_TABLE_NAME: &'static str = "fts5_audio";
+    pub const ALIASES_TABLE_NAME: &'static str = "aliases";
 
     pub fn new(connection: DbConnection) -> Self {
         Self { conn: connection }
@@ -316,8 +425,11 @@ impl AudioTable {
         if text.len() < 3 {
             log::debug!("low character auto complete: '{text}'");
             let table_name = Self::TABLE_NAME;
-            let sql =
-                format!("SELECT name FROM {table_name} ORDER BY created_at DESC LIMIT {limit}");
+            let sql = format!(
+                "SELECT name FROM {table_name}
+                WHERE deleted_at IS NULL
+                ORDER BY created_at DESC LIMIT {limit}"
+            );
             let mut stmt = self
                 .conn
                 .prepare(sql.as_str())
@@ -337,8 +449,15 @@ impl AudioTable {
         }
 
         log::debug!("Auto complete partial search on {text}");
+        let table_name = Self::TABLE_NAME;
         let fts5_table_name = Self::FTS5_TABLE_NAME;
-        let sql = format!("SELECT name FROM {fts5_table_name}(?) LIMIT {limit}");
+        let sql = format!(
+            "SELECT FTS.name FROM {fts5_table_name}(?) FTS
+            INNER JOIN {table_name} Audio
+                ON Audio.id = FTS.rowid
+            WHERE Audio.deleted_at IS NULL
+            LIMIT {limit}"
+        );
         let mut stmt = self
             .conn
             .prepare(sql.as_str())
@@ -354,13 +473,144 @@ impl AudioTable {
         }
     }
 
+    /// Return list of audio tracks by name matching partial string, full text matches first followed
+    /// by fuzzy matches. ie: misspellings, or partials too short for full text search
+    pub fn autocomplete_track_names(
+        &self,
+        partial: impl AsRef<str>,
+        limit: Option<usize>,
+    ) -> Vec<String> {
+        let text = partial.as_ref();
+        let limit = limit.unwrap_or(5);
+
+        let mut names = self.fts_autocomplete_track_names(text, Some(limit));
+        if text.trim().is_empty() {
+            return names;
+        }
+
+        match self.fuzzy_find_audio_rows(text, vars::FUZZY_MIN_SCORE, limit) {
+            Ok(rows) => {
+                // low char queries list latest sounds added, which fuzzy matches replace
+                if text.len() < 3 && !rows.is_empty() {
+                    names.clear();
+                }
+
+                for (row, _) in rows {
+                    if names.len() >= limit {
+                        break;
+                    }
+                    if !names.contains(&row.name) {
+                        names.push(row.name);
+                    }
+                }
+            }
+            Err(err) => log::error!("Autocomplete fuzzy match error - {err}"),
+        }
+
+        names
+    }
+
+    /// Non-trashed audio tracks most similar to query by name, aliases & tags, with their similarity.
+    /// Most similar first. See [`fuzzy::track_similarity`]
+    pub fn fuzzy_find_audio_rows(
+        &self,
+        query: impl AsRef<str>,
+        min_score: f64,
+        limit: usize,
+    ) -> Result<Vec<(AudioTableRow, f64)>, PoiseError> {
+        let query = query.as_ref();
+        let table_name = Self::TABLE_NAME;
+        let sql = format!("SELECT * FROM {table_name} WHERE deleted_at IS NULL");
+
+        let mut stmt = self
+            .conn
+            .prepare(sql.as_str())
+            .log_err_msg("Failed preparing fuzzy find")?;
+
+        let mut rows: Vec<(AudioTableRow, f64)> = stmt
+            .query_map((), |row| AudioTableRow::try_from(row))
+            .log_err_msg("Failed fuzzy find")?
+            .filter_map(|row| row.ok())
+            .map(|row| {
+                let score = row
+                    .aliases
+                    .iter()
+                    .map(|alias| fuzzy::similarity(query, alias))
+                    .fold(
+                        fuzzy::track_similarity(query, &row.name, &row.tags),
+                        f64::max,
+                    );
+                (row, score)
+            })
+            .filter(|(_, score)| *score >= min_score)
+            .collect();
+
+        rows.sort_by(|(a_row, a), (b_row, b)| b.total_cmp(a).then(a_row.name.cmp(&b_row.name)));
+        rows.truncate(limit);
+
+        Ok(rows)
+    }
+
+    /// Return list of trashed audio tracks by name that contain partial string
+    pub fn autocomplete_trashed_track_names(
+        &self,
+        partial: impl AsRef<str>,
+        limit: Option<usize>,
+    ) -> Vec<String> {
+        let text = partial.as_ref();
+        let limit = limit.unwrap_or(5);
+        let table_name = Self::TABLE_NAME;
+
+        let sql = format!(
+            "SELECT name FROM {table_name}
+            WHERE deleted_at IS NOT NULL AND name LIKE '%' || ? || '%'
+            ORDER BY deleted_at DESC LIMIT {limit}"
+        );
+        let mut stmt = self
+            .conn
+            .prepare(sql.as_str())
+            .expect("Autocomplete trashed sql invalid");
+
+        let rows = stmt.query_map(params![&text], |row| row.get("name"));
+        match rows {
+            Ok(rows) => rows.filter_map(|row| row.ok()).collect(),
+            Err(err) => {
+                log::error!("Autocomplete trashed sql query error - {err}");
+                vec![]
+            }
+        }
+    }
+
+    /// Find audio row that isn't in the trash
     pub fn find_audio_row(&self, col: impl AsRef<UniqueAudioTableCol>) -> Option<AudioTableRow> {
+        self.find_row(col, false)
+    }
+
+    /// Find audio row that is in the trash
+    pub fn find_trashed_audio_row(
+        &self,
+        col: impl AsRef<UniqueAudioTableCol>,
+    ) -> Option<AudioTableRow> {
+        self.find_row(col, true)
+    }
+
+    fn find_row(
+        &self,
+        col: impl AsRef<UniqueAudioTableCol>,
+        trashed: bool,
+    ) -> Option<AudioTableRow> {
         let col = col.as_ref();
         let col_value = col.value();
         let table_name = Self::TABLE_NAME;
 
         let sql_condition = col.sql_condition();
-        let sql = format!("SELECT * FROM {table_name} WHERE {sql_condition}");
+        let trashed_condition = if trashed {
+            "deleted_at IS NOT NULL"
+        } else {
+            "deleted_at IS NULL"
+        };
+        let sql =
+            format!("SELECT * FROM {table_name} WHERE {sql_condition} AND {trashed_condition}");
 
         self.conn
             .query_row(sql.as_str(), params![&col_value], |row| {
@@ -385,9 +635,9 @@ impl AudioTable {
         let sql = format!(
             "
             INSERT INTO {table_name}
-                (name, tags, audio_file, created_at, author_id, author_name, author_global_name)
+                (name, tags, audio_file, created_at, author_id, author_name, author_global_name, guild_id, duration_ms, file_size)
             VALUES
-                (?1, ?2, ?3, ?4, ?5, ?6, ?7)"
+                (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)"
         );
 
         self.connection()
@@ -401,6 +651,9 @@ impl AudioTable {
                     &audio_row.author_id,
                     &audio_row.author_name,
                     &audio_row.author_global_name,
+                    &audio_row.guild_id,
+                    &audio_row.duration_ms,
+                    &audio_row.file_size,
                 ),
             )
             .map_err(|err| {
@@ -411,35 +664,114 @@ impl AudioTable {
         Ok(())
     }
 
+    /// Update audio row name, tags & aliases. Names & aliases must be unique across each other
     pub fn update_audio_row(&self, audio_row: impl AsRef<AudioTableRow>) -> Result<(), String> {
         let audio_row = audio_row.as_ref();
         log::info!("Updating audio row. Name: {}", audio_row.name);
 
         let table_name = Self::TABLE_NAME;
+        let aliases_table_name = Self::ALIASES_TABLE_NAME;
         let name = &audio_row.name;
         let tags = &audio_row.tags;
+        let button_style = &audio_row.button_style;
         let row_id = audio_row.id;
 
+        self.validate_aliases(audio_row)?;
+
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .log_err_msg("Failed starting update audio track transaction")
+            .map_err(|err| err.to_string())?;
+
+        tx.execute(
+            format!("DELETE FROM {aliases_table_name} WHERE audio_id = ?").as_str(),
+            params![&row_id],
+        )
+        .log_err_msg("Failed clearing audio track aliases")
+        .map_err(|err| err.to_string())?;
+
         let sql = format!(
             "
             UPDATE {table_name}
             SET
                 name = ?,
-                tags = ?
+                tags = ?,
+                button_style = ?
             WHERE
                 id = ?;
         "
         );
 
-        self.conn
-            .execute(sql.as_str(), params![&name, &tags, &row_id])
+        tx.execute(sql.as_str(), params![&name, &tags, button_style, &row_id])
             .log_err_msg("Failed updating audio track")
             .map_err(|err| err.to_string())?;
 
+        for alias in audio_row.aliases.iter() {
+            tx.execute(
+                format!("INSERT INTO {aliases_table_name} (alias, audio_id) VALUES (?, ?)")
+                    .as_str(),
+                params![alias, &row_id],
+            )
+            .log_err_msg("Failed inserting audio track alias")
+            .map_err(|err| err.to_string())?;
+        }
+
+        tx.commit()
+            .log_err_msg("Failed committing update audio track transaction")
+            .map_err(|err| err.to_string())?;
+
         log::info!("Updated audio row. Name: {name}");
         Ok(())
     }
 
+    /// Check aliases aren't duplicated, or already the name or alias of a sound
+    fn validate_aliases(&self, audio_row: &AudioTableRow) -> Result<(), String> {
+        let table_name = Self::TABLE_NAME;
+        let aliases_table_name = Self::ALIASES_TABLE_NAME;
+
+        let sql = format!(
+            "SELECT EXISTS (
+                SELECT 1 FROM {table_name} WHERE name = ?1 COLLATE NOCASE AND id != ?2
+                UNION ALL
+                SELECT 1 FROM {aliases_table_name} WHERE alias = ?1 AND audio_id != ?2
+            )"
+        );
+
+        for (idx, alias) in audio_row.aliases.iter().enumerate() {
+            if alias.trim().is_empty()
+                || alias.contains('\n')
+                || alias.chars().count() > vars::BTN_LABEL_MAX_LEN
+            {
+                return Err(format!("Invalid alias `{alias}`"));
+            }
+
+            if alias.eq_ignore_ascii_case(&audio_row.name)
+                || audio_row.aliases[..idx]
+                    .iter()
+                    .any(|other| other.eq_ignore_ascii_case(alias))
+            {
+                return Err(format!("Alias `{alias}` is used more than once"));
+            }
+
+            let taken: bool = self
+                .conn
+                .query_row(sql.as_str(), params![alias, &audio_row.id], |row| {
+                    row.get(0)
+                })
+                .log_err_msg("Failed checking audio track alias")
+                .map_err(|err| err.to_string())?;
+
+            if taken {
+                return Err(format!(
+                    "Alias `{alias}` is already the name or alias of another sound"
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn increment_play_count(&self, row_id: i64) -> Result<(), String> {
         log::info!("Incrementing play count for audio row with id: {row_id}");
 
@@ -481,29 +813,336 @@ impl AudioTable {
         Ok(())
     }
 
-    pub fn delete_audio_row(&self, col: impl AsRef<UniqueAudioTableCol>) -> Result<(), PoiseError> {
+    /// Moves audio row to the trash. Trashed rows are hidden from listings until restored or purged
+    pub fn trash_audio_row(&self, col: impl AsRef<UniqueAudioTableCol>) -> Result<(), PoiseError> {
         let column = col.as_ref();
-        match self.find_audio_row(&col) {
-            None => log::info!("Can't delete non-existent audio track. {column:?}"),
-            Some(row) => {
-                row.audio_file.delete();
-                let table_name = Self::TABLE_NAME;
-                let row_id = row.id;
-                let sql = format!("DELETE FROM {table_name} WHERE id = {row_id}");
-
-                self.conn
-                    .execute(sql.as_str(), ())
-                    .log_err_msg("Failed to delete audio row")?;
-            }
+        let row = self
+            .find_audio_row(&col)
+            .ok_or(format!("Can't trash non-existent audio track. {column:?}"))
+            .log_err()?;
+
+        let table_name = Self::TABLE_NAME;
+        let deleted_at = chrono::Utc::now();
+        let sql = format!("UPDATE {table_name} SET deleted_at = ? WHERE id = ?");
+
+        self.conn
+            .execute(sql.as_str(), params![&deleted_at, &row.id])
+            .log_err_msg("Failed to trash audio row")?;
+
+        Ok(())
+    }
+
+    /// Restores audio row from the trash
+    pub fn restore_audio_row(
+        &self,
+        col: impl AsRef<UniqueAudioTableCol>,
+    ) -> Result<(), PoiseError> {
+        let column = col.as_ref();
+        let row = self
+            .find_trashed_audio_row(&col)
+            .ok_or(format!(
+                "Can't restore audio track not in trash. {column:?}"
+            ))
+            .log_err()?;
+
+        let table_name = Self::TABLE_NAME;
+        let sql = format!("UPDATE {table_name} SET deleted_at = NULL WHERE id = ?");
+
+        self.conn
+            .execute(sql.as_str(), params![&row.id])
+            .log_err_msg("Failed to restore audio row")?;
+
+        Ok(())
+    }
+
+    /// Permanently deletes audio rows, and their audio files, trashed before `before`.
+    /// Rows of other tables referencing them are deleted in the same transaction, so a purged row's
+    /// id can't be reused by a new audio track before they're gone. Returns number of purged rows
+    pub fn purge_trashed_rows(
+        &self,
+        before: chrono::DateTime<chrono::Utc>,
+    ) -> Result<usize, PoiseError> {
+        let table_name = Self::TABLE_NAME;
+        let sql =
+            format!("SELECT * FROM {table_name} WHERE deleted_at IS NOT NULL AND deleted_at < ?");
+
+        let rows: Vec<AudioTableRow> = self
+            .conn
+            .prepare(sql.as_str())
+            .log_err_msg("Failed preparing purge trashed rows sql")?
+            .query_map(params![&before], |row| AudioTableRow::try_from(row))
+            .log_err_msg("Failed querying trashed rows")?
+            .filter_map(|row| row.ok())
+            .collect();
+
+        if rows.is_empty() {
+            return Ok(0);
+        }
+
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .log_err_msg("Failed starting purge trashed rows transaction")?;
+
+        let sql = format!("DELETE FROM {table_name} WHERE id = ?");
+        for row in rows.iter() {
+            log::info!("Purging trashed audio track: {}", row.name);
+            tx.execute(sql.as_str(), params![&row.id])
+                .log_err_msg("Failed to delete audio row")?;
+        }
+
+        super::delete_orphaned_rows(&tx)?;
+        tx.commit()
+            .log_err_msg("Failed committing purge trashed rows transaction")?;
+
+        for row in rows.iter() {
+            row.audio_file.delete();
+        }
+
+        Ok(rows.len())
+    }
+
+    /// Number of audio tracks added by author to guild, excluding the trash.
+    /// **note**: Tracks added before guild ids were recorded count towards every guild
+    pub fn count_audio_rows_by_author(
+        &self,
+        guild_id: u64,
+        author_id: u64,
+    ) -> Result<u64, PoiseError> {
+        let table_name = Self::TABLE_NAME;
+        let sql = format!(
+            "SELECT COUNT(id) FROM {table_name}
+            WHERE (guild_id = ?1 OR guild_id IS NULL) AND author_id = ?2 AND deleted_at IS NULL"
+        );
+
+        let count: u64 = self
+            .conn
+            .query_row(sql.as_str(), params![&guild_id, &author_id], |row| {
+                row.get(0)
+            })
+            .log_err_msg("Failed counting audio rows by author")?;
+
+        Ok(count)
+    }
+
+    /// Number of audio tracks added by author to guild since `since`, including the trash so
+    /// removing sounds doesn't free up uploads.
+    /// **note**: Tracks added before guild ids were recorded count towards every guild
+    pub fn count_audio_rows_by_author_since(
+        &self,
+        guild_id: u64,
+        author_id: u64,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> Result<u64, PoiseError> {
+        let table_name = Self::TABLE_NAME;
+        let sql = format!(
+            "SELECT COUNT(id) FROM {table_name}
+            WHERE (guild_id = ?1 OR guild_id IS NULL) AND author_id = ?2 AND created_at >= ?3"
+        );
+
+        let count: u64 = self
+            .conn
+            .query_row(
+                sql.as_str(),
+                params![&guild_id, &author_id, &since],
+                |row| row.get(0),
+            )
+            .log_err_msg("Failed counting audio rows by author since date")?;
+
+        Ok(count)
+    }
+
+    /// Total size in bytes of audio files added in guild, including the trash since its files are still on disk.
+    /// **note**: Tracks added before guild ids were recorded count towards every guild
+    pub fn guild_library_size(&self, guild_id: u64) -> Result<u64, PoiseError> {
+        let table_name = Self::TABLE_NAME;
+        let sql = format!(
+            "SELECT COALESCE(SUM(file_size), 0) FROM {table_name}
+            WHERE guild_id = ? OR guild_id IS NULL"
+        );
+
+        Ok(self
+            .conn
+            .query_row(sql.as_str(), params![&guild_id], |row| row.get(0))
+            .log_err_msg("Failed querying guild library size")?)
+    }
+
+    pub fn update_audio_row_file_size(
+        &self,
+        row_id: i64,
+        file_size: u64,
+    ) -> Result<(), PoiseError> {
+        let table_name = Self::TABLE_NAME;
+        let sql = format!("UPDATE {table_name} SET file_size = ? WHERE id = ?");
+
+        self.conn
+            .execute(sql.as_str(), params![&file_size, &row_id])
+            .log_err_msg("Failed updating audio row file size")?;
+
+        Ok(())
+    }
+
+    /// Audio tracks added before file sizes were recorded
+    pub fn list_rows_missing_file_size(&self) -> Result<Vec<AudioTableRow>, PoiseError> {
+        let table_name = Self::TABLE_NAME;
+        let sql = format!("SELECT * FROM {table_name} WHERE file_size IS NULL");
+
+        let mut stmt = self
+            .conn
+            .prepare(sql.as_str())
+            .log_err_msg("Failed preparing rows missing file size sql")?;
+
+        let rows = stmt
+            .query_map([], |row| AudioTableRow::try_from(row))
+            .log_err_msg("Failed querying rows missing file size")?
+            .filter_map(|row| row.ok())
+            .collect();
+
+        Ok(rows)
+    }
+
+    /// Replaces popularity of every audio track. Tracks missing from `scores` are reset to 0
+    pub fn update_popularity(&self, scores: &HashMap<i64, f64>) -> Result<(), PoiseError> {
+        let table_name = Self::TABLE_NAME;
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .log_err_msg("Failed starting update popularity transaction")?;
+
+        tx.execute(
+            format!("UPDATE {table_name} SET popularity = 0 WHERE popularity != 0").as_str(),
+            (),
+        )
+        .log_err_msg("Failed resetting popularity")?;
+
+        let sql = format!("UPDATE {table_name} SET popularity = ? WHERE id = ?");
+        for (row_id, popularity) in scores.iter() {
+            tx.execute(sql.as_str(), params![popularity, row_id])
+                .log_err_msg("Failed updating popularity")?;
         }
+
+        tx.commit()
+            .log_err_msg("Failed committing update popularity transaction")?;
+
+        Ok(())
+    }
+
+    pub fn update_audio_row_popularity(
+        &self,
+        row_id: i64,
+        popularity: f64,
+    ) -> Result<(), PoiseError> {
+        let table_name = Self::TABLE_NAME;
+        let sql = format!("UPDATE {table_name} SET popularity = ? WHERE id = ?");
+
+        self.conn
+            .execute(sql.as_str(), params![&popularity, &row_id])
+            .log_err_msg("Failed updating audio row popularity")?;
+
+        Ok(())
+    }
+
+    pub fn update_audio_row_duration(
+        &self,
+        row_id: i64,
+        duration: std::time::Duration,
+    ) -> Result<(), PoiseError> {
+        let table_name = Self::TABLE_NAME;
+        let sql = format!("UPDATE {table_name} SET duration_ms = ? WHERE id = ?");
+
+        self.conn
+            .execute(
+                sql.as_str(),
+                params![&(duration.as_millis() as u64), &row_id],
+            )
+            .log_err_msg("Failed updating audio row duration")?;
+
         Ok(())
     }
 
+    /// Audio tracks added before durations were recorded
+    pub fn list_rows_missing_duration(&self) -> Result<Vec<AudioTableRow>, PoiseError> {
+        let table_name = Self::TABLE_NAME;
+        let sql = format!("SELECT * FROM {table_name} WHERE duration_ms IS NULL");
+
+        let mut stmt = self
+            .conn
+            .prepare(sql.as_str())
+            .log_err_msg("Failed preparing rows missing duration sql")?;
+
+        let rows = stmt
+            .query_map([], |row| AudioTableRow::try_from(row))
+            .log_err_msg("Failed querying rows missing duration")?
+            .filter_map(|row| row.ok())
+            .collect();
+
+        Ok(rows)
+    }
+
+    /// Audio track count & total duration of guild library.
+    /// **note**: Tracks added before guild ids were recorded count towards every guild
+    pub fn guild_library_duration(
+        &self,
+        guild_id: u64,
+    ) -> Result<(u64, std::time::Duration), PoiseError> {
+        let table_name = Self::TABLE_NAME;
+        let sql = format!(
+            "SELECT COUNT(id), COALESCE(SUM(duration_ms), 0) FROM {table_name}
+            WHERE (guild_id = ? OR guild_id IS NULL) AND deleted_at IS NULL"
+        );
+
+        let (count, duration_ms): (u64, u64) = self
+            .conn
+            .query_row(sql.as_str(), params![&guild_id], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .log_err_msg("Failed querying guild library duration")?;
+
+        Ok((count, std::time::Duration::from_millis(duration_ms)))
+    }
+
+    /// Authors who added the most audio tracks to guild since `since`. Returns (author id, count)
+    pub fn top_uploaders(
+        &self,
+        guild_id: u64,
+        since: Option<chrono::DateTime<chrono::Utc>>,
+        limit: u64,
+    ) -> Result<Vec<(u64, u64)>, PoiseError> {
+        let table_name = Self::TABLE_NAME;
+        let sql = format!(
+            "SELECT author_id, COUNT(id) AS uploads FROM {table_name}
+            WHERE (guild_id = ?1 OR guild_id IS NULL)
+                AND deleted_at IS NULL
+                AND author_id IS NOT NULL
+                AND (?2 IS NULL OR created_at >= ?2)
+            GROUP BY author_id
+            ORDER BY uploads DESC, author_id ASC
+            LIMIT {limit}"
+        );
+
+        let mut stmt = self
+            .conn
+            .prepare(sql.as_str())
+            .log_err_msg("Failed preparing top uploaders sql")?;
+
+        let rows = stmt
+            .query_map(params![&guild_id, &since], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .log_err_msg("Failed querying top uploaders")?
+            .filter_map(|row| row.log_err().ok())
+            .collect();
+
+        Ok(rows)
+    }
+
     pub fn get_random_row(&self) -> Result<Option<AudioTableRow>, String> {
         log::info!("Getting random audio row");
 
         let table_name = Self::TABLE_NAME;
-        let sql = format!("SELECT * FROM {table_name} ORDER BY RANDOM() LIMIT 1");
+        let sql = format!(
+            "SELECT * FROM {table_name} WHERE deleted_at IS NULL ORDER BY RANDOM() LIMIT 1"
+        );
 
         let result = self
             .conn
@@ -525,8 +1164,9 @@ impl Table for AudioTable {
     fn drop_table(&self) {
         let table_name = Self::TABLE_NAME;
         let fts5_table_name = Self::FTS5_TABLE_NAME;
+        let aliases_table_name = Self::ALIASES_TABLE_NAME;
 
-        log::info!("Dropping tables {table_name}, {fts5_table_name}...");
+        log::info!("Dropping tables {table_name}, {fts5_table_name}, {aliases_table_name}...");
 
         let sql = format!(
             "
@@ -535,6 +1175,8 @@ impl Table for AudioTable {
 
                 DROP TABLE IF EXISTS {fts5_table_name};
 
+                DROP TABLE IF EXISTS {aliases_table_name};
+
                 DROP TRIGGER IF EXISTS {table_name}_insert;
 
                 DROP TRIGGER IF EXISTS {table_name}_delete;
@@ -548,14 +1190,20 @@ impl Table for AudioTable {
             .log_err_msg(format!("Failed creating table:{table_name}"))
             .unwrap();
 
-        log::info!("Created tables {table_name}, {fts5_table_name}!");
+        log::info!("Created tables {table_name}, {fts5_table_name}, {aliases_table_name}!");
     }
 
     fn create_table(&self) {
         let table_name = Self::TABLE_NAME;
         let fts5_table_name = Self::FTS5_TABLE_NAME;
+        let aliases_table_name = Self::ALIASES_TABLE_NAME;
+
+        log::info!("Creating tables {table_name}, {fts5_table_name}, {aliases_table_name}...");
 
-        log::info!("Creating tables {table_name}, {fts5_table_name}...");
+        let rebuild_fts5 = self
+            .migrate_fts5_aliases()
+            .log_err_msg(format!("Failed migrating table:{fts5_table_name}"))
+            .unwrap();
 
         let sql = format!(
             "
@@ -572,29 +1220,83 @@ impl Table for AudioTable {
                     play_count INTEGER DEFAULT 0,
                     last_played_at VARCHAR(25) DEFAULT NULL,
                     popularity REAL DEFAULT 0,
-                    pinned BOOLEAN DEFAULT FALSE
+                    pinned BOOLEAN DEFAULT FALSE,
+                    guild_id INTEGER,
+                    deleted_at VARCHAR(25) DEFAULT NULL,
+                    duration_ms INTEGER,
+                    file_size INTEGER,
+                    aliases TEXT,
+                    button_style VARCHAR(10)
+                );
+
+                CREATE TABLE IF NOT EXISTS {aliases_table_name} (
+                    alias VARCHAR(80) NOT NULL PRIMARY KEY COLLATE NOCASE,
+                    audio_id INTEGER NOT NULL
                 );
 
+                CREATE INDEX IF NOT EXISTS {aliases_table_name}_audio_id
+                    ON {aliases_table_name} (audio_id);
+
                 CREATE VIRTUAL TABLE IF NOT EXISTS {fts5_table_name} USING FTS5(
-                    name, tags, content={table_name}, content_rowid=id, tokenize='trigram remove_diacritics 1'
+                    name, tags, aliases, content={table_name}, content_rowid=id, tokenize='trigram remove_diacritics 1'
                 );
 
                 CREATE TRIGGER IF NOT EXISTS {table_name}_insert AFTER INSERT ON {table_name} BEGIN
-                    INSERT INTO {fts5_table_name}(rowid, name, tags)
-                        VALUES (new.id, new.name, new.tags);
+                    INSERT INTO {fts5_table_name}(rowid, name, tags, aliases)
+                        VALUES (new.id, new.name, new.tags, new.aliases);
                 END;
 
                 CREATE TRIGGER IF NOT EXISTS {table_name}_delete AFTER DELETE ON {table_name} BEGIN
-                    INSERT INTO {fts5_table_name}({fts5_table_name}, rowid, name, tags)
-                        VALUES('delete', old.id, old.name, old.tags);
+                    INSERT INTO {fts5_table_name}({fts5_table_name}, rowid, name, tags, aliases)
+                        VALUES('delete', old.id, old.name, old.tags, old.aliases);
+
+                    DELETE FROM {aliases_table_name} WHERE audio_id = old.id;
                 END;
 
                 CREATE TRIGGER IF NOT EXISTS {table_name}_update AFTER UPDATE ON {table_name} BEGIN
-                    INSERT INTO {fts5_table_name}({fts5_table_name}, rowid, name, tags)
-                        VALUES('delete', old.id, old.name, old.tags);
+                    INSERT INTO {fts5_table_name}({fts5_table_name}, rowid, name, tags, aliases)
+                        VALUES('delete', old.id, old.name, old.tags, old.aliases);
+
+                    INSERT INTO {fts5_table_name}(rowid, name, tags, aliases)
+                        VALUES (new.id, new.name, new.tags, new.aliases);
+                END;
+
+                CREATE TRIGGER IF NOT EXISTS {table_name}_name_insert BEFORE INSERT ON {table_name} BEGIN
+                    SELECT RAISE(ABORT, 'Sound name is already the alias of another sound')
+                        WHERE EXISTS (SELECT 1 FROM {aliases_table_name} WHERE alias = new.name);
+                END;
+
+                CREATE TRIGGER IF NOT EXISTS {table_name}_name_update BEFORE UPDATE OF name ON {table_name} BEGIN
+                    SELECT RAISE(ABORT, 'Sound name is already the alias of another sound')
+                        WHERE EXISTS (
+                            SELECT 1 FROM {aliases_table_name}
+                            WHERE alias = new.name AND audio_id != new.id
+                        );
+                END;
+
+                CREATE TRIGGER IF NOT EXISTS {aliases_table_name}_name BEFORE INSERT ON {aliases_table_name} BEGIN
+                    SELECT RAISE(ABORT, 'Alias is already the name of a sound')
+                        WHERE EXISTS (
+                            SELECT 1 FROM {table_name} WHERE name = new.alias COLLATE NOCASE
+                        );
+                END;
+
+                CREATE TRIGGER IF NOT EXISTS {aliases_table_name}_insert AFTER INSERT ON {aliases_table_name} BEGIN
+                    UPDATE {table_name}
+                    SET aliases = (
+                        SELECT group_concat(alias, char(10)) FROM {aliases_table_name}
+                        WHERE audio_id = new.audio_id
+                    )
+                    WHERE id = new.audio_id;
+                END;
 
-                    INSERT INTO {fts5_table_name}(rowid, name, tags)
-                        VALUES (new.id, new.name, new.tags);
+                CREATE TRIGGER IF NOT EXISTS {aliases_table_name}_delete AFTER DELETE ON {aliases_table_name} BEGIN
+                    UPDATE {table_name}
+                    SET aliases = (
+                        SELECT group_concat(alias, char(10)) FROM {aliases_table_name}
+                        WHERE audio_id = old.audio_id
+                    )
+                    WHERE id = old.audio_id;
                 END;
             COMMIT;"
         );
@@ -604,7 +1306,84 @@ impl Table for AudioTable {
             .log_err_msg(format!("Failed creating table:{table_name}"))
             .unwrap();
 
-        log::info!("Created tables {table_name}, {fts5_table_name}!");
+        if rebuild_fts5 {
+            log::info!("Rebuilding {fts5_table_name} index");
+            self.conn
+                .execute(
+                    format!("INSERT INTO {fts5_table_name}({fts5_table_name}) VALUES('rebuild')")
+                        .as_str(),
+                    (),
+                )
+                .log_err_msg(format!("Failed rebuilding table:{fts5_table_name}"))
+                .unwrap();
+        }
+
+        add_column_if_missing(&self.conn, table_name, "guild_id", "INTEGER")
+            .and_then(|_| {
+                add_column_if_missing(
+                    &self.conn,
+                    table_name,
+                    "deleted_at",
+                    "VARCHAR(25) DEFAULT NULL",
+                )
+            })
+            .and_then(|_| add_column_if_missing(&self.conn, table_name, "duration_ms", "INTEGER"))
+            .and_then(|_| add_column_if_missing(&self.conn, table_name, "file_size", "INTEGER"))
+            .and_then(|_| {
+                add_column_if_missing(&self.conn, table_name, "button_style", "VARCHAR(10)")
+            })
+            .log_err_msg(format!("Failed migrating table:{table_name}"))
+            .unwrap();
+
+        log::info!("Created tables {table_name}, {fts5_table_name}, {aliases_table_name}!");
+    }
+}
+
+impl AudioTable {
+    /// Databases created by older bot versions index audio tracks without aliases. The full text
+    /// table can't be altered, so it's dropped along with its triggers to be recreated & rebuilt.
+    /// Returns `true` if the full text index needs rebuilding
+    fn migrate_fts5_aliases(&self) -> rusqlite::Result<bool> {
+        let table_name = Self::TABLE_NAME;
+        let fts5_table_name = Self::FTS5_TABLE_NAME;
+
+        let has_column = |table: &str, column: &str| -> rusqlite::Result<bool> {
+            let sql = format!("SELECT COUNT(*) FROM pragma_table_info('{table}') WHERE name = ?");
+            let count: i64 = self
+                .conn
+                .query_row(sql.as_str(), [column], |row| row.get(0))?;
+            Ok(count > 0)
+        };
+
+        // no tables yet, nothing to migrate
+        if !has_column(fts5_table_name, "name")? {
+            return Ok(false);
+        }
+
+        if has_column(fts5_table_name, "aliases")? {
+            return Ok(false);
+        }
+
+        log::info!("Migrating table {fts5_table_name}. Adding column aliases");
+        add_column_if_missing(&self.conn, table_name, "aliases", "TEXT")?;
+
+        self.conn.execute_batch(
+            format!(
+                "
+                BEGIN;
+                    DROP TABLE IF EXISTS {fts5_table_name};
+
+                    DROP TRIGGER IF EXISTS {table_name}_insert;
+
+                    DROP TRIGGER IF EXISTS {table_name}_delete;
+
+                    DROP TRIGGER IF EXISTS {table_name}_update;
+                COMMIT;"
+            )
+            .as_str(),
+        )?;
+
+        Ok(true)
     }
 }
 
@@ -615,15 +1394,24 @@ pub enum AudioTableOrderBy {
     Id(Order),
     Name(Order),
     PlayCount(Order),
+    /// Latest play in play history. Only valid when paginating rows played in a guild
+    PlayedAt(Order),
+    Popularity(Order),
+    /// Position in collection. Only valid when paginating rows in a collection
+    CollectionPosition(Order),
 }
 
 impl AudioTableOrderBy {
+    /// Order by sql, with audio table aliased as `Audio`
     pub fn to_sql_str(&self) -> String {
         match self {
-            Self::CreatedAt(order) => format!("created_at {order}"),
-            Self::Id(order) => format!("id {order}"),
-            Self::Name(order) => format!("name COLLATE NOCASE {order}"),
-            Self::PlayCount(order) => format!("play_count {order}"),
+            Self::CreatedAt(order) => format!("Audio.created_at {order}"),
+            Self::Id(order) => format!("Audio.id {order}"),
+            Self::Name(order) => format!("Audio.name COLLATE NOCASE {order}"),
+            Self::PlayCount(order) => format!("Audio.play_count {order}"),
+            Self::PlayedAt(order) => format!("History.played_at {order}"),
+            Self::Popularity(order) => format!("Audio.popularity {order}"),
+            Self::CollectionPosition(order) => format!("CollectionItem.position {order}"),
         }
     }
 }
@@ -635,6 +1423,10 @@ mod tests {
     use r2d2_sqlite::SqliteConnectionManager;
 
     use super::*;
+    use crate::db::{
+        CollectionsTable, EmojiBindingsTable, FavoritesTable, MemberSoundsTable, PlayHistoryEntry,
+        PlayHistoryTable, PlaySource, SchedulesTable, TriggersTable,
+    };
 
     fn get_db_connection() -> DbConnection {
         let db_manager = SqliteConnectionManager::memory();
@@ -713,6 +1505,151 @@ mod tests {
         assert_eq!(updated_row.name, new_name);
     }
 
+    #[test]
+    fn table_aliases_test() {
+        let table = get_audio_table();
+        table.create_table();
+
+        for name in ["airhorn", "darth vader"] {
+            let mut row_insert = make_audio_table_row_insert();
+            row_insert.name = name.into();
+            table.insert_audio_row(row_insert).unwrap();
+        }
+
+        let mut row = table
+            .find_audio_row(UniqueAudioTableCol::Name("darth vader".into()))
+            .unwrap();
+        assert!(row.aliases.is_empty());
+
+        row.aliases = vec!["vader".into(), "Anakin".into()];
+        table.update_audio_row(&row).unwrap();
+
+        // find by alias, ignoring case
+        let found = table
+            .find_audio_row(UniqueAudioTableCol::Name("anakin".into()))
+            .unwrap();
+        assert_eq!(found.name, "darth vader");
+        assert_eq!(found.aliases, vec!["vader", "Anakin"]);
+
+        // full text & fuzzy search aliases
+        assert_eq!(
+            table.fts_autocomplete_track_names("anak", None),
+            vec!["darth vader"]
+        );
+        assert_eq!(
+            table.autocomplete_track_names("anakn", None),
+            vec!["darth vader"]
+        );
+
+        // names & aliases are unique across each other
+        let mut other = table
+            .find_audio_row(UniqueAudioTableCol::Name("airhorn".into()))
+            .unwrap();
+        other.aliases = vec!["VADER".into()];
+        assert!(table.update_audio_row(&other).is_err());
+        other.aliases = vec!["Darth Vader".into()];
+        assert!(table.update_audio_row(&other).is_err());
+        other.aliases = vec!["horn".into(), "HORN".into()];
+        assert!(table.update_audio_row(&other).is_err());
+        other.aliases = vec![];
+        other.name = "vader".into();
+        assert!(table.update_audio_row(&other).is_err());
+
+        let mut row_insert = make_audio_table_row_insert();
+        row_insert.name = "anakin".into();
+        assert!(table.insert_audio_row(row_insert).is_err());
+
+        // failed updates are rolled back
+        let found = table
+            .find_audio_row(UniqueAudioTableCol::Name("airhorn".into()))
+            .unwrap();
+        assert!(found.aliases.is_empty());
+
+        // removing aliases
+        row.aliases = vec!["vader".into()];
+        table.update_audio_row(&row).unwrap();
+        assert!(table
+            .find_audio_row(UniqueAudioTableCol::Name("anakin".into()))
+            .is_none());
+        assert!(table.fts_autocomplete_track_names("anak", None).is_empty());
+    }
+
+    #[test]
+    fn table_button_style_test() {
+        let table = get_audio_table();
+        table.create_table();
+        table
+            .insert_audio_row(make_audio_table_row_insert())
+            .unwrap();
+
+        let mut row = table.find_audio_row(UniqueAudioTableCol::Id(1)).unwrap();
+        assert_eq!(row.button_style, None);
+
+        row.button_style = Some(SoundButtonStyle::Danger);
+        table.update_audio_row(&row).unwrap();
+        let row = table.find_audio_row(UniqueAudioTableCol::Id(1)).unwrap();
+        assert_eq!(row.button_style, Some(SoundButtonStyle::Danger));
+
+        assert_eq!(
+            SoundButtonStyle::try_from(" Secondary "),
+            Ok(SoundButtonStyle::Secondary)
+        );
+        assert!(SoundButtonStyle::try_from("grey").is_err());
+    }
+
+    #[test]
+    fn table_aliases_migration_test() {
+        let table = get_audio_table();
+
+        // full text table created by older bot versions, without aliases
+        table
+            .conn
+            .execute_batch(
+                "
+                CREATE TABLE audio (
+                    id INTEGER PRIMARY KEY,
+                    name VARCHAR(80) NOT NULL UNIQUE,
+                    tags VARCHAR(2048),
+                    audio_file VARCHAR(500) NOT NULL UNIQUE,
+                    created_at VARCHAR(25) NOT NULL,
+                    author_id INTEGER,
+                    author_name VARCHAR(256),
+                    author_global_name VARCHAR(256),
+                    play_count INTEGER DEFAULT 0,
+                    last_played_at VARCHAR(25) DEFAULT NULL,
+                    popularity REAL DEFAULT 0,
+                    pinned BOOLEAN DEFAULT FALSE
+                );
+
+                CREATE VIRTUAL TABLE fts5_audio USING FTS5(
+                    name, tags, content=audio, content_rowid=id, tokenize='trigram remove_diacritics 1'
+                );
+
+                INSERT INTO audio (name, tags, audio_file, created_at)
+                    VALUES ('airhorn', 'loud', '/tmp/airhorn.mp3', '2024-01-01T00:00:00Z');",
+            )
+            .unwrap();
+
+        table.create_table();
+
+        // existing tracks are reindexed
+        assert_eq!(
+            table.fts_autocomplete_track_names("airh", None),
+            vec!["airhorn"]
+        );
+
+        let mut row = table
+            .find_audio_row(UniqueAudioTableCol::Name("airhorn".into()))
+            .unwrap();
+        row.aliases = vec!["honk".into()];
+        table.update_audio_row(&row).unwrap();
+
+        assert_eq!(
+            table.fts_autocomplete_track_names("honk", None),
+            vec!["airhorn"]
+        );
+    }
+
     #[test]
     fn table_autocomplete_track_names_test() {
         let table = get_audio_table();
@@ -751,6 +1688,282 @@ mod tests {
         assert_eq!("Beez's Biz", results[0]);
     }
 
+    #[test]
+    fn table_fuzzy_autocomplete_track_names_test() {
+        let table = get_audio_table();
+        table.create_table();
+
+        for (name, tags) in [
+            ("airhorn", "loud"),
+            ("darth vader", "star-wars sith"),
+            ("yoda", "star-wars jedi"),
+        ] {
+            let mut row_insert = make_audio_table_row_insert();
+            row_insert.name = name.into();
+            row_insert.tags = Tags::from(tags);
+            table.insert_audio_row(row_insert).unwrap();
+        }
+
+        // misspellings full text search misses
+        assert!(table
+            .fts_autocomplete_track_names("airhonr", None)
+            .is_empty());
+        assert_eq!(
+            table.autocomplete_track_names("airhonr", None),
+            vec!["airhorn"]
+        );
+        assert_eq!(
+            table.autocomplete_track_names("vadr", None),
+            vec!["darth vader"]
+        );
+        assert_eq!(
+            table.autocomplete_track_names("stawars", None),
+            vec!["darth vader", "yoda"]
+        );
+
+        // short queries
+        assert_eq!(table.autocomplete_track_names("yo", None), vec!["yoda"]);
+        assert_eq!(table.autocomplete_track_names("", None).len(), 3);
+        assert_eq!(table.autocomplete_track_names("qq", None).len(), 3);
+
+        // full text matches first, then fuzzy matches
+        assert_eq!(
+            table.autocomplete_track_names("star", None),
+            vec!["darth vader", "yoda"]
+        );
+
+        let rows = table
+            .fuzzy_find_audio_rows("darth vadr", vars::FUZZY_MIN_SCORE, 5)
+            .unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].0.name, "darth vader");
+
+        table
+            .trash_audio_row(UniqueAudioTableCol::Name("yoda".into()))
+            .unwrap();
+        assert!(table.autocomplete_track_names("yoda", None).is_empty());
+    }
+
+    #[test]
+    fn table_count_by_author_test() {
+        let table = get_audio_table();
+        table.create_table();
+
+        for (author_id, guild_id) in [(1, Some(10)), (1, None), (1, Some(20)), (2, Some(10))] {
+            let mut row_insert = make_audio_table_row_insert();
+            row_insert.author_id = Some(author_id);
+            row_insert.guild_id = guild_id;
+            table.insert_audio_row(row_insert).unwrap();
+        }
+
+        assert_eq!(table.count_audio_rows_by_author(10, 1).unwrap(), 2);
+        assert_eq!(table.count_audio_rows_by_author(20, 1).unwrap(), 2);
+        assert_eq!(table.count_audio_rows_by_author(10, 2).unwrap(), 1);
+        assert_eq!(table.count_audio_rows_by_author(10, 3).unwrap(), 0);
+
+        // trashed tracks don't count
+        let mut row_insert = make_audio_table_row_insert();
+        row_insert.author_id = Some(2);
+        row_insert.guild_id = Some(10);
+        let name = row_insert.name.clone();
+        table.insert_audio_row(row_insert).unwrap();
+        table
+            .trash_audio_row(UniqueAudioTableCol::Name(name))
+            .unwrap();
+        assert_eq!(table.count_audio_rows_by_author(10, 2).unwrap(), 1);
+
+        // trashed tracks still count as uploads
+        let yesterday = chrono::Utc::now() - chrono::Duration::days(1);
+        let tomorrow = chrono::Utc::now() + chrono::Duration::days(1);
+        assert_eq!(
+            table
+                .count_audio_rows_by_author_since(10, 2, yesterday)
+                .unwrap(),
+            2
+        );
+        assert_eq!(
+            table
+                .count_audio_rows_by_author_since(10, 2, tomorrow)
+                .unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn table_guild_library_stats_test() {
+        let table = get_audio_table();
+        table.create_table();
+
+        let now = chrono::Utc::now();
+        // (author id, guild id, duration secs, created at)
+        let rows = [
+            (1u64, Some(10u64), 2, now),
+            (1, Some(10), 3, now - chrono::Duration::days(10)),
+            (2, Some(10), 4, now),
+            (3, None, 5, now),
+            (4, Some(20), 6, now),
+        ];
+        for (author_id, guild_id, secs, created_at) in rows {
+            table
+                .insert_audio_row(
+                    AudioTableRowInsertBuilder::new(
+                        uuid_v4_str(),
+                        make_audio_table_row_insert().audio_file,
+                    )
+                    .author_id(Some(author_id))
+                    .guild_id(guild_id)
+                    .duration(Some(std::time::Duration::from_secs(secs)))
+                    .created_at(created_at)
+                    .build(),
+                )
+                .unwrap();
+        }
+
+        let (count, duration) = table.guild_library_duration(10).unwrap();
+        assert_eq!(count, 4);
+        assert_eq!(duration, std::time::Duration::from_secs(14));
+
+        assert_eq!(
+            table.top_uploaders(10, None, 5).unwrap(),
+            vec![(1, 2), (2, 1), (3, 1)]
+        );
+        assert_eq!(
+            table
+                .top_uploaders(10, Some(now - chrono::Duration::days(1)), 1)
+                .unwrap(),
+            vec![(1, 1)]
+        );
+    }
+
+    #[test]
+    fn table_guild_library_size_test() {
+        let table = get_audio_table();
+        table.create_table();
+
+        for guild_id in [Some(10), Some(10), None, Some(20)] {
+            let mut row_insert = make_audio_table_row_insert();
+            row_insert.guild_id = guild_id;
+            table.insert_audio_row(row_insert).unwrap();
+        }
+
+        // sizes are summed from the file size column
+        assert_eq!(table.guild_library_size(10).unwrap(), 0);
+        assert_eq!(table.list_rows_missing_file_size().unwrap().len(), 4);
+        for row in table.list_rows_missing_file_size().unwrap() {
+            table
+                .update_audio_row_file_size(row.id, row.id as u64 * 100)
+                .unwrap();
+        }
+        assert!(table.list_rows_missing_file_size().unwrap().is_empty());
+        assert_eq!(table.guild_library_size(10).unwrap(), 100 + 200 + 300);
+        assert_eq!(table.guild_library_size(20).unwrap(), 300 + 400);
+    }
+
+    /// Audio table, and pool of its database where the tables referencing audio tracks are created
+    fn get_audio_table_with_dependents() -> (AudioTable, r2d2::Pool<SqliteConnectionManager>) {
+        let db_manager = SqliteConnectionManager::memory();
+        let db_pool = r2d2::Pool::new(db_manager).unwrap();
+        let table = AudioTable::new(db_pool.get().unwrap());
+        table.create_table();
+
+        let dependents: Vec<Box<dyn Table>> = vec![
+            Box::new(FavoritesTable::new(db_pool.get().unwrap())),
+            Box::new(CollectionsTable::new(db_pool.get().unwrap())),
+            Box::new(MemberSoundsTable::new(db_pool.get().unwrap())),
+            Box::new(SchedulesTable::new(db_pool.get().unwrap())),
+            Box::new(TriggersTable::new(db_pool.get().unwrap())),
+            Box::new(EmojiBindingsTable::new(db_pool.get().unwrap())),
+            Box::new(PlayHistoryTable::new(db_pool.get().unwrap())),
+        ];
+        for dependent in dependents.iter() {
+            dependent.create_table();
+        }
+
+        (table, db_pool)
+    }
+
+    #[test]
+    fn table_trash_restore_purge_test() {
+        let (table, _db_pool) = get_audio_table_with_dependents();
+
+        let mut row_insert = make_audio_table_row_insert();
+        row_insert.name = "Beep Boop".into();
+        let name = row_insert.name.clone();
+        table.insert_audio_row(row_insert).unwrap();
+
+        let col = UniqueAudioTableCol::Name(name.clone());
+        table.trash_audio_row(&col).unwrap();
+        assert!(table.trash_audio_row(&col).is_err());
+
+        assert!(table.find_audio_row(&col).is_none());
+        assert!(table.find_trashed_audio_row(&col).is_some());
+        assert!(table.fts_autocomplete_track_names("beep", None).is_empty());
+        assert!(table.fts_autocomplete_track_names("", None).is_empty());
+        assert!(table.get_random_row().unwrap().is_none());
+        assert_eq!(
+            table.autocomplete_trashed_track_names("eep", None),
+            vec![name.clone()]
+        );
+
+        table.restore_audio_row(&col).unwrap();
+        assert!(table.find_audio_row(&col).is_some());
+        assert!(table.find_trashed_audio_row(&col).is_none());
+        assert!(table.restore_audio_row(&col).is_err());
+
+        // purge only rows trashed before cutoff
+        table.trash_audio_row(&col).unwrap();
+        let an_hour_ago = chrono::Utc::now() - chrono::Duration::hours(1);
+        assert_eq!(table.purge_trashed_rows(an_hour_ago).unwrap(), 0);
+        assert!(table.find_trashed_audio_row(&col).is_some());
+
+        let in_an_hour = chrono::Utc::now() + chrono::Duration::hours(1);
+        assert_eq!(table.purge_trashed_rows(in_an_hour).unwrap(), 1);
+        assert!(table.find_trashed_audio_row(&col).is_none());
+    }
+
+    #[test]
+    fn purge_deletes_dependent_rows_test() {
+        let (table, db_pool) = get_audio_table_with_dependents();
+        let favorites = FavoritesTable::new(db_pool.get().unwrap());
+        let history = PlayHistoryTable::new(db_pool.get().unwrap());
+
+        let mut audio_ids = vec![];
+        for name in ["purged", "kept"] {
+            let mut row_insert = make_audio_table_row_insert();
+            row_insert.name = name.into();
+            table.insert_audio_row(row_insert).unwrap();
+
+            let audio_id = table
+                .find_audio_row(UniqueAudioTableCol::Name(name.into()))
+                .unwrap()
+                .id;
+            favorites.add_favorite(1, audio_id).unwrap();
+            history
+                .insert_entry(&PlayHistoryEntry::new(audio_id, 10u64, PlaySource::Slash))
+                .unwrap();
+            audio_ids.push(audio_id);
+        }
+
+        table
+            .trash_audio_row(UniqueAudioTableCol::Name("purged".into()))
+            .unwrap();
+        let in_an_hour = chrono::Utc::now() + chrono::Duration::hours(1);
+        assert_eq!(table.purge_trashed_rows(in_an_hour).unwrap(), 1);
+
+        assert!(!favorites.is_favorite(1, audio_ids[0]).unwrap());
+        assert!(favorites.is_favorite(1, audio_ids[1]).unwrap());
+
+        let plays: Vec<i64> = history
+            .connection()
+            .prepare("SELECT audio_id FROM play_history")
+            .unwrap()
+            .query_map((), |row| row.get(0))
+            .unwrap()
+            .map(|row| row.unwrap())
+            .collect();
+        assert_eq!(plays, vec![audio_ids[1]]);
+    }
+
     #[test]
     fn tags_test() {
         let tags = Tags::from("tag-1, tag_2, tag3, !#$%^&tag4&*(()\ttag5");
@@ -0,0 +1,395 @@
+use rusqlite::{params, types::FromSql, ToSql};
+
+use crate::{commands::PoiseError, common::LogResult};
+
+use super::{DbConnection, Table};
+
+#[derive(Debug, Clone, Copy, PartialEq, poise::ChoiceParameter)]
+pub enum AuditAction {
+    #[name = "Add"]
+    Add,
+    #[name = "Edit"]
+    Edit,
+    #[name = "Rename"]
+    Rename,
+    #[name = "Tag Change"]
+    TagChange,
+    #[name = "Collection Change"]
+    CollectionChange,
+    #[name = "Pin"]
+    Pin,
+    #[name = "Unpin"]
+    Unpin,
+    #[name = "Remove"]
+    Remove,
+    #[name = "Restore"]
+    Restore,
+    #[name = "Settings Change"]
+    SettingsChange,
+}
+
+impl AuditAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Add => "add",
+            Self::Edit => "edit",
+            Self::Rename => "rename",
+            Self::TagChange => "tag_change",
+            Self::CollectionChange => "collection_change",
+            Self::Pin => "pin",
+            Self::Unpin => "unpin",
+            Self::Remove => "remove",
+            Self::Restore => "restore",
+            Self::SettingsChange => "settings_change",
+        }
+    }
+}
+
+impl TryFrom<&str> for AuditAction {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "add" => Ok(Self::Add),
+            "edit" => Ok(Self::Edit),
+            "rename" => Ok(Self::Rename),
+            "tag_change" => Ok(Self::TagChange),
+            "collection_change" => Ok(Self::CollectionChange),
+            "pin" => Ok(Self::Pin),
+            "unpin" => Ok(Self::Unpin),
+            "remove" => Ok(Self::Remove),
+            "restore" => Ok(Self::Restore),
+            "settings_change" => Ok(Self::SettingsChange),
+            val => Err(format!("Unknown audit action: {val}")),
+        }
+    }
+}
+
+impl ToSql for AuditAction {
+    fn to_sql(&self) -> rusqlite::Result<rusqlite::types::ToSqlOutput<'_>> {
+        self.as_str().to_sql()
+    }
+}
+
+impl FromSql for AuditAction {
+    fn column_result(value: rusqlite::types::ValueRef<'_>) -> rusqlite::types::FromSqlResult<Self> {
+        AuditAction::try_from(value.as_str()?)
+            .map_err(|err| rusqlite::types::FromSqlError::Other(err.into()))
+    }
+}
+
+/// Audit log entry to insert. `target` is the audio track name or setting changed
+pub struct AuditLogEntry {
+    pub guild_id: u64,
+    pub actor_id: u64,
+    pub actor_name: String,
+    pub action: AuditAction,
+    pub target: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+impl AuditLogEntry {
+    pub fn new(
+        guild_id: impl Into<u64>,
+        actor: &serenity::all::User,
+        action: AuditAction,
+        target: impl Into<String>,
+    ) -> Self {
+        Self {
+            guild_id: guild_id.into(),
+            actor_id: actor.id.into(),
+            actor_name: actor.name.clone(),
+            action,
+            target: target.into(),
+            before: None,
+            after: None,
+        }
+    }
+
+    pub fn before(mut self, before: Option<impl Into<String>>) -> Self {
+        self.before = before.map(|val| val.into());
+        self
+    }
+
+    pub fn after(mut self, after: Option<impl Into<String>>) -> Self {
+        self.after = after.map(|val| val.into());
+        self
+    }
+}
+
+pub struct AuditLogRow {
+    #[allow(dead_code)]
+    pub id: i64,
+    #[allow(dead_code)]
+    pub guild_id: u64,
+    pub actor_id: u64,
+    #[allow(dead_code)]
+    pub actor_name: String,
+    pub action: AuditAction,
+    pub target: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl TryFrom<&rusqlite::Row<'_>> for AuditLogRow {
+    type Error = rusqlite::Error;
+
+    fn try_from(row: &rusqlite::Row<'_>) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: row.get("id")?,
+            guild_id: row.get("guild_id")?,
+            actor_id: row.get("actor_id")?,
+            actor_name: row.get("actor_name")?,
+            action: row.get("action")?,
+            target: row.get("target")?,
+            before: row.get("before")?,
+            after: row.get("after")?,
+            created_at: row.get("created_at")?,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AuditLogFilter {
+    pub guild_id: u64,
+    pub action: Option<AuditAction>,
+    pub actor_id: Option<u64>,
+}
+
+impl AuditLogFilter {
+    fn where_sql(&self) -> (String, Vec<&dyn ToSql>) {
+        let mut where_sql = vec!["guild_id = ?"];
+        let mut params: Vec<&dyn ToSql> = vec![&self.guild_id];
+
+        if let Some(action) = self.action.as_ref() {
+            where_sql.push("action = ?");
+            params.push(action);
+        }
+
+        if let Some(actor_id) = self.actor_id.as_ref() {
+            where_sql.push("actor_id = ?");
+            params.push(actor_id);
+        }
+
+        (format!("WHERE {}", where_sql.join(" AND ")), params)
+    }
+}
+
+pub struct AuditLogTable {
+    conn: DbConnection,
+}
+
+impl AuditLogTable {
+    pub const TABLE_NAME: &'static str = "audit_log";
+
+    pub fn new(connection: DbConnection) -> Self {
+        Self { conn: connection }
+    }
+
+    pub fn insert_entry(&self, entry: &AuditLogEntry) -> Result<(), PoiseError> {
+        log::info!(
+            "Audit log. Actor: {}, Action: {}, Target: {}",
+            entry.actor_name,
+            entry.action.as_str(),
+            entry.target
+        );
+
+        let table_name = Self::TABLE_NAME;
+        let sql = format!(
+            "
+            INSERT INTO {table_name}
+                (guild_id, actor_id, actor_name, action, target, before, after, created_at)
+            VALUES
+                (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)"
+        );
+
+        self.conn
+            .execute(
+                sql.as_str(),
+                params![
+                    &entry.guild_id,
+                    &entry.actor_id,
+                    &entry.actor_name,
+                    &entry.action,
+                    &entry.target,
+                    &entry.before,
+                    &entry.after,
+                    &chrono::Utc::now(),
+                ],
+            )
+            .log_err_msg("Failed to insert audit log entry")?;
+
+        Ok(())
+    }
+
+    /// Newest entries first
+    pub fn list_entries(
+        &self,
+        filter: &AuditLogFilter,
+        limit: u64,
+        offset: u64,
+    ) -> Result<Vec<AuditLogRow>, PoiseError> {
+        let table_name = Self::TABLE_NAME;
+        let (where_sql, params) = filter.where_sql();
+        let sql = format!(
+            "SELECT * FROM {table_name}
+            {where_sql}
+            ORDER BY id DESC
+            LIMIT {limit}
+            OFFSET {offset}"
+        );
+
+        let mut stmt = self
+            .conn
+            .prepare(sql.as_str())
+            .log_err_msg("Failed preparing audit log sql")?;
+
+        let rows = stmt
+            .query_map(params.as_slice(), |row| AuditLogRow::try_from(row))
+            .log_err_msg("Failed querying audit log")?
+            .filter_map(|row| row.log_err().ok())
+            .collect();
+
+        Ok(rows)
+    }
+
+    pub fn count_entries(&self, filter: &AuditLogFilter) -> Result<u64, PoiseError> {
+        let table_name = Self::TABLE_NAME;
+        let (where_sql, params) = filter.where_sql();
+        let sql = format!("SELECT COUNT(id) FROM {table_name} {where_sql}");
+
+        let count: u64 = self
+            .conn
+            .query_row(sql.as_str(), params.as_slice(), |row| row.get(0))
+            .log_err_msg("Failed counting audit log")?;
+
+        Ok(count)
+    }
+}
+
+impl Table for AuditLogTable {
+    fn connection(&self) -> &DbConnection {
+        &self.conn
+    }
+
+    fn drop_table(&self) {
+        let table_name = Self::TABLE_NAME;
+        log::info!("Dropping table: {table_name}");
+        let sql = format!("DROP TABLE IF EXISTS {table_name};");
+
+        self.conn
+            .execute_batch(sql.as_str())
+            .log_err_msg("Failed dropping table")
+            .log_ok_msg(format!("Dropped table {table_name}"))
+            .unwrap();
+    }
+
+    fn create_table(&self) {
+        let table_name = Self::TABLE_NAME;
+        log::info!("Creating table: {table_name}");
+        let sql = format!(
+            "
+            CREATE TABLE IF NOT EXISTS {table_name} (
+                id INTEGER PRIMARY KEY,
+                guild_id INTEGER NOT NULL,
+                actor_id INTEGER NOT NULL,
+                actor_name VARCHAR(256) NOT NULL,
+                action VARCHAR(32) NOT NULL,
+                target VARCHAR(256) NOT NULL,
+                before TEXT,
+                after TEXT,
+                created_at VARCHAR(25) NOT NULL
+            );
+
+            CREATE INDEX IF NOT EXISTS {table_name}_guild_id_idx ON {table_name}(guild_id);
+        "
+        );
+
+        self.conn
+            .execute_batch(sql.as_str())
+            .log_err_msg("Failed create table")
+            .log_ok_msg(format!("Created table {table_name}"))
+            .unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use r2d2_sqlite::SqliteConnectionManager;
+
+    use super::*;
+
+    fn get_audit_log_table() -> AuditLogTable {
+        let db_manager = SqliteConnectionManager::memory();
+        let db_pool = r2d2::Pool::new(db_manager).unwrap();
+        AuditLogTable::new(db_pool.get().unwrap())
+    }
+
+    fn make_entry(guild_id: u64, actor_id: u64, action: AuditAction) -> AuditLogEntry {
+        AuditLogEntry {
+            guild_id,
+            actor_id,
+            actor_name: "actor".into(),
+            action,
+            target: "Beep Boop".into(),
+            before: None,
+            after: None,
+        }
+    }
+
+    #[test]
+    fn table_create_test() {
+        let table = get_audit_log_table();
+        table.create_table();
+        table.create_table();
+    }
+
+    #[test]
+    fn insert_and_filter_entries_test() {
+        let table = get_audit_log_table();
+        table.create_table();
+
+        table
+            .insert_entry(&make_entry(1, 10, AuditAction::Add))
+            .unwrap();
+        table
+            .insert_entry(
+                &make_entry(1, 11, AuditAction::Rename)
+                    .before(Some("Beep"))
+                    .after(Some("Beep Boop")),
+            )
+            .unwrap();
+        table
+            .insert_entry(&make_entry(1, 10, AuditAction::Remove))
+            .unwrap();
+        table
+            .insert_entry(&make_entry(2, 10, AuditAction::Add))
+            .unwrap();
+
+        let mut filter = AuditLogFilter {
+            guild_id: 1,
+            action: None,
+            actor_id: None,
+        };
+        assert_eq!(table.count_entries(&filter).unwrap(), 3);
+
+        let rows = table.list_entries(&filter, 2, 0).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].action, AuditAction::Remove);
+        assert_eq!(rows[1].action, AuditAction::Rename);
+        assert_eq!(rows[1].before.as_deref(), Some("Beep"));
+        assert_eq!(rows[1].after.as_deref(), Some("Beep Boop"));
+
+        let rows = table.list_entries(&filter, 2, 2).unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].action, AuditAction::Add);
+
+        filter.actor_id = Some(10);
+        assert_eq!(table.count_entries(&filter).unwrap(), 2);
+
+        filter.action = Some(AuditAction::Add);
+        assert_eq!(table.count_entries(&filter).unwrap(), 1);
+    }
+}
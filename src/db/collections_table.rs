@@ -0,0 +1,368 @@
+use rusqlite::params;
+
+use crate::{commands::PoiseError, common::LogResult};
+
+use super::{AudioTable, DbConnection, Table};
+
+#[derive(Debug)]
+pub struct CollectionRow {
+    pub id: i64,
+    #[allow(dead_code)]
+    pub guild_id: u64,
+    pub name: String,
+    #[allow(dead_code)]
+    pub author_id: Option<u64>,
+    #[allow(dead_code)]
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl TryFrom<&rusqlite::Row<'_>> for CollectionRow {
+    type Error = rusqlite::Error;
+
+    fn try_from(row: &rusqlite::Row<'_>) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: row.get("id")?,
+            guild_id: row.get("guild_id")?,
+            name: row.get("name")?,
+            author_id: row.get("author_id")?,
+            created_at: row.get("created_at")?,
+        })
+    }
+}
+
+/// Named, ordered collections of audio tracks per guild. ie: "Star Wars", "Movie night"
+pub struct CollectionsTable {
+    conn: DbConnection,
+}
+
+impl CollectionsTable {
+    pub const TABLE_NAME: &'static str = "collections";
+    /// Audio tracks of each collection, in play order
+    pub const ITEMS_TABLE_NAME: &'static str = "collection_audio";
+
+    pub fn new(connection: DbConnection) -> Self {
+        Self { conn: connection }
+    }
+
+    pub fn create_collection(
+        &self,
+        guild_id: u64,
+        name: impl AsRef<str>,
+        author_id: Option<u64>,
+    ) -> Result<i64, PoiseError> {
+        let name = name.as_ref();
+        log::info!("Creating collection. Guild id: {guild_id}, Name: {name}");
+
+        let table_name = Self::TABLE_NAME;
+        let sql = format!(
+            "INSERT INTO {table_name} (guild_id, name, author_id, created_at) VALUES (?1, ?2, ?3, ?4)"
+        );
+
+        self.conn
+            .execute(
+                sql.as_str(),
+                params![&guild_id, name, &author_id, &chrono::Utc::now()],
+            )
+            .log_err_msg("Failed inserting collection")?;
+
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// Deletes collection & its audio track list. Returns false if collection didn't exist
+    pub fn delete_collection(&self, collection_id: i64) -> Result<bool, PoiseError> {
+        log::info!("Deleting collection. Id: {collection_id}");
+
+        let table_name = Self::TABLE_NAME;
+        let items_table_name = Self::ITEMS_TABLE_NAME;
+        let sql = format!("DELETE FROM {items_table_name} WHERE collection_id = ?1");
+        self.conn
+            .execute(sql.as_str(), params![&collection_id])
+            .log_err_msg("Failed deleting collection audio tracks")?;
+
+        let sql = format!("DELETE FROM {table_name} WHERE id = ?1");
+        let count = self
+            .conn
+            .execute(sql.as_str(), params![&collection_id])
+            .log_err_msg("Failed deleting collection")?;
+
+        Ok(count > 0)
+    }
+
+    /// Find collection in guild by name, ignoring case
+    pub fn find_collection(&self, guild_id: u64, name: impl AsRef<str>) -> Option<CollectionRow> {
+        let table_name = Self::TABLE_NAME;
+        let sql = format!("SELECT * FROM {table_name} WHERE guild_id = ?1 AND name = ?2");
+
+        self.conn
+            .query_row(sql.as_str(), params![&guild_id, name.as_ref()], |row| {
+                CollectionRow::try_from(row)
+            })
+            .ok()
+    }
+
+    /// Collections of guild, with their number of non-trashed audio tracks
+    pub fn list_collections(&self, guild_id: u64) -> Result<Vec<(CollectionRow, u64)>, PoiseError> {
+        let table_name = Self::TABLE_NAME;
+        let items_table_name = Self::ITEMS_TABLE_NAME;
+        let audio_table_name = AudioTable::TABLE_NAME;
+        let sql = format!(
+            "SELECT Collection.*, COUNT(Audio.id) AS audio_count FROM {table_name} Collection
+            LEFT JOIN {items_table_name} Item ON Item.collection_id = Collection.id
+            LEFT JOIN {audio_table_name} Audio
+                ON Audio.id = Item.audio_id AND Audio.deleted_at IS NULL
+            WHERE Collection.guild_id = ?1
+            GROUP BY Collection.id
+            ORDER BY Collection.name COLLATE NOCASE ASC"
+        );
+
+        let mut stmt = self
+            .conn
+            .prepare(sql.as_str())
+            .log_err_msg("Failed preparing list collections sql")?;
+
+        let rows = stmt
+            .query_map(params![&guild_id], |row| {
+                Ok((CollectionRow::try_from(row)?, row.get("audio_count")?))
+            })
+            .log_err_msg("Failed querying collections")?
+            .filter_map(|row| row.log_err().ok())
+            .collect();
+
+        Ok(rows)
+    }
+
+    /// Appends audio track to end of collection. Returns false if it was already in the collection
+    pub fn add_audio(&self, collection_id: i64, audio_id: i64) -> Result<bool, PoiseError> {
+        log::info!(
+            "Adding audio to collection. Collection id: {collection_id}, Audio id: {audio_id}"
+        );
+
+        let items_table_name = Self::ITEMS_TABLE_NAME;
+        let sql = format!(
+            "INSERT OR IGNORE INTO {items_table_name} (collection_id, audio_id, position, added_at)
+            VALUES (
+                ?1,
+                ?2,
+                (SELECT COALESCE(MAX(position), 0) + 1 FROM {items_table_name} WHERE collection_id = ?1),
+                ?3
+            )"
+        );
+
+        let count = self
+            .conn
+            .execute(
+                sql.as_str(),
+                params![&collection_id, &audio_id, &chrono::Utc::now()],
+            )
+            .log_err_msg("Failed adding audio to collection")?;
+
+        Ok(count > 0)
+    }
+
+    /// Removes audio track from collection. Returns false if it wasn't in the collection
+    pub fn remove_audio(&self, collection_id: i64, audio_id: i64) -> Result<bool, PoiseError> {
+        log::info!(
+            "Removing audio from collection. Collection id: {collection_id}, Audio id: {audio_id}"
+        );
+
+        let items_table_name = Self::ITEMS_TABLE_NAME;
+        let sql =
+            format!("DELETE FROM {items_table_name} WHERE collection_id = ?1 AND audio_id = ?2");
+
+        let count = self
+            .conn
+            .execute(sql.as_str(), params![&collection_id, &audio_id])
+            .log_err_msg("Failed removing audio from collection")?;
+
+        Ok(count > 0)
+    }
+
+    /// Names of guild's collections containing `partial`
+    pub fn autocomplete_collection_names(
+        &self,
+        guild_id: u64,
+        partial: impl AsRef<str>,
+        limit: Option<usize>,
+    ) -> Vec<String> {
+        let text = partial.as_ref();
+        let limit = limit.unwrap_or(5);
+        let table_name = Self::TABLE_NAME;
+
+        let sql = format!(
+            "SELECT name FROM {table_name}
+            WHERE guild_id = ?1 AND name LIKE '%' || ?2 || '%'
+            ORDER BY name COLLATE NOCASE ASC LIMIT {limit}"
+        );
+        let mut stmt = self
+            .conn
+            .prepare(sql.as_str())
+            .expect("Autocomplete collections sql invalid");
+
+        let rows = stmt.query_map(params![&guild_id, &text], |row| row.get("name"));
+        match rows {
+            Ok(rows) => rows.filter_map(|row| row.ok()).collect(),
+            Err(err) => {
+                log::error!("Autocomplete collections sql query error - {err}");
+                vec![]
+            }
+        }
+    }
+
+    /// Deletes collection entries of audio tracks that no longer exist. ie: purged from the trash
+    pub fn delete_orphaned_audio(conn: &rusqlite::Connection) -> Result<usize, PoiseError> {
+        let items_table_name = Self::ITEMS_TABLE_NAME;
+        let audio_table_name = AudioTable::TABLE_NAME;
+        let sql = format!(
+            "DELETE FROM {items_table_name} WHERE audio_id NOT IN (SELECT id FROM {audio_table_name})"
+        );
+
+        let count = conn
+            .execute(sql.as_str(), ())
+            .log_err_msg("Failed deleting orphaned collection audio tracks")?;
+
+        Ok(count)
+    }
+}
+
+impl Table for CollectionsTable {
+    fn connection(&self) -> &DbConnection {
+        &self.conn
+    }
+
+    fn drop_table(&self) {
+        let table_name = Self::TABLE_NAME;
+        let items_table_name = Self::ITEMS_TABLE_NAME;
+        log::info!("Dropping table: {table_name}");
+        let sql = format!(
+            "
+            DROP TABLE IF EXISTS {items_table_name};
+            DROP TABLE IF EXISTS {table_name};
+        "
+        );
+
+        self.conn
+            .execute_batch(sql.as_str())
+            .log_err_msg("Failed dropping table")
+            .log_ok_msg(format!("Dropped table {table_name}"))
+            .unwrap();
+    }
+
+    fn create_table(&self) {
+        let table_name = Self::TABLE_NAME;
+        let items_table_name = Self::ITEMS_TABLE_NAME;
+        log::info!("Creating table: {table_name}");
+        let sql = format!(
+            "
+            CREATE TABLE IF NOT EXISTS {table_name} (
+                id INTEGER PRIMARY KEY,
+                guild_id INTEGER NOT NULL,
+                name VARCHAR(32) NOT NULL COLLATE NOCASE,
+                author_id INTEGER,
+                created_at VARCHAR(25) NOT NULL,
+                UNIQUE (guild_id, name)
+            );
+
+            CREATE TABLE IF NOT EXISTS {items_table_name} (
+                collection_id INTEGER NOT NULL,
+                audio_id INTEGER NOT NULL,
+                position INTEGER NOT NULL,
+                added_at VARCHAR(25) NOT NULL,
+                PRIMARY KEY (collection_id, audio_id)
+            );
+
+            CREATE INDEX IF NOT EXISTS {items_table_name}_audio_id_idx
+                ON {items_table_name}(audio_id);
+        "
+        );
+
+        self.conn
+            .execute_batch(sql.as_str())
+            .log_err_msg("Failed create table")
+            .log_ok_msg(format!("Created table {table_name}"))
+            .unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use r2d2_sqlite::SqliteConnectionManager;
+
+    use crate::{
+        audio::AudioFile, db::audio_table::AudioTableRowInsertBuilder, helpers::uuid_v4_str,
+    };
+
+    use super::*;
+
+    #[test]
+    fn table_create_test() {
+        let db_manager = SqliteConnectionManager::memory();
+        let db_pool = r2d2::Pool::new(db_manager).unwrap();
+        let table = CollectionsTable::new(db_pool.get().unwrap());
+        table.create_table();
+        table.create_table();
+    }
+
+    #[test]
+    fn collections_test() {
+        let db_manager = SqliteConnectionManager::memory();
+        let db_pool = r2d2::Pool::new(db_manager).unwrap();
+        let audio_table = AudioTable::new(db_pool.get().unwrap());
+        let table = CollectionsTable::new(db_pool.get().unwrap());
+        audio_table.create_table();
+        table.create_table();
+
+        for name in ["vader", "yoda", "jaws"] {
+            let audio_file = AudioFile::new(format!("/tmp/{}.mp3", uuid_v4_str()).into());
+            audio_table
+                .insert_audio_row(AudioTableRowInsertBuilder::new(name, audio_file).build())
+                .unwrap();
+        }
+
+        let star_wars = table.create_collection(1, "Star Wars", Some(10)).unwrap();
+        let movies = table.create_collection(1, "Movie night", None).unwrap();
+        table.create_collection(2, "Star Wars", None).unwrap();
+
+        // names are unique per guild, ignoring case
+        assert!(table.create_collection(1, "star wars", None).is_err());
+        assert_eq!(table.find_collection(1, "STAR WARS").unwrap().id, star_wars);
+        assert!(table.find_collection(3, "Star Wars").is_none());
+
+        assert!(table.add_audio(star_wars, 2).unwrap());
+        assert!(table.add_audio(star_wars, 1).unwrap());
+        assert!(!table.add_audio(star_wars, 1).unwrap());
+        assert!(table.add_audio(movies, 3).unwrap());
+
+        let collections: Vec<_> = table
+            .list_collections(1)
+            .unwrap()
+            .into_iter()
+            .map(|(row, count)| (row.name, count))
+            .collect();
+        assert_eq!(
+            collections,
+            vec![("Movie night".to_string(), 1), ("Star Wars".to_string(), 2)]
+        );
+
+        assert_eq!(
+            table.autocomplete_collection_names(1, "star", None),
+            vec!["Star Wars"]
+        );
+
+        assert!(table.remove_audio(star_wars, 2).unwrap());
+        assert!(!table.remove_audio(star_wars, 2).unwrap());
+
+        assert!(table.delete_collection(movies).unwrap());
+        assert!(!table.delete_collection(movies).unwrap());
+        assert_eq!(table.list_collections(1).unwrap().len(), 1);
+
+        audio_table
+            .connection()
+            .execute("DELETE FROM audio WHERE id = 1", ())
+            .unwrap();
+        assert_eq!(
+            CollectionsTable::delete_orphaned_audio(&table.conn).unwrap(),
+            1
+        );
+        assert_eq!(table.list_collections(1).unwrap()[0].1, 0);
+    }
+}
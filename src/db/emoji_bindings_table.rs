@@ -0,0 +1,289 @@
+use std::collections::HashMap;
+
+use rusqlite::{params, OptionalExtension};
+use serenity::all::ReactionType;
+
+use crate::{commands::PoiseError, common::LogResult};
+
+use super::{AudioTable, DbConnection, Table};
+
+/// Key emoji bindings are looked up by. Custom emojis by id, since they can be renamed, and unicode
+/// emojis without variation selectors, since discord doesn't always send them the way they were typed
+pub fn emoji_key(emoji: &ReactionType) -> String {
+    match emoji {
+        ReactionType::Custom { id, .. } => id.to_string(),
+        ReactionType::Unicode(emoji) => emoji.replace('\u{fe0f}', ""),
+        _ => emoji.to_string(),
+    }
+}
+
+/// Emojis that play an audio track when reacted to messages of the guild they were bound in, and
+/// are shown on the track's buttons there. Each audio track has at most one per guild
+pub struct EmojiBindingsTable {
+    conn: DbConnection,
+}
+
+impl EmojiBindingsTable {
+    pub const TABLE_NAME: &'static str = "emoji_bindings";
+
+    pub fn new(connection: DbConnection) -> Self {
+        Self { conn: connection }
+    }
+
+    /// Binds emoji to audio track in guild, replacing the emoji's previous track & the track's
+    /// previous emoji in the guild
+    pub fn bind_emoji(
+        &self,
+        guild_id: u64,
+        emoji: &ReactionType,
+        audio_id: i64,
+    ) -> Result<(), PoiseError> {
+        log::info!("Binding emoji {emoji} to audio id: {audio_id}, guild_id: {guild_id}");
+
+        let table_name = Self::TABLE_NAME;
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .log_err_msg("Failed starting bind emoji transaction")?;
+
+        tx.execute(
+            format!(
+                "DELETE FROM {table_name}
+                WHERE guild_id = ?1 AND (emoji_key = ?2 OR audio_id = ?3)"
+            )
+            .as_str(),
+            params![&guild_id, &emoji_key(emoji), &audio_id],
+        )
+        .log_err_msg("Failed clearing previous emoji bindings")?;
+
+        tx.execute(
+            format!(
+                "INSERT INTO {table_name} (guild_id, emoji_key, emoji, audio_id)
+                VALUES (?1, ?2, ?3, ?4)"
+            )
+            .as_str(),
+            params![&guild_id, &emoji_key(emoji), &emoji.to_string(), &audio_id],
+        )
+        .log_err_msg("Failed inserting emoji binding")?;
+
+        tx.commit()
+            .log_err_msg("Failed committing bind emoji transaction")?;
+
+        Ok(())
+    }
+
+    /// Unbinds audio track's emoji in guild. Returns false if it had none
+    pub fn unbind_emoji(&self, guild_id: u64, audio_id: i64) -> Result<bool, PoiseError> {
+        log::info!("Unbinding emoji of audio id: {audio_id}, guild_id: {guild_id}");
+
+        let table_name = Self::TABLE_NAME;
+        let sql = format!("DELETE FROM {table_name} WHERE guild_id = ?1 AND audio_id = ?2");
+
+        let count = self
+            .conn
+            .execute(sql.as_str(), params![&guild_id, &audio_id])
+            .log_err_msg("Failed deleting emoji binding")?;
+
+        Ok(count > 0)
+    }
+
+    /// Audio track id bound to emoji in guild, if any
+    pub fn find_audio_id(
+        &self,
+        guild_id: u64,
+        emoji: &ReactionType,
+    ) -> Result<Option<i64>, PoiseError> {
+        let table_name = Self::TABLE_NAME;
+        let sql =
+            format!("SELECT audio_id FROM {table_name} WHERE guild_id = ?1 AND emoji_key = ?2");
+
+        Ok(self
+            .conn
+            .query_row(sql.as_str(), params![&guild_id, &emoji_key(emoji)], |row| {
+                row.get(0)
+            })
+            .optional()
+            .log_err_msg("Failed finding emoji binding")?)
+    }
+
+    /// Emoji bound to audio track in guild, if any
+    pub fn find_emoji(&self, guild_id: u64, audio_id: i64) -> Result<Option<String>, PoiseError> {
+        let table_name = Self::TABLE_NAME;
+        let sql = format!("SELECT emoji FROM {table_name} WHERE guild_id = ?1 AND audio_id = ?2");
+
+        Ok(self
+            .conn
+            .query_row(sql.as_str(), params![&guild_id, &audio_id], |row| {
+                row.get(0)
+            })
+            .optional()
+            .log_err_msg("Failed finding emoji of audio track")?)
+    }
+
+    /// Emojis bound in guild, by audio track id
+    pub fn guild_emojis(
+        conn: &rusqlite::Connection,
+        guild_id: u64,
+    ) -> Result<HashMap<i64, String>, PoiseError> {
+        let table_name = Self::TABLE_NAME;
+        let sql = format!("SELECT audio_id, emoji FROM {table_name} WHERE guild_id = ?1");
+
+        let mut stmt = conn
+            .prepare(sql.as_str())
+            .log_err_msg("Failed preparing guild emojis")?;
+
+        let emojis = stmt
+            .query_map(params![&guild_id], |row| Ok((row.get(0)?, row.get(1)?)))
+            .log_err_msg("Failed listing guild emojis")?
+            .filter_map(|row| row.log_err_msg("Failed reading emoji binding row").ok())
+            .collect();
+
+        Ok(emojis)
+    }
+
+    /// Deletes bindings of audio tracks that no longer exist. ie: purged from the trash
+    pub fn delete_orphaned_bindings(conn: &rusqlite::Connection) -> Result<usize, PoiseError> {
+        let table_name = Self::TABLE_NAME;
+        let audio_table_name = AudioTable::TABLE_NAME;
+        let sql = format!(
+            "DELETE FROM {table_name} WHERE audio_id NOT IN (SELECT id FROM {audio_table_name})"
+        );
+
+        let count = conn
+            .execute(sql.as_str(), ())
+            .log_err_msg("Failed deleting orphaned emoji bindings")?;
+
+        Ok(count)
+    }
+}
+
+impl Table for EmojiBindingsTable {
+    fn connection(&self) -> &DbConnection {
+        &self.conn
+    }
+
+    fn drop_table(&self) {
+        let table_name = Self::TABLE_NAME;
+        log::info!("Dropping table: {table_name}");
+        let sql = format!("DROP TABLE IF EXISTS {table_name};");
+
+        self.conn
+            .execute_batch(sql.as_str())
+            .log_err_msg("Failed dropping table")
+            .log_ok_msg(format!("Dropped table {table_name}"))
+            .unwrap();
+    }
+
+    fn create_table(&self) {
+        let table_name = Self::TABLE_NAME;
+        log::info!("Creating table: {table_name}");
+        let sql = format!(
+            "
+            CREATE TABLE IF NOT EXISTS {table_name} (
+                guild_id INTEGER NOT NULL,
+                emoji_key VARCHAR(100) NOT NULL,
+                emoji VARCHAR(100) NOT NULL,
+                audio_id INTEGER NOT NULL,
+                PRIMARY KEY (guild_id, emoji_key),
+                UNIQUE (guild_id, audio_id)
+            );
+        "
+        );
+
+        self.conn
+            .execute_batch(sql.as_str())
+            .log_err_msg("Failed create table")
+            .log_ok_msg(format!("Created table {table_name}"))
+            .unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use r2d2_sqlite::SqliteConnectionManager;
+    use serenity::all::EmojiId;
+
+    use super::*;
+
+    const GUILD_ID: u64 = 1;
+    const OTHER_GUILD_ID: u64 = 2;
+
+    #[test]
+    fn emoji_key_test() {
+        assert_eq!(
+            emoji_key(&ReactionType::Unicode("❤️".into())),
+            emoji_key(&ReactionType::Unicode("❤".into()))
+        );
+
+        let custom = |name: &str| ReactionType::Custom {
+            animated: false,
+            id: EmojiId::new(600404340292059257),
+            name: Some(name.into()),
+        };
+        assert_eq!(emoji_key(&custom("airhorn")), "600404340292059257");
+        assert_eq!(emoji_key(&custom("airhorn")), emoji_key(&custom("renamed")));
+    }
+
+    #[test]
+    fn table_create_test() {
+        let db_manager = SqliteConnectionManager::memory();
+        let db_pool = r2d2::Pool::new(db_manager).unwrap();
+        let table = EmojiBindingsTable::new(db_pool.get().unwrap());
+        table.create_table();
+        table.create_table();
+    }
+
+    #[test]
+    fn bind_emoji_test() {
+        let db_manager = SqliteConnectionManager::memory();
+        let db_pool = r2d2::Pool::new(db_manager).unwrap();
+        AudioTable::new(db_pool.get().unwrap()).create_table();
+        let table = EmojiBindingsTable::new(db_pool.get().unwrap());
+        table.create_table();
+
+        let horn = ReactionType::Unicode("📯".into());
+        let skull = ReactionType::Unicode("💀".into());
+        assert_eq!(table.find_audio_id(GUILD_ID, &horn).unwrap(), None);
+
+        table.bind_emoji(GUILD_ID, &horn, 1).unwrap();
+        assert_eq!(table.find_audio_id(GUILD_ID, &horn).unwrap(), Some(1));
+        assert_eq!(table.find_emoji(GUILD_ID, 1).unwrap(), Some("📯".into()));
+
+        // bindings are per guild
+        assert_eq!(table.find_audio_id(OTHER_GUILD_ID, &horn).unwrap(), None);
+        assert_eq!(table.find_emoji(OTHER_GUILD_ID, 1).unwrap(), None);
+        assert!(!table.unbind_emoji(OTHER_GUILD_ID, 1).unwrap());
+
+        // rebinding emoji moves it to the other track
+        table.bind_emoji(GUILD_ID, &horn, 2).unwrap();
+        assert_eq!(table.find_audio_id(GUILD_ID, &horn).unwrap(), Some(2));
+        assert_eq!(table.find_emoji(GUILD_ID, 1).unwrap(), None);
+
+        // tracks have one emoji per guild
+        table.bind_emoji(GUILD_ID, &skull, 2).unwrap();
+        assert_eq!(table.find_audio_id(GUILD_ID, &horn).unwrap(), None);
+        assert_eq!(table.find_emoji(GUILD_ID, 2).unwrap(), Some("💀".into()));
+
+        // binding in another guild leaves the guild's bindings alone
+        table.bind_emoji(OTHER_GUILD_ID, &horn, 2).unwrap();
+        assert_eq!(table.find_emoji(GUILD_ID, 2).unwrap(), Some("💀".into()));
+        assert_eq!(
+            EmojiBindingsTable::guild_emojis(&table.conn, OTHER_GUILD_ID).unwrap(),
+            HashMap::from([(2, "📯".to_string())])
+        );
+
+        assert!(table.unbind_emoji(GUILD_ID, 2).unwrap());
+        assert!(!table.unbind_emoji(GUILD_ID, 2).unwrap());
+        assert_eq!(
+            table.find_emoji(OTHER_GUILD_ID, 2).unwrap(),
+            Some("📯".into())
+        );
+
+        // bindings of purged tracks are deleted
+        assert_eq!(
+            EmojiBindingsTable::delete_orphaned_bindings(&table.conn).unwrap(),
+            1
+        );
+        assert_eq!(table.find_audio_id(OTHER_GUILD_ID, &horn).unwrap(), None);
+    }
+}
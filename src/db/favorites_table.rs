@@ -0,0 +1,228 @@
+use rusqlite::params;
+
+use crate::{commands::PoiseError, common::LogResult};
+
+use super::{AudioTable, DbConnection, Table};
+
+/// Audio tracks each user has favorited
+pub struct FavoritesTable {
+    conn: DbConnection,
+}
+
+impl FavoritesTable {
+    pub const TABLE_NAME: &'static str = "favorites";
+
+    pub fn new(connection: DbConnection) -> Self {
+        Self { conn: connection }
+    }
+
+    /// Favorites audio track for user. Returns false if it was already a favorite
+    pub fn add_favorite(&self, user_id: u64, audio_id: i64) -> Result<bool, PoiseError> {
+        log::info!("Adding favorite. User id: {user_id}, Audio id: {audio_id}");
+
+        let table_name = Self::TABLE_NAME;
+        let sql = format!(
+            "INSERT OR IGNORE INTO {table_name} (user_id, audio_id, created_at) VALUES (?1, ?2, ?3)"
+        );
+
+        let count = self
+            .conn
+            .execute(
+                sql.as_str(),
+                params![&user_id, &audio_id, &chrono::Utc::now()],
+            )
+            .log_err_msg("Failed inserting favorite")?;
+
+        Ok(count > 0)
+    }
+
+    /// Unfavorites audio track for user. Returns false if it wasn't a favorite
+    pub fn remove_favorite(&self, user_id: u64, audio_id: i64) -> Result<bool, PoiseError> {
+        log::info!("Removing favorite. User id: {user_id}, Audio id: {audio_id}");
+
+        let table_name = Self::TABLE_NAME;
+        let sql = format!("DELETE FROM {table_name} WHERE user_id = ?1 AND audio_id = ?2");
+
+        let count = self
+            .conn
+            .execute(sql.as_str(), params![&user_id, &audio_id])
+            .log_err_msg("Failed deleting favorite")?;
+
+        Ok(count > 0)
+    }
+
+    pub fn is_favorite(&self, user_id: u64, audio_id: i64) -> Result<bool, PoiseError> {
+        let table_name = Self::TABLE_NAME;
+        let sql = format!("SELECT COUNT(*) FROM {table_name} WHERE user_id = ?1 AND audio_id = ?2");
+
+        let count: u64 = self
+            .conn
+            .query_row(sql.as_str(), params![&user_id, &audio_id], |row| row.get(0))
+            .log_err_msg("Failed checking favorite")?;
+
+        Ok(count > 0)
+    }
+
+    /// Names of user's favorite, non-trashed, audio tracks containing `partial`
+    pub fn autocomplete_favorite_track_names(
+        &self,
+        user_id: u64,
+        partial: impl AsRef<str>,
+        limit: Option<usize>,
+    ) -> Vec<String> {
+        let text = partial.as_ref();
+        let limit = limit.unwrap_or(5);
+        let table_name = Self::TABLE_NAME;
+        let audio_table_name = AudioTable::TABLE_NAME;
+
+        let sql = format!(
+            "SELECT Audio.name FROM {table_name} Favorites
+            INNER JOIN {audio_table_name} Audio ON Audio.id = Favorites.audio_id
+            WHERE Favorites.user_id = ?1
+                AND Audio.deleted_at IS NULL
+                AND Audio.name LIKE '%' || ?2 || '%'
+            ORDER BY Audio.name ASC LIMIT {limit}"
+        );
+        let mut stmt = self
+            .conn
+            .prepare(sql.as_str())
+            .expect("Autocomplete favorites sql invalid");
+
+        let rows = stmt.query_map(params![&user_id, &text], |row| row.get("name"));
+        match rows {
+            Ok(rows) => rows.filter_map(|row| row.ok()).collect(),
+            Err(err) => {
+                log::error!("Autocomplete favorites sql query error - {err}");
+                vec![]
+            }
+        }
+    }
+
+    /// Deletes favorites of audio tracks that no longer exist. ie: purged from the trash
+    pub fn delete_orphaned_favorites(conn: &rusqlite::Connection) -> Result<usize, PoiseError> {
+        let table_name = Self::TABLE_NAME;
+        let audio_table_name = AudioTable::TABLE_NAME;
+        let sql = format!(
+            "DELETE FROM {table_name} WHERE audio_id NOT IN (SELECT id FROM {audio_table_name})"
+        );
+
+        let count = conn
+            .execute(sql.as_str(), ())
+            .log_err_msg("Failed deleting orphaned favorites")?;
+
+        Ok(count)
+    }
+}
+
+impl Table for FavoritesTable {
+    fn connection(&self) -> &DbConnection {
+        &self.conn
+    }
+
+    fn drop_table(&self) {
+        let table_name = Self::TABLE_NAME;
+        log::info!("Dropping table: {table_name}");
+        let sql = format!("DROP TABLE IF EXISTS {table_name};");
+
+        self.conn
+            .execute_batch(sql.as_str())
+            .log_err_msg("Failed dropping table")
+            .log_ok_msg(format!("Dropped table {table_name}"))
+            .unwrap();
+    }
+
+    fn create_table(&self) {
+        let table_name = Self::TABLE_NAME;
+        log::info!("Creating table: {table_name}");
+        let sql = format!(
+            "
+            CREATE TABLE IF NOT EXISTS {table_name} (
+                user_id INTEGER NOT NULL,
+                audio_id INTEGER NOT NULL,
+                created_at VARCHAR(25) NOT NULL,
+                PRIMARY KEY (user_id, audio_id)
+            );
+
+            CREATE INDEX IF NOT EXISTS {table_name}_audio_id_idx ON {table_name}(audio_id);
+        "
+        );
+
+        self.conn
+            .execute_batch(sql.as_str())
+            .log_err_msg("Failed create table")
+            .log_ok_msg(format!("Created table {table_name}"))
+            .unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use r2d2_sqlite::SqliteConnectionManager;
+
+    use crate::{
+        audio::AudioFile, db::audio_table::AudioTableRowInsertBuilder, helpers::uuid_v4_str,
+    };
+
+    use super::*;
+
+    #[test]
+    fn table_create_test() {
+        let db_manager = SqliteConnectionManager::memory();
+        let db_pool = r2d2::Pool::new(db_manager).unwrap();
+        let table = FavoritesTable::new(db_pool.get().unwrap());
+        table.create_table();
+        table.create_table();
+    }
+
+    #[test]
+    fn add_and_remove_favorites_test() {
+        let db_manager = SqliteConnectionManager::memory();
+        let db_pool = r2d2::Pool::new(db_manager).unwrap();
+        let audio_table = AudioTable::new(db_pool.get().unwrap());
+        let table = FavoritesTable::new(db_pool.get().unwrap());
+        audio_table.create_table();
+        table.create_table();
+
+        for name in ["airhorn", "bruh", "boom"] {
+            let audio_file = AudioFile::new(format!("/tmp/{}.mp3", uuid_v4_str()).into());
+            audio_table
+                .insert_audio_row(AudioTableRowInsertBuilder::new(name, audio_file).build())
+                .unwrap();
+        }
+
+        assert!(table.add_favorite(1, 1).unwrap());
+        assert!(!table.add_favorite(1, 1).unwrap());
+        assert!(table.add_favorite(1, 2).unwrap());
+        assert!(table.add_favorite(2, 3).unwrap());
+
+        assert!(table.is_favorite(1, 1).unwrap());
+        assert!(!table.is_favorite(2, 1).unwrap());
+        assert_eq!(
+            table.autocomplete_favorite_track_names(1, "", None),
+            vec!["airhorn", "bruh"]
+        );
+        assert_eq!(
+            table.autocomplete_favorite_track_names(2, "b", None),
+            vec!["boom"]
+        );
+
+        assert!(table.remove_favorite(1, 1).unwrap());
+        assert!(!table.remove_favorite(1, 1).unwrap());
+        assert_eq!(
+            table.autocomplete_favorite_track_names(1, "", None),
+            vec!["bruh"]
+        );
+
+        // favorites of purged tracks are cleaned up
+        audio_table
+            .connection()
+            .execute("DELETE FROM audio WHERE id = 2", ())
+            .unwrap();
+        assert_eq!(
+            FavoritesTable::delete_orphaned_favorites(&table.conn).unwrap(),
+            1
+        );
+        assert!(!table.is_favorite(1, 2).unwrap());
+        assert!(table.is_favorite(2, 3).unwrap());
+    }
+}
@@ -0,0 +1,266 @@
+use rusqlite::{params, OptionalExtension};
+
+use crate::{commands::PoiseError, common::LogResult, vars};
+
+use super::{DbConnection, MemberSound, Table};
+
+/// Settings of a single guild. Guilds without a saved row use default settings
+#[derive(Debug, Clone, PartialEq)]
+pub struct GuildSettingsRow {
+    pub guild_id: u64,
+    pub intros_enabled: bool,
+    pub max_intro_duration_ms: u64,
+    pub intro_cooldown_secs: u64,
+    pub outros_enabled: bool,
+    pub auto_join_enabled: bool,
+    /// Voice channels the bot auto joins. Any voice channel if empty
+    pub auto_join_channel_ids: Vec<u64>,
+    /// Leave voice channel after nothing has played for this long. Never if `None`
+    pub idle_timeout_secs: Option<u64>,
+    /// Text channel audit log entries are mirrored into. Not mirrored if `None`
+    pub audit_log_channel_id: Option<u64>,
+}
+
+impl GuildSettingsRow {
+    pub fn new(guild_id: u64) -> Self {
+        Self {
+            guild_id,
+            intros_enabled: true,
+            max_intro_duration_ms: vars::DEFAULT_MAX_INTRO_DURATION_MS,
+            intro_cooldown_secs: vars::DEFAULT_INTRO_COOLDOWN_SECS,
+            outros_enabled: true,
+            auto_join_enabled: false,
+            auto_join_channel_ids: vec![],
+            idle_timeout_secs: None,
+            audit_log_channel_id: None,
+        }
+    }
+
+    pub fn idle_timeout(&self) -> Option<chrono::Duration> {
+        self.idle_timeout_secs
+            .map(|secs| chrono::Duration::seconds(secs as i64))
+    }
+
+    /// True if the bot should auto join members entering voice channel
+    pub fn is_auto_join_channel(&self, channel_id: u64) -> bool {
+        self.auto_join_enabled
+            && (self.auto_join_channel_ids.is_empty()
+                || self.auto_join_channel_ids.contains(&channel_id))
+    }
+
+    pub fn is_member_sound_enabled(&self, sound: MemberSound) -> bool {
+        match sound {
+            MemberSound::Intro => self.intros_enabled,
+            MemberSound::Outro => self.outros_enabled,
+        }
+    }
+
+    /// Longest an intro or outro plays for
+    pub fn max_intro_duration(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(self.max_intro_duration_ms)
+    }
+
+    /// Least time between a member's intros, or outros
+    pub fn intro_cooldown(&self) -> chrono::Duration {
+        chrono::Duration::seconds(self.intro_cooldown_secs as i64)
+    }
+}
+
+impl TryFrom<&rusqlite::Row<'_>> for GuildSettingsRow {
+    type Error = rusqlite::Error;
+
+    fn try_from(row: &rusqlite::Row<'_>) -> Result<Self, Self::Error> {
+        Ok(Self {
+            guild_id: row.get("guild_id")?,
+            intros_enabled: row.get("intros_enabled")?,
+            max_intro_duration_ms: row.get("max_intro_duration_ms")?,
+            intro_cooldown_secs: row.get("intro_cooldown_secs")?,
+            outros_enabled: row.get("outros_enabled")?,
+            auto_join_enabled: row.get("auto_join_enabled")?,
+            auto_join_channel_ids: row
+                .get::<_, Option<String>>("auto_join_channel_ids")?
+                .unwrap_or_default()
+                .split_whitespace()
+                .filter_map(|val| val.parse().ok())
+                .collect(),
+            idle_timeout_secs: row.get("idle_timeout_secs")?,
+            audit_log_channel_id: row.get("audit_log_channel_id")?,
+        })
+    }
+}
+
+pub struct GuildSettingsTable {
+    conn: DbConnection,
+}
+
+impl GuildSettingsTable {
+    pub const TABLE_NAME: &'static str = "guild_settings";
+
+    pub fn new(connection: DbConnection) -> Self {
+        Self { conn: connection }
+    }
+
+    /// Settings of guild, or default settings if guild hasn't changed any
+    pub fn get_guild_settings(&self, guild_id: u64) -> Result<GuildSettingsRow, PoiseError> {
+        let table_name = Self::TABLE_NAME;
+        let sql = format!("SELECT * FROM {table_name} WHERE guild_id = ?");
+
+        let settings = self
+            .conn
+            .query_row(sql.as_str(), params![&guild_id], |row| {
+                GuildSettingsRow::try_from(row)
+            })
+            .optional()
+            .log_err_msg(format!(
+                "Failed getting guild settings. Guild id: {guild_id}"
+            ))?;
+
+        Ok(settings.unwrap_or_else(|| GuildSettingsRow::new(guild_id)))
+    }
+
+    pub fn update_guild_settings(&self, settings: &GuildSettingsRow) -> Result<(), PoiseError> {
+        log::info!("Saving guild settings. Guild id: {}", settings.guild_id);
+
+        let table_name = Self::TABLE_NAME;
+        let auto_join_channel_ids = settings
+            .auto_join_channel_ids
+            .iter()
+            .map(|id| id.to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let sql = format!(
+            "
+            INSERT OR REPLACE INTO {table_name}
+                (guild_id, intros_enabled, max_intro_duration_ms, intro_cooldown_secs, outros_enabled,
+                auto_join_enabled, auto_join_channel_ids, idle_timeout_secs, audit_log_channel_id)
+            VALUES
+                (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+            "
+        );
+
+        self.conn
+            .execute(
+                sql.as_str(),
+                params![
+                    &settings.guild_id,
+                    &settings.intros_enabled,
+                    &settings.max_intro_duration_ms,
+                    &settings.intro_cooldown_secs,
+                    &settings.outros_enabled,
+                    &settings.auto_join_enabled,
+                    &auto_join_channel_ids,
+                    &settings.idle_timeout_secs,
+                    &settings.audit_log_channel_id
+                ],
+            )
+            .log_err_msg("Failed saving guild settings")?;
+
+        Ok(())
+    }
+}
+
+impl Table for GuildSettingsTable {
+    fn connection(&self) -> &DbConnection {
+        &self.conn
+    }
+
+    fn drop_table(&self) {
+        let table_name = Self::TABLE_NAME;
+        log::info!("Dropping table: {table_name}");
+        let sql = format!("DROP TABLE IF EXISTS {table_name};");
+
+        self.conn
+            .execute_batch(sql.as_str())
+            .log_err_msg("Failed dropping table")
+            .log_ok_msg(format!("Dropped table {table_name}"))
+            .unwrap();
+    }
+
+    fn create_table(&self) {
+        let table_name = Self::TABLE_NAME;
+        log::info!("Creating table: {table_name}");
+        let sql = format!(
+            "
+            CREATE TABLE IF NOT EXISTS {table_name} (
+                guild_id INTEGER PRIMARY KEY,
+                intros_enabled BOOLEAN NOT NULL DEFAULT TRUE,
+                max_intro_duration_ms INTEGER NOT NULL,
+                intro_cooldown_secs INTEGER NOT NULL,
+                outros_enabled BOOLEAN NOT NULL DEFAULT TRUE,
+                auto_join_enabled BOOLEAN NOT NULL DEFAULT FALSE,
+                auto_join_channel_ids TEXT,
+                idle_timeout_secs INTEGER,
+                audit_log_channel_id INTEGER
+            );
+        "
+        );
+
+        self.conn
+            .execute_batch(sql.as_str())
+            .log_err_msg("Failed create table")
+            .log_ok_msg(format!("Created table {table_name}"))
+            .unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use r2d2_sqlite::SqliteConnectionManager;
+
+    use super::*;
+
+    fn get_guild_settings_table() -> GuildSettingsTable {
+        let db_manager = SqliteConnectionManager::memory();
+        let db_pool = r2d2::Pool::new(db_manager).unwrap();
+        GuildSettingsTable::new(db_pool.get().unwrap())
+    }
+
+    #[test]
+    fn table_create_test() {
+        let table = get_guild_settings_table();
+        table.create_table();
+        table.create_table();
+    }
+
+    #[test]
+    fn get_and_update_guild_settings_test() {
+        let table = get_guild_settings_table();
+        table.create_table();
+
+        // defaults for guilds without settings
+        let mut settings = table.get_guild_settings(1).unwrap();
+        assert_eq!(settings, GuildSettingsRow::new(1));
+        assert!(settings.intros_enabled);
+
+        settings.intros_enabled = false;
+        settings.max_intro_duration_ms = 2_000;
+        settings.intro_cooldown_secs = 60;
+        settings.outros_enabled = false;
+        settings.auto_join_enabled = true;
+        settings.auto_join_channel_ids = vec![10, 20];
+        settings.idle_timeout_secs = Some(600);
+        settings.audit_log_channel_id = Some(30);
+        table.update_guild_settings(&settings).unwrap();
+        table.update_guild_settings(&settings).unwrap();
+
+        assert_eq!(table.get_guild_settings(1).unwrap(), settings);
+        assert_eq!(
+            table.get_guild_settings(2).unwrap(),
+            GuildSettingsRow::new(2)
+        );
+    }
+
+    #[test]
+    fn auto_join_channel_test() {
+        let mut settings = GuildSettingsRow::new(1);
+        assert!(!settings.is_auto_join_channel(10));
+
+        // any channel without an allow-list
+        settings.auto_join_enabled = true;
+        assert!(settings.is_auto_join_channel(10));
+
+        settings.auto_join_channel_ids = vec![20];
+        assert!(!settings.is_auto_join_channel(10));
+        assert!(settings.is_auto_join_channel(20));
+    }
+}
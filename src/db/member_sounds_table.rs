@@ -0,0 +1,338 @@
+use rusqlite::{params, OptionalExtension};
+
+use crate::{commands::PoiseError, common::LogResult};
+
+use super::{AudioTable, DbConnection, Table};
+
+/// Kind of personal sound a guild member can set
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MemberSound {
+    /// Played when member joins the bot's voice channel
+    Intro,
+    /// Played when member leaves, or moves out of, the bot's voice channel
+    Outro,
+}
+
+impl MemberSound {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Intro => "intro",
+            Self::Outro => "outro",
+        }
+    }
+
+    fn audio_id_column(&self) -> &'static str {
+        match self {
+            Self::Intro => "intro_audio_id",
+            Self::Outro => "outro_audio_id",
+        }
+    }
+
+    fn last_played_at_column(&self) -> &'static str {
+        match self {
+            Self::Intro => "last_intro_at",
+            Self::Outro => "last_outro_at",
+        }
+    }
+}
+
+/// Personal sounds of a guild member
+#[derive(Debug)]
+pub struct MemberSoundsRow {
+    #[allow(dead_code)]
+    pub guild_id: u64,
+    #[allow(dead_code)]
+    pub user_id: u64,
+    pub intro_audio_id: Option<i64>,
+    pub last_intro_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub outro_audio_id: Option<i64>,
+    pub last_outro_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl MemberSoundsRow {
+    pub fn audio_id(&self, sound: MemberSound) -> Option<i64> {
+        match sound {
+            MemberSound::Intro => self.intro_audio_id,
+            MemberSound::Outro => self.outro_audio_id,
+        }
+    }
+
+    /// True if member's sound played less than `cooldown` before `now`
+    pub fn is_on_cooldown(
+        &self,
+        sound: MemberSound,
+        cooldown: chrono::Duration,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> bool {
+        let last_played_at = match sound {
+            MemberSound::Intro => self.last_intro_at,
+            MemberSound::Outro => self.last_outro_at,
+        };
+
+        match last_played_at {
+            Some(last_played_at) => now - last_played_at < cooldown,
+            None => false,
+        }
+    }
+}
+
+impl TryFrom<&rusqlite::Row<'_>> for MemberSoundsRow {
+    type Error = rusqlite::Error;
+
+    fn try_from(row: &rusqlite::Row<'_>) -> Result<Self, Self::Error> {
+        Ok(Self {
+            guild_id: row.get("guild_id")?,
+            user_id: row.get("user_id")?,
+            intro_audio_id: row.get("intro_audio_id")?,
+            last_intro_at: row.get("last_intro_at")?,
+            outro_audio_id: row.get("outro_audio_id")?,
+            last_outro_at: row.get("last_outro_at")?,
+        })
+    }
+}
+
+pub struct MemberSoundsTable {
+    conn: DbConnection,
+}
+
+impl MemberSoundsTable {
+    pub const TABLE_NAME: &'static str = "member_sounds";
+
+    pub fn new(connection: DbConnection) -> Self {
+        Self { conn: connection }
+    }
+
+    pub fn find_member_sounds(
+        &self,
+        guild_id: u64,
+        user_id: u64,
+    ) -> Result<Option<MemberSoundsRow>, PoiseError> {
+        let table_name = Self::TABLE_NAME;
+        let sql = format!("SELECT * FROM {table_name} WHERE guild_id = ?1 AND user_id = ?2");
+
+        Ok(self
+            .conn
+            .query_row(sql.as_str(), params![&guild_id, &user_id], |row| {
+                MemberSoundsRow::try_from(row)
+            })
+            .optional()
+            .log_err_msg("Failed finding member sounds")?)
+    }
+
+    /// Sets, or clears with `None`, member's intro or outro audio track
+    pub fn set_member_sound(
+        &self,
+        sound: MemberSound,
+        guild_id: u64,
+        user_id: u64,
+        audio_id: Option<i64>,
+    ) -> Result<(), PoiseError> {
+        log::info!(
+            "Setting {}. Guild id: {guild_id}, User id: {user_id}, Audio id: {audio_id:?}",
+            sound.as_str()
+        );
+
+        let table_name = Self::TABLE_NAME;
+        let column = sound.audio_id_column();
+        let sql = format!(
+            "INSERT INTO {table_name} (guild_id, user_id, {column}) VALUES (?1, ?2, ?3)
+            ON CONFLICT (guild_id, user_id) DO UPDATE SET {column} = excluded.{column}"
+        );
+
+        self.conn
+            .execute(sql.as_str(), params![&guild_id, &user_id, &audio_id])
+            .log_err_msg(format!("Failed setting {}", sound.as_str()))?;
+
+        Ok(())
+    }
+
+    /// Records when member's intro or outro played, to start its cooldown
+    pub fn update_last_played_at(
+        &self,
+        sound: MemberSound,
+        guild_id: u64,
+        user_id: u64,
+        played_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<(), PoiseError> {
+        let table_name = Self::TABLE_NAME;
+        let column = sound.last_played_at_column();
+        let sql =
+            format!("UPDATE {table_name} SET {column} = ?3 WHERE guild_id = ?1 AND user_id = ?2");
+
+        self.conn
+            .execute(sql.as_str(), params![&guild_id, &user_id, &played_at])
+            .log_err_msg(format!("Failed updating last {} played at", sound.as_str()))?;
+
+        Ok(())
+    }
+
+    /// Clears personal sounds of audio tracks that no longer exist. ie: purged from the trash
+    pub fn clear_orphaned_sounds(conn: &rusqlite::Connection) -> Result<usize, PoiseError> {
+        let table_name = Self::TABLE_NAME;
+        let audio_table_name = AudioTable::TABLE_NAME;
+        let mut count = 0;
+
+        for sound in [MemberSound::Intro, MemberSound::Outro] {
+            let column = sound.audio_id_column();
+            let sql = format!(
+                "UPDATE {table_name} SET {column} = NULL
+                WHERE {column} NOT IN (SELECT id FROM {audio_table_name})"
+            );
+
+            count += conn
+                .execute(sql.as_str(), ())
+                .log_err_msg("Failed clearing orphaned member sounds")?;
+        }
+
+        Ok(count)
+    }
+}
+
+impl Table for MemberSoundsTable {
+    fn connection(&self) -> &DbConnection {
+        &self.conn
+    }
+
+    fn drop_table(&self) {
+        let table_name = Self::TABLE_NAME;
+        log::info!("Dropping table: {table_name}");
+        let sql = format!("DROP TABLE IF EXISTS {table_name};");
+
+        self.conn
+            .execute_batch(sql.as_str())
+            .log_err_msg("Failed dropping table")
+            .log_ok_msg(format!("Dropped table {table_name}"))
+            .unwrap();
+    }
+
+    fn create_table(&self) {
+        let table_name = Self::TABLE_NAME;
+        log::info!("Creating table: {table_name}");
+        let sql = format!(
+            "
+            CREATE TABLE IF NOT EXISTS {table_name} (
+                guild_id INTEGER NOT NULL,
+                user_id INTEGER NOT NULL,
+                intro_audio_id INTEGER,
+                last_intro_at VARCHAR(25) DEFAULT NULL,
+                outro_audio_id INTEGER,
+                last_outro_at VARCHAR(25) DEFAULT NULL,
+                PRIMARY KEY (guild_id, user_id)
+            );
+        "
+        );
+
+        self.conn
+            .execute_batch(sql.as_str())
+            .log_err_msg("Failed create table")
+            .log_ok_msg(format!("Created table {table_name}"))
+            .unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use r2d2_sqlite::SqliteConnectionManager;
+
+    use crate::{
+        audio::AudioFile, db::audio_table::AudioTableRowInsertBuilder, helpers::uuid_v4_str,
+    };
+
+    use super::*;
+
+    #[test]
+    fn table_create_test() {
+        let db_manager = SqliteConnectionManager::memory();
+        let db_pool = r2d2::Pool::new(db_manager).unwrap();
+        let table = MemberSoundsTable::new(db_pool.get().unwrap());
+        table.create_table();
+        table.create_table();
+    }
+
+    #[test]
+    fn set_member_sound_test() {
+        let db_manager = SqliteConnectionManager::memory();
+        let db_pool = r2d2::Pool::new(db_manager).unwrap();
+        let audio_table = AudioTable::new(db_pool.get().unwrap());
+        let table = MemberSoundsTable::new(db_pool.get().unwrap());
+        audio_table.create_table();
+        table.create_table();
+
+        let audio_file = AudioFile::new(format!("/tmp/{}.mp3", uuid_v4_str()).into());
+        audio_table
+            .insert_audio_row(AudioTableRowInsertBuilder::new("airhorn", audio_file).build())
+            .unwrap();
+
+        assert!(table.find_member_sounds(1, 10).unwrap().is_none());
+
+        table
+            .set_member_sound(MemberSound::Intro, 1, 10, Some(1))
+            .unwrap();
+        let now = chrono::Utc::now();
+        table
+            .update_last_played_at(MemberSound::Intro, 1, 10, now)
+            .unwrap();
+
+        let row = table.find_member_sounds(1, 10).unwrap().unwrap();
+        assert_eq!(row.audio_id(MemberSound::Intro), Some(1));
+        assert_eq!(row.audio_id(MemberSound::Outro), None);
+        assert_eq!(row.last_intro_at, Some(now));
+
+        // outros are set independently of intros
+        table
+            .set_member_sound(MemberSound::Outro, 1, 10, Some(1))
+            .unwrap();
+        let row = table.find_member_sounds(1, 10).unwrap().unwrap();
+        assert_eq!(row.audio_id(MemberSound::Intro), Some(1));
+        assert_eq!(row.audio_id(MemberSound::Outro), Some(1));
+        assert_eq!(row.last_outro_at, None);
+
+        // other guilds have their own sounds
+        assert!(table.find_member_sounds(2, 10).unwrap().is_none());
+
+        // sounds of purged tracks are cleared
+        table
+            .set_member_sound(MemberSound::Outro, 2, 10, Some(99))
+            .unwrap();
+        assert_eq!(
+            MemberSoundsTable::clear_orphaned_sounds(&table.conn).unwrap(),
+            1
+        );
+        let row = table.find_member_sounds(2, 10).unwrap().unwrap();
+        assert_eq!(row.audio_id(MemberSound::Outro), None);
+
+        table
+            .set_member_sound(MemberSound::Intro, 1, 10, None)
+            .unwrap();
+        let row = table.find_member_sounds(1, 10).unwrap().unwrap();
+        assert_eq!(row.audio_id(MemberSound::Intro), None);
+        assert_eq!(row.audio_id(MemberSound::Outro), Some(1));
+        assert_eq!(row.last_intro_at, Some(now));
+    }
+
+    #[test]
+    fn member_sound_cooldown_test() {
+        let now = chrono::Utc::now();
+        let cooldown = chrono::Duration::seconds(60);
+        let mut row = MemberSoundsRow {
+            guild_id: 1,
+            user_id: 10,
+            intro_audio_id: Some(1),
+            last_intro_at: None,
+            outro_audio_id: Some(1),
+            last_outro_at: None,
+        };
+
+        assert!(!row.is_on_cooldown(MemberSound::Intro, cooldown, now));
+
+        row.last_intro_at = Some(now - chrono::Duration::seconds(30));
+        assert!(row.is_on_cooldown(MemberSound::Intro, cooldown, now));
+        assert!(!row.is_on_cooldown(MemberSound::Outro, cooldown, now));
+
+        row.last_intro_at = Some(now - chrono::Duration::seconds(60));
+        assert!(!row.is_on_cooldown(MemberSound::Intro, cooldown, now));
+
+        row.last_outro_at = Some(now);
+        assert!(row.is_on_cooldown(MemberSound::Outro, cooldown, now));
+    }
+}
@@ -1,12 +1,37 @@
 pub mod audio_table;
+pub mod audit_log_table;
+pub mod collections_table;
+pub mod emoji_bindings_table;
+pub mod favorites_table;
+pub mod guild_settings_table;
+pub mod member_sounds_table;
 pub mod paginators;
+pub mod panels_table;
+pub mod play_history_table;
+pub mod schedules_table;
+pub mod search_query;
 pub mod settings_table;
+pub mod tags_table;
+pub mod triggers_table;
 
 use core::fmt;
 
-pub use audio_table::{AudioTable, AudioTableRow, Tags, UniqueAudioTableCol};
+use crate::commands::PoiseError;
+
+pub use audio_table::{AudioTable, AudioTableRow, SoundButtonStyle, Tags, UniqueAudioTableCol};
+pub use audit_log_table::{AuditAction, AuditLogEntry, AuditLogFilter, AuditLogTable};
+pub use collections_table::{CollectionRow, CollectionsTable};
+pub use emoji_bindings_table::EmojiBindingsTable;
+pub use favorites_table::FavoritesTable;
+pub use guild_settings_table::{GuildSettingsRow, GuildSettingsTable};
+pub use member_sounds_table::{MemberSound, MemberSoundsTable};
 pub use paginators::{AudioTablePaginator, AudioTablePaginatorBuilder};
+pub use panels_table::{PanelRow, PanelsTable};
+pub use play_history_table::{PlayHistoryEntry, PlayHistoryTable, PlaySource};
+pub use schedules_table::{ScheduleRow, SchedulesTable};
 pub use settings_table::SettingsTable;
+pub use tags_table::TagsTable;
+pub use triggers_table::{TriggerInsert, TriggerKind, TriggerRow, TriggersTable};
 
 pub type DbConnection = r2d2::PooledConnection<r2d2_sqlite::SqliteConnectionManager>;
 
@@ -17,6 +42,38 @@ pub trait Table {
     fn drop_table(&self);
 }
 
+/// Deletes rows of every table referencing audio tracks that no longer exist. ie: purged from the trash.
+/// Returns number of deleted or cleared rows
+pub fn delete_orphaned_rows(conn: &rusqlite::Connection) -> Result<usize, PoiseError> {
+    Ok(FavoritesTable::delete_orphaned_favorites(conn)?
+        + CollectionsTable::delete_orphaned_audio(conn)?
+        + MemberSoundsTable::clear_orphaned_sounds(conn)?
+        + SchedulesTable::delete_orphaned_schedules(conn)?
+        + TriggersTable::delete_orphaned_triggers(conn)?
+        + EmojiBindingsTable::delete_orphaned_bindings(conn)?
+        + PlayHistoryTable::delete_orphaned_entries(conn)?)
+}
+
+/// Adds column to table if it's missing. Used to migrate databases created by older bot versions,
+/// since `CREATE TABLE IF NOT EXISTS` won't alter an existing table.
+pub fn add_column_if_missing(
+    conn: &DbConnection,
+    table_name: &str,
+    column_name: &str,
+    column_def: &str,
+) -> rusqlite::Result<()> {
+    let sql = format!("SELECT COUNT(*) FROM pragma_table_info('{table_name}') WHERE name = ?");
+    let count: i64 = conn.query_row(sql.as_str(), [column_name], |row| row.get(0))?;
+
+    if count == 0 {
+        log::info!("Migrating table {table_name}. Adding column {column_name}");
+        let sql = format!("ALTER TABLE {table_name} ADD COLUMN {column_name} {column_def}");
+        conn.execute(sql.as_str(), ())?;
+    }
+
+    Ok(())
+}
+
 #[derive(Debug)]
 pub enum Order {
     Asc,
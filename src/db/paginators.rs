@@ -1,8 +1,9 @@
-use crate::db;
+use crate::{db, errors::SearchQueryError};
 
 use super::{
     audio_table::{AudioTableOrderBy, AudioTableRow},
-    AudioTable, DbConnection,
+    search_query::{SearchQuery, SearchSql},
+    AudioTable, CollectionsTable, DbConnection, FavoritesTable, PlayHistoryTable, TagsTable,
 };
 
 #[derive(Debug)]
@@ -11,9 +12,14 @@ pub struct AudioTablePaginator {
     order_by: AudioTableOrderBy,
     page_limit: u64,
     offset: u64,
-    fts_filter: Option<String>,
+    search: Option<SearchSql>,
     pinned: Option<bool>,
-    limit: Option<u64>, // Limit for the total number of rows to fetch
+    played_in_guild: Option<u64>, // Only rows with play history in guild
+    min_popularity: Option<f64>,  // Only rows more popular than this
+    favorited_by: Option<u64>,    // Only rows favorited by user
+    in_collection: Option<i64>,   // Only rows in collection
+    tagged: Option<String>,       // Only rows with tag
+    limit: Option<u64>,           // Limit for the total number of rows to fetch
 }
 
 pub struct PaginateInfo {
@@ -23,7 +29,6 @@ pub struct PaginateInfo {
     pub last_page_offset: Option<u64>,
     pub total_pages: u64,
     pub cur_page: u64,
-    #[allow(unused)]
     pub total_row_count: u64,
     #[allow(unused)]
     pub page_limit: u64,
@@ -77,15 +82,7 @@ impl AudioTablePaginator {
 
     pub fn row_count(&self) -> Result<u64, String> {
         let conn = &self.conn;
-        let audio_table_name = AudioTable::TABLE_NAME;
-        let fts_table_name = AudioTable::FTS5_TABLE_NAME;
-        let fts_filter = if let Some(fts_filter) = self.fts_filter.as_ref() {
-            Some(self.fts_escape(fts_filter))
-        } else {
-            None
-        };
-        let mut where_sql: Vec<String> = vec![];
-        let mut params: Vec<(&'static str, &dyn rusqlite::ToSql)> = vec![];
+        let (from_sql, where_sql, params) = self.query_parts_sql();
 
         let limit_sql = if let Some(limit) = self.limit {
             format!("LIMIT {limit}")
@@ -93,42 +90,13 @@ impl AudioTablePaginator {
             String::new()
         };
 
-        if let Some(pinned) = self.pinned.as_ref() {
-            where_sql.push("pinned = :pinned".into());
-            params.push((":pinned", pinned));
-        }
-
-        let where_sql = if where_sql.is_empty() {
-            String::new()
-        } else {
-            format!("WHERE {}", where_sql.join(" AND "))
-        };
-
-        let sql = match fts_filter.as_ref() {
-            Some(fts_filter) => {
-                params.insert(0, (":fts_filter", fts_filter));
-
-                // fts filtering
-                format!(
-                    "SELECT Audio.id FROM {audio_table_name} Audio
-                    INNER JOIN {fts_table_name}(:fts_filter) FTS
-                        ON Audio.id = FTS.rowid
-                    {where_sql}
-                    {limit_sql}
-                    "
-                )
-            }
-            None => {
-                format!(
-                    "SELECT id FROM {audio_table_name}
-                    {where_sql}
-                    {limit_sql}
-                    "
-                )
-            }
-        };
-
-        let sql = format!("SELECT COUNT(id) FROM ({sql});");
+        let sql = format!(
+            "SELECT COUNT(id) FROM (
+                SELECT Audio.id FROM {from_sql}
+                {where_sql}
+                {limit_sql}
+            );"
+        );
 
         let mut stmt = conn
             .prepare(sql.as_ref())
@@ -141,26 +109,107 @@ impl AudioTablePaginator {
         Ok(count)
     }
 
-    fn fts_escape(&self, fts: impl AsRef<str>) -> String {
-        let fts = fts.as_ref();
+    /// Builds FROM & WHERE sql shared by row counting & paging. Audio table is aliased as `Audio`
+    fn query_parts_sql(&self) -> (String, String, Vec<(&str, &dyn rusqlite::ToSql)>) {
+        let audio_table_name = AudioTable::TABLE_NAME;
+        let fts_table_name = AudioTable::FTS5_TABLE_NAME;
+        let history_table_name = PlayHistoryTable::TABLE_NAME;
+        let favorites_table_name = FavoritesTable::TABLE_NAME;
+        let collection_items_table_name = CollectionsTable::ITEMS_TABLE_NAME;
+        let tags_table_name = TagsTable::TABLE_NAME;
+        let audio_tags_table_name = TagsTable::AUDIO_TAGS_TABLE_NAME;
+
+        let mut from_sql = vec![format!("{audio_table_name} Audio")];
+        let mut where_sql: Vec<String> = vec![];
+        let mut params: Vec<(&str, &dyn rusqlite::ToSql)> = vec![];
+
+        if let Some(fts_match) = self
+            .search
+            .as_ref()
+            .and_then(|search| search.fts_match.as_ref())
+        {
+            from_sql.push(format!(
+                "INNER JOIN {fts_table_name}(:search_fts_match) SearchFTS ON Audio.id = SearchFTS.rowid"
+            ));
+            params.push((":search_fts_match", fts_match));
+        }
+
+        if let Some(guild_id) = self.played_in_guild.as_ref() {
+            // latest play of each audio track in guild. ie: `History.played_at`
+            from_sql.push(format!(
+                "INNER JOIN (
+                    SELECT audio_id, MAX(played_at) AS played_at FROM {history_table_name}
+                    WHERE guild_id = :played_in_guild
+                    GROUP BY audio_id
+                ) History ON Audio.id = History.audio_id"
+            ));
+            params.push((":played_in_guild", guild_id));
+        }
+
+        if let Some(user_id) = self.favorited_by.as_ref() {
+            from_sql.push(format!(
+                "INNER JOIN {favorites_table_name} Favorites
+                    ON Audio.id = Favorites.audio_id AND Favorites.user_id = :favorited_by"
+            ));
+            params.push((":favorited_by", user_id));
+        }
+
+        if let Some(collection_id) = self.in_collection.as_ref() {
+            // ie: `CollectionItem.position`
+            from_sql.push(format!(
+                "INNER JOIN {collection_items_table_name} CollectionItem
+                    ON Audio.id = CollectionItem.audio_id
+                    AND CollectionItem.collection_id = :in_collection"
+            ));
+            params.push((":in_collection", collection_id));
+        }
+
+        if let Some(tag) = self.tagged.as_ref() {
+            from_sql.push(format!(
+                "INNER JOIN {audio_tags_table_name} AudioTag ON Audio.id = AudioTag.audio_id
+                INNER JOIN {tags_table_name} Tag ON Tag.id = AudioTag.tag_id AND Tag.name = :tagged"
+            ));
+            params.push((":tagged", tag));
+        }
+
+        // trashed sounds are never listed
+        where_sql.push("Audio.deleted_at IS NULL".into());
+
+        if let Some(pinned) = self.pinned.as_ref() {
+            where_sql.push("Audio.pinned = :pinned".into());
+            params.push((":pinned", pinned));
+        }
+
+        if let Some(min_popularity) = self.min_popularity.as_ref() {
+            where_sql.push("Audio.popularity > :min_popularity".into());
+            params.push((":min_popularity", min_popularity));
+        }
+
+        if let Some(search) = self.search.as_ref() {
+            where_sql.extend(search.where_sql.iter().cloned());
+            params.extend(
+                search
+                    .params
+                    .iter()
+                    .map(|(name, value)| (name.as_str(), value as &dyn rusqlite::ToSql)),
+            );
+        }
+
+        (
+            from_sql.join("\n"),
+            format!("WHERE {}", where_sql.join(" AND ")),
+            params,
+        )
+    }
 
-        format!("\"{}\"", fts.replace('"', "\"\""))
+    pub fn connection(&self) -> &DbConnection {
+        &self.conn
     }
 
     pub fn next_page(&mut self) -> Result<Vec<AudioTableRow>, String> {
-        let conn = &self.conn;
-        let audio_table_name = AudioTable::TABLE_NAME;
-        let fts_table_name = AudioTable::FTS5_TABLE_NAME;
         let order_by_sql = self.order_by.to_sql_str();
         let offset = self.offset;
-        let fts_filter = if let Some(fts_filter) = self.fts_filter.as_ref() {
-            Some(self.fts_escape(fts_filter))
-        } else {
-            None
-        };
 
-        let mut where_sql: Vec<String> = vec![];
-        let mut params: Vec<(&'static str, &dyn rusqlite::ToSql)> = vec![];
         let mut page_limit = self.page_limit;
 
         if let Some(limit) = self.limit {
@@ -180,44 +229,17 @@ impl AudioTablePaginator {
             }
         }
 
-        if let Some(pinned) = self.pinned.as_ref() {
-            where_sql.push("pinned = :pinned".into());
-            params.push((":pinned", pinned));
-        }
-
-        let where_sql = if where_sql.is_empty() {
-            String::new()
-        } else {
-            format!("WHERE {}", where_sql.join(" AND "))
-        };
-
-        let sql = match fts_filter.as_ref() {
-            Some(fts_filter) => {
-                params.insert(0, (":fts_filter", fts_filter));
-
-                // fts filtering
-                format!(
-                    "SELECT Audio.* FROM {audio_table_name} Audio
-                    INNER JOIN {fts_table_name}(:fts_filter) FTS
-                        ON Audio.id = FTS.rowid
-                    {where_sql}
-                    ORDER BY {order_by_sql}
-                    LIMIT {page_limit}
-                    OFFSET {offset};
-                    "
-                )
-            }
-            None => {
-                format!(
-                    "SELECT * FROM {audio_table_name}
-                    {where_sql}
-                    ORDER BY {order_by_sql}
-                    LIMIT {page_limit}
-                    OFFSET {offset};
-                    "
-                )
-            }
-        };
+        let conn = &self.conn;
+        let (from_sql, where_sql, params) = self.query_parts_sql();
+
+        let sql = format!(
+            "SELECT Audio.* FROM {from_sql}
+            {where_sql}
+            ORDER BY {order_by_sql}
+            LIMIT {page_limit}
+            OFFSET {offset};
+            "
+        );
 
         let mut stmt = conn
             .prepare(sql.as_ref())
@@ -237,6 +259,7 @@ impl AudioTablePaginator {
             })
             .collect();
 
+        drop(stmt);
         self.offset += rows.len() as u64;
 
         Ok(rows)
@@ -254,8 +277,13 @@ impl AudioTablePaginatorBuilder {
                 conn: conn,
                 order_by: AudioTableOrderBy::Id(db::Order::Asc),
                 page_limit: 500,
-                fts_filter: None,
+                search: None,
                 pinned: None,
+                played_in_guild: None,
+                min_popularity: None,
+                favorited_by: None,
+                in_collection: None,
+                tagged: None,
                 offset: 0,
                 limit: None,
             },
@@ -274,17 +302,54 @@ impl AudioTablePaginatorBuilder {
             .page_limit(20)
     }
 
-    pub fn search_template(conn: DbConnection, fts_filter: impl AsRef<str>) -> Self {
-        let fts_filter = fts_filter.as_ref();
-        Self::new(conn)
-            .fts_filter(Some(fts_filter.into()))
-            .page_limit(20)
+    /// Rows matching search query language. See [`SearchQuery`]
+    pub fn search_template(
+        conn: DbConnection,
+        search: impl AsRef<str>,
+    ) -> Result<Self, SearchQueryError> {
+        let search = SearchQuery::parse(search)?;
+        Ok(Self::new(conn).search(Some(search)).page_limit(20))
     }
 
     pub fn all_template(conn: DbConnection) -> Self {
         Self::new(conn).page_limit(20)
     }
 
+    pub fn recently_played_template(conn: DbConnection, guild_id: u64) -> Self {
+        Self::new(conn)
+            .played_in_guild(Some(guild_id))
+            .order_by(AudioTableOrderBy::PlayedAt(db::Order::Desc))
+            .page_limit(20)
+    }
+
+    pub fn trending_template(conn: DbConnection) -> Self {
+        Self::new(conn)
+            .min_popularity(Some(0.0))
+            .order_by(AudioTableOrderBy::Popularity(db::Order::Desc))
+            .page_limit(20)
+    }
+
+    pub fn favorites_template(conn: DbConnection, user_id: u64) -> Self {
+        Self::new(conn)
+            .favorited_by(Some(user_id))
+            .order_by(AudioTableOrderBy::Name(db::Order::Asc))
+            .page_limit(20)
+    }
+
+    pub fn collection_template(conn: DbConnection, collection_id: i64) -> Self {
+        Self::new(conn)
+            .in_collection(Some(collection_id))
+            .order_by(AudioTableOrderBy::CollectionPosition(db::Order::Asc))
+            .page_limit(20)
+    }
+
+    pub fn tag_template(conn: DbConnection, tag: impl AsRef<str>) -> Self {
+        Self::new(conn)
+            .tagged(Some(tag.as_ref().into()))
+            .order_by(AudioTableOrderBy::Name(db::Order::Asc))
+            .page_limit(20)
+    }
+
     pub fn pinned_template(conn: DbConnection) -> Self {
         Self::new(conn)
             .pinned(Some(true))
@@ -302,8 +367,8 @@ impl AudioTablePaginatorBuilder {
         self
     }
 
-    pub fn fts_filter(mut self, value: Option<String>) -> Self {
-        self.paginator.fts_filter = value;
+    pub fn search(mut self, value: Option<SearchQuery>) -> Self {
+        self.paginator.search = value.map(|search| search.to_sql());
         self
     }
 
@@ -312,6 +377,31 @@ impl AudioTablePaginatorBuilder {
         self
     }
 
+    pub fn played_in_guild(mut self, value: Option<u64>) -> Self {
+        self.paginator.played_in_guild = value;
+        self
+    }
+
+    pub fn min_popularity(mut self, value: Option<f64>) -> Self {
+        self.paginator.min_popularity = value;
+        self
+    }
+
+    pub fn favorited_by(mut self, value: Option<u64>) -> Self {
+        self.paginator.favorited_by = value;
+        self
+    }
+
+    pub fn in_collection(mut self, value: Option<i64>) -> Self {
+        self.paginator.in_collection = value;
+        self
+    }
+
+    pub fn tagged(mut self, value: Option<String>) -> Self {
+        self.paginator.tagged = value;
+        self
+    }
+
     #[allow(unused)]
     pub fn limit(mut self, value: Option<u64>) -> Self {
         self.paginator.limit = value;
@@ -359,7 +449,7 @@ mod tests {
         audio::AudioFile,
         db::{
             audio_table::{AudioTableRowInsert, AudioTableRowInsertBuilder},
-            Table,
+            PlayHistoryEntry, PlaySource, Table, UniqueAudioTableCol,
         },
         helpers::{self, uuid_v4_str},
     };
@@ -488,11 +578,11 @@ mod tests {
             ))
             .unwrap();
 
-        // plain fts filter
+        // plain search
         {
             let mut paginator = AudioTablePaginatorBuilder::new(db_pool.get().unwrap())
                 .page_limit(2)
-                .fts_filter(Some("star".into()))
+                .search(Some(SearchQuery::parse("star").unwrap()))
                 .build();
 
             assert_eq!(paginator.row_count().unwrap(), 2);
@@ -509,7 +599,7 @@ mod tests {
         // fts edge case
         {
             let mut paginator = AudioTablePaginatorBuilder::new(db_pool.get().unwrap())
-                .fts_filter(Some("asdfasdfasdfasdf".into()))
+                .search(Some(SearchQuery::parse("asdfasdfasdfasdf").unwrap()))
                 .build();
 
             assert_eq!(paginator.row_count().unwrap(), 0);
@@ -518,7 +608,7 @@ mod tests {
             assert!(page.is_none());
 
             paginator = AudioTablePaginatorBuilder::new(db_pool.get().unwrap())
-                .fts_filter(Some("@''\"''\"@#$%^&*()!".into()))
+                .search(Some(SearchQuery::parse("@''\"''\"@#$%^&*()!").unwrap()))
                 .build();
 
             assert_eq!(paginator.row_count().unwrap(), 0);
@@ -561,7 +651,7 @@ mod tests {
         table.insert_audio_row(row).unwrap();
 
         let mut paginator = AudioTablePaginatorBuilder::new(db_pool.get().unwrap())
-            .fts_filter(Some("tag1".into()))
+            .search(Some(SearchQuery::parse("tag1").unwrap()))
             .page_limit(1)
             .offset(2)
             .build();
@@ -642,6 +732,297 @@ mod tests {
         assert_eq!(name_results, names);
     }
 
+    #[test]
+    fn audio_table_trashed_pagination_test() {
+        let db_manager = SqliteConnectionManager::memory();
+        let db_pool = r2d2::Pool::new(db_manager).unwrap();
+        let table = AudioTable::new(db_pool.get().unwrap());
+        table.create_table();
+
+        table
+            .insert_audio_row(make_detailed_audio_table_row_insert(
+                "han solo",
+                "star wars",
+            ))
+            .unwrap();
+        table
+            .insert_audio_row(make_detailed_audio_table_row_insert("obi wan", "star wars"))
+            .unwrap();
+
+        table
+            .trash_audio_row(db::UniqueAudioTableCol::Name("han solo".into()))
+            .unwrap();
+
+        let mut paginator = AudioTablePaginatorBuilder::new(db_pool.get().unwrap()).build();
+        assert_eq!(paginator.row_count().unwrap(), 1);
+        assert_eq!(paginator.next().unwrap().unwrap()[0].name, "obi wan");
+
+        let mut paginator =
+            AudioTablePaginatorBuilder::search_template(db_pool.get().unwrap(), "star")
+                .unwrap()
+                .build();
+        assert_eq!(paginator.row_count().unwrap(), 1);
+        assert_eq!(paginator.next().unwrap().unwrap()[0].name, "obi wan");
+    }
+
+    #[test]
+    fn audio_table_recently_played_pagination_test() {
+        let db_manager = SqliteConnectionManager::memory();
+        let db_pool = r2d2::Pool::new(db_manager).unwrap();
+        let table = AudioTable::new(db_pool.get().unwrap());
+        let history_table = PlayHistoryTable::new(db_pool.get().unwrap());
+        table.create_table();
+        history_table.create_table();
+
+        for name in ["first", "second", "third"] {
+            table
+                .insert_audio_row(make_detailed_audio_table_row_insert(name, ""))
+                .unwrap();
+        }
+
+        let now = chrono::Utc::now();
+        let plays = [(1, 1, 4), (2, 1, 3), (1, 1, 2), (3, 2, 1)];
+        for (audio_id, guild_id, mins_ago) in plays {
+            history_table
+                .insert_entry_at(
+                    &PlayHistoryEntry::new(audio_id, guild_id as u64, PlaySource::Button),
+                    now - chrono::Duration::minutes(mins_ago),
+                )
+                .unwrap();
+        }
+
+        let mut paginator =
+            AudioTablePaginatorBuilder::recently_played_template(db_pool.get().unwrap(), 1).build();
+        assert_eq!(paginator.row_count().unwrap(), 2);
+
+        let names: Vec<_> = paginator
+            .next()
+            .unwrap()
+            .unwrap()
+            .into_iter()
+            .map(|row| row.name)
+            .collect();
+        assert_eq!(names, vec!["first", "second"]);
+
+        let mut paginator =
+            AudioTablePaginatorBuilder::recently_played_template(db_pool.get().unwrap(), 3).build();
+        assert_eq!(paginator.row_count().unwrap(), 0);
+        assert!(paginator.next().is_none());
+    }
+
+    #[test]
+    fn audio_table_trending_pagination_test() {
+        let db_manager = SqliteConnectionManager::memory();
+        let db_pool = r2d2::Pool::new(db_manager).unwrap();
+        let table = AudioTable::new(db_pool.get().unwrap());
+        table.create_table();
+
+        for name in ["cold", "warm", "hot"] {
+            table
+                .insert_audio_row(make_detailed_audio_table_row_insert(name, ""))
+                .unwrap();
+        }
+
+        table.update_audio_row_popularity(2, 0.5).unwrap();
+        table.update_audio_row_popularity(3, 2.5).unwrap();
+
+        let mut paginator =
+            AudioTablePaginatorBuilder::trending_template(db_pool.get().unwrap()).build();
+        assert_eq!(paginator.row_count().unwrap(), 2);
+
+        let names: Vec<_> = paginator
+            .next()
+            .unwrap()
+            .unwrap()
+            .into_iter()
+            .map(|row| row.name)
+            .collect();
+        assert_eq!(names, vec!["hot", "warm"]);
+    }
+
+    #[test]
+    fn audio_table_favorites_pagination_test() {
+        let db_manager = SqliteConnectionManager::memory();
+        let db_pool = r2d2::Pool::new(db_manager).unwrap();
+        let table = AudioTable::new(db_pool.get().unwrap());
+        let favorites_table = FavoritesTable::new(db_pool.get().unwrap());
+        table.create_table();
+        favorites_table.create_table();
+
+        for name in ["zap", "airhorn", "bruh"] {
+            table
+                .insert_audio_row(make_detailed_audio_table_row_insert(name, ""))
+                .unwrap();
+        }
+
+        favorites_table.add_favorite(1, 1).unwrap();
+        favorites_table.add_favorite(1, 2).unwrap();
+        favorites_table.add_favorite(2, 3).unwrap();
+
+        let names_fn = |user_id: u64| -> Vec<String> {
+            AudioTablePaginatorBuilder::favorites_template(db_pool.get().unwrap(), user_id)
+                .build()
+                .next()
+                .map(|rows| rows.unwrap().into_iter().map(|row| row.name).collect())
+                .unwrap_or_default()
+        };
+
+        assert_eq!(names_fn(1), vec!["airhorn", "zap"]);
+        assert_eq!(names_fn(2), vec!["bruh"]);
+        assert!(names_fn(3).is_empty());
+
+        // trashed favorites aren't listed
+        table
+            .trash_audio_row(UniqueAudioTableCol::Name("zap".into()))
+            .unwrap();
+        assert_eq!(names_fn(1), vec!["airhorn"]);
+    }
+
+    #[test]
+    fn audio_table_collection_pagination_test() {
+        let db_manager = SqliteConnectionManager::memory();
+        let db_pool = r2d2::Pool::new(db_manager).unwrap();
+        let table = AudioTable::new(db_pool.get().unwrap());
+        let collections_table = CollectionsTable::new(db_pool.get().unwrap());
+        table.create_table();
+        collections_table.create_table();
+
+        for name in ["vader", "yoda", "jaws", "luke"] {
+            table
+                .insert_audio_row(make_detailed_audio_table_row_insert(name, ""))
+                .unwrap();
+        }
+
+        let collection_id = collections_table
+            .create_collection(1, "Star Wars", None)
+            .unwrap();
+        for audio_id in [4, 1, 2] {
+            collections_table
+                .add_audio(collection_id, audio_id)
+                .unwrap();
+        }
+
+        let names_fn = || -> Vec<String> {
+            AudioTablePaginatorBuilder::collection_template(db_pool.get().unwrap(), collection_id)
+                .build()
+                .next()
+                .map(|rows| rows.unwrap().into_iter().map(|row| row.name).collect())
+                .unwrap_or_default()
+        };
+
+        // listed in the order they were added
+        assert_eq!(names_fn(), vec!["luke", "vader", "yoda"]);
+
+        table
+            .trash_audio_row(UniqueAudioTableCol::Name("vader".into()))
+            .unwrap();
+        assert_eq!(names_fn(), vec!["luke", "yoda"]);
+    }
+
+    #[test]
+    fn audio_table_search_query_pagination_test() {
+        let db_manager = SqliteConnectionManager::memory();
+        let db_pool = r2d2::Pool::new(db_manager).unwrap();
+        let table = AudioTable::new(db_pool.get().unwrap());
+        let tags_table = TagsTable::new(db_pool.get().unwrap());
+        table.create_table();
+        tags_table.create_table();
+
+        for (name, tags, author_id, author_name, duration_ms) in [
+            ("darth vader", "star-wars sith", 1, "krywicki", Some(2_000)),
+            ("yoda", "star-wars jedi", 2, "bob", Some(5_000)),
+            ("jaws", "movie shark", 1, "krywicki", None),
+            ("star trek", "movie", 2, "bob", Some(1_000)),
+        ] {
+            let mut row = make_detailed_audio_table_row_insert(name, tags);
+            row.author_id = Some(author_id);
+            row.author_name = Some(author_name.into());
+            row.duration_ms = duration_ms;
+            table.insert_audio_row(row).unwrap();
+        }
+
+        table.update_audio_row_pin_by_name("yoda", true).unwrap();
+        for _ in 0..11 {
+            table.increment_play_count(3).unwrap();
+        }
+
+        let names_fn = |search: &str| -> Vec<String> {
+            AudioTablePaginatorBuilder::search_template(db_pool.get().unwrap(), search)
+                .unwrap()
+                .order_by(AudioTableOrderBy::Name(db::Order::Asc))
+                .build()
+                .next()
+                .map(|rows| rows.unwrap().into_iter().map(|row| row.name).collect())
+                .unwrap_or_default()
+        };
+
+        assert_eq!(names_fn("star"), vec!["darth vader", "star trek", "yoda"]);
+        assert_eq!(names_fn("star -trek"), vec!["darth vader", "yoda"]);
+        assert_eq!(names_fn("\"star trek\""), vec!["star trek"]);
+        assert_eq!(names_fn("tag:star-wars"), vec!["darth vader", "yoda"]);
+        assert_eq!(names_fn("-tag:star-wars"), vec!["jaws", "star trek"]);
+        assert_eq!(
+            names_fn("tag:sith OR tag:shark"),
+            vec!["darth vader", "jaws"]
+        );
+        assert_eq!(names_fn("jaws OR yoda"), vec!["jaws", "yoda"]);
+        assert_eq!(names_fn("jaws OR tag:jedi"), vec!["jaws", "yoda"]);
+        assert_eq!(names_fn("by:@KRYWICKI"), vec!["darth vader", "jaws"]);
+        assert_eq!(names_fn("by:<@2> movie"), vec!["star trek"]);
+        assert_eq!(names_fn("duration:<3s"), vec!["darth vader", "star trek"]);
+        assert_eq!(names_fn("duration:>=2000ms"), vec!["darth vader", "yoda"]);
+        assert_eq!(
+            names_fn("-duration:<3s"),
+            vec!["jaws", "yoda"],
+            "unknown durations are kept when excluding"
+        );
+        assert_eq!(names_fn("pinned:true"), vec!["yoda"]);
+        assert_eq!(names_fn("pinned:false tag:star-wars"), vec!["darth vader"]);
+        assert_eq!(names_fn("played:>10"), vec!["jaws"]);
+        assert_eq!(
+            names_fn("played:0 -pinned:true"),
+            vec!["darth vader", "star trek"]
+        );
+        assert_eq!(names_fn("tag:missing"), Vec::<String>::new());
+        assert!(
+            AudioTablePaginatorBuilder::search_template(db_pool.get().unwrap(), "played:lots")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn audio_table_tag_pagination_test() {
+        let db_manager = SqliteConnectionManager::memory();
+        let db_pool = r2d2::Pool::new(db_manager).unwrap();
+        let table = AudioTable::new(db_pool.get().unwrap());
+        let tags_table = TagsTable::new(db_pool.get().unwrap());
+        table.create_table();
+        tags_table.create_table();
+
+        for (name, tags) in [
+            ("yoda", "star-wars jedi"),
+            ("jaws", "movie shark"),
+            ("vader", "star-wars sith"),
+        ] {
+            table
+                .insert_audio_row(make_detailed_audio_table_row_insert(name, tags))
+                .unwrap();
+        }
+
+        let names_fn = |tag: &str| -> Vec<String> {
+            AudioTablePaginatorBuilder::tag_template(db_pool.get().unwrap(), tag)
+                .build()
+                .next()
+                .map(|rows| rows.unwrap().into_iter().map(|row| row.name).collect())
+                .unwrap_or_default()
+        };
+
+        assert_eq!(names_fn("star-wars"), vec!["vader", "yoda"]);
+        assert_eq!(names_fn("STAR-WARS"), vec!["vader", "yoda"]);
+        assert_eq!(names_fn("shark"), vec!["jaws"]);
+        assert_eq!(names_fn("star"), Vec::<String>::new());
+    }
+
     #[test]
     fn paginate_info_test() {
         let db_manager = SqliteConnectionManager::memory();
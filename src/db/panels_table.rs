@@ -0,0 +1,225 @@
+use rusqlite::{params, OptionalExtension};
+
+use crate::{commands::PoiseError, common::LogResult};
+
+use super::{DbConnection, Table};
+
+/// Soundboard panel message kept up to date as sounds change. Each guild has at most one
+#[derive(Debug, Clone)]
+pub struct PanelRow {
+    pub guild_id: u64,
+    pub channel_id: u64,
+    pub message_id: u64,
+    #[allow(dead_code)]
+    pub author_id: Option<u64>,
+    #[allow(dead_code)]
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl TryFrom<&rusqlite::Row<'_>> for PanelRow {
+    type Error = rusqlite::Error;
+
+    fn try_from(row: &rusqlite::Row<'_>) -> Result<Self, Self::Error> {
+        Ok(Self {
+            guild_id: row.get("guild_id")?,
+            channel_id: row.get("channel_id")?,
+            message_id: row.get("message_id")?,
+            author_id: row.get("author_id")?,
+            created_at: row.get("created_at")?,
+        })
+    }
+}
+
+pub struct PanelsTable {
+    conn: DbConnection,
+}
+
+impl PanelsTable {
+    pub const TABLE_NAME: &'static str = "panels";
+
+    pub fn new(connection: DbConnection) -> Self {
+        Self { conn: connection }
+    }
+
+    /// Sets guild's panel, replacing its previous panel
+    pub fn upsert_panel(
+        &self,
+        guild_id: u64,
+        channel_id: u64,
+        message_id: u64,
+        author_id: Option<u64>,
+    ) -> Result<(), PoiseError> {
+        log::info!(
+            "Upserting panel. Guild id: {guild_id}, Channel id: {channel_id}, Message id: {message_id}"
+        );
+
+        let table_name = Self::TABLE_NAME;
+        let sql = format!(
+            "INSERT OR REPLACE INTO {table_name} (guild_id, channel_id, message_id, author_id, created_at)
+            VALUES (?1, ?2, ?3, ?4, ?5)"
+        );
+
+        self.conn
+            .execute(
+                sql.as_str(),
+                params![
+                    &guild_id,
+                    &channel_id,
+                    &message_id,
+                    &author_id,
+                    &chrono::Utc::now()
+                ],
+            )
+            .log_err_msg("Failed upserting panel")?;
+
+        Ok(())
+    }
+
+    /// Points guild's panel at a re-posted message
+    pub fn update_panel_message(&self, guild_id: u64, message_id: u64) -> Result<(), PoiseError> {
+        log::info!("Updating panel message. Guild id: {guild_id}, Message id: {message_id}");
+
+        let table_name = Self::TABLE_NAME;
+        let sql = format!("UPDATE {table_name} SET message_id = ?2 WHERE guild_id = ?1");
+
+        self.conn
+            .execute(sql.as_str(), params![&guild_id, &message_id])
+            .log_err_msg("Failed updating panel message")?;
+
+        Ok(())
+    }
+
+    pub fn find_panel(&self, guild_id: u64) -> Result<Option<PanelRow>, PoiseError> {
+        let table_name = Self::TABLE_NAME;
+        let sql = format!("SELECT * FROM {table_name} WHERE guild_id = ?1");
+
+        Ok(self
+            .conn
+            .query_row(sql.as_str(), params![&guild_id], |row| {
+                PanelRow::try_from(row)
+            })
+            .optional()
+            .log_err_msg(format!("Failed finding panel. Guild id: {guild_id}"))?)
+    }
+
+    /// Panels of every guild
+    pub fn list_panels(&self) -> Result<Vec<PanelRow>, PoiseError> {
+        let table_name = Self::TABLE_NAME;
+        let sql = format!("SELECT * FROM {table_name} ORDER BY guild_id ASC");
+
+        let mut stmt = self
+            .conn
+            .prepare(sql.as_str())
+            .log_err_msg("Failed preparing list panels")?;
+
+        let rows = stmt
+            .query_map((), |row| PanelRow::try_from(row))
+            .log_err_msg("Failed listing panels")?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(rows)
+    }
+
+    /// Deletes guild's panel. Returns false if guild has no panel
+    pub fn delete_panel(&self, guild_id: u64) -> Result<bool, PoiseError> {
+        log::info!("Deleting panel. Guild id: {guild_id}");
+
+        let table_name = Self::TABLE_NAME;
+        let sql = format!("DELETE FROM {table_name} WHERE guild_id = ?1");
+
+        let count = self
+            .conn
+            .execute(sql.as_str(), params![&guild_id])
+            .log_err_msg("Failed deleting panel")?;
+
+        Ok(count > 0)
+    }
+}
+
+impl Table for PanelsTable {
+    fn connection(&self) -> &DbConnection {
+        &self.conn
+    }
+
+    fn drop_table(&self) {
+        let table_name = Self::TABLE_NAME;
+        log::info!("Dropping table: {table_name}");
+        let sql = format!("DROP TABLE IF EXISTS {table_name};");
+
+        self.conn
+            .execute_batch(sql.as_str())
+            .log_err_msg("Failed dropping table")
+            .log_ok_msg(format!("Dropped table {table_name}"))
+            .unwrap();
+    }
+
+    fn create_table(&self) {
+        let table_name = Self::TABLE_NAME;
+        log::info!("Creating table: {table_name}");
+        let sql = format!(
+            "
+            CREATE TABLE IF NOT EXISTS {table_name} (
+                guild_id INTEGER NOT NULL PRIMARY KEY,
+                channel_id INTEGER NOT NULL,
+                message_id INTEGER NOT NULL,
+                author_id INTEGER,
+                created_at VARCHAR(25) NOT NULL
+            );
+        "
+        );
+
+        self.conn
+            .execute_batch(sql.as_str())
+            .log_err_msg("Failed create table")
+            .log_ok_msg(format!("Created table {table_name}"))
+            .unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use r2d2_sqlite::SqliteConnectionManager;
+
+    use super::*;
+
+    #[test]
+    fn table_create_test() {
+        let db_manager = SqliteConnectionManager::memory();
+        let db_pool = r2d2::Pool::new(db_manager).unwrap();
+        let table = PanelsTable::new(db_pool.get().unwrap());
+        table.create_table();
+        table.create_table();
+    }
+
+    #[test]
+    fn upsert_find_delete_panels_test() {
+        let db_manager = SqliteConnectionManager::memory();
+        let db_pool = r2d2::Pool::new(db_manager).unwrap();
+        let table = PanelsTable::new(db_pool.get().unwrap());
+        table.create_table();
+
+        assert!(table.find_panel(1).unwrap().is_none());
+
+        table.upsert_panel(1, 10, 100, Some(5)).unwrap();
+        table.upsert_panel(2, 20, 200, None).unwrap();
+        let panel = table.find_panel(1).unwrap().unwrap();
+        assert_eq!((panel.channel_id, panel.message_id), (10, 100));
+        assert_eq!(table.list_panels().unwrap().len(), 2);
+
+        // guilds have one panel
+        table.upsert_panel(1, 11, 101, Some(5)).unwrap();
+        let panel = table.find_panel(1).unwrap().unwrap();
+        assert_eq!((panel.channel_id, panel.message_id), (11, 101));
+        assert_eq!(table.list_panels().unwrap().len(), 2);
+
+        // re-posted panels stay in their channel
+        table.update_panel_message(1, 102).unwrap();
+        let panel = table.find_panel(1).unwrap().unwrap();
+        assert_eq!((panel.channel_id, panel.message_id), (11, 102));
+
+        assert!(table.delete_panel(1).unwrap());
+        assert!(!table.delete_panel(1).unwrap());
+        assert!(table.find_panel(1).unwrap().is_none());
+        assert_eq!(table.list_panels().unwrap().len(), 1);
+    }
+}
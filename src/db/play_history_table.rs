@@ -0,0 +1,497 @@
+use rusqlite::{params, types::FromSql, ToSql};
+
+use crate::{commands::PoiseError, common::LogResult};
+
+use super::{AudioTable, DbConnection, Table};
+
+/// What triggered an audio track to play
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PlaySource {
+    Slash,
+    Button,
+    Random,
+    Join,
+    Schedule,
+    Collection,
+    Intro,
+    Outro,
+    Trigger,
+    Reaction,
+}
+
+impl PlaySource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Slash => "slash",
+            Self::Button => "button",
+            Self::Random => "random",
+            Self::Join => "join",
+            Self::Schedule => "schedule",
+            Self::Collection => "collection",
+            Self::Intro => "intro",
+            Self::Outro => "outro",
+            Self::Trigger => "trigger",
+            Self::Reaction => "reaction",
+        }
+    }
+}
+
+impl TryFrom<&str> for PlaySource {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "slash" => Ok(Self::Slash),
+            "button" => Ok(Self::Button),
+            "random" => Ok(Self::Random),
+            "join" => Ok(Self::Join),
+            "schedule" => Ok(Self::Schedule),
+            "collection" => Ok(Self::Collection),
+            "intro" => Ok(Self::Intro),
+            "outro" => Ok(Self::Outro),
+            "trigger" => Ok(Self::Trigger),
+            "reaction" => Ok(Self::Reaction),
+            val => Err(format!("Unknown play source: {val}")),
+        }
+    }
+}
+
+impl ToSql for PlaySource {
+    fn to_sql(&self) -> rusqlite::Result<rusqlite::types::ToSqlOutput<'_>> {
+        self.as_str().to_sql()
+    }
+}
+
+impl FromSql for PlaySource {
+    fn column_result(value: rusqlite::types::ValueRef<'_>) -> rusqlite::types::FromSqlResult<Self> {
+        PlaySource::try_from(value.as_str()?)
+            .map_err(|err| rusqlite::types::FromSqlError::Other(err.into()))
+    }
+}
+
+/// Play history entry to insert. `channel_id` is the voice channel the track played in, if known
+pub struct PlayHistoryEntry {
+    pub audio_id: i64,
+    pub user_id: Option<u64>,
+    pub guild_id: u64,
+    pub channel_id: Option<u64>,
+    pub source: PlaySource,
+}
+
+impl PlayHistoryEntry {
+    pub fn new(audio_id: i64, guild_id: impl Into<u64>, source: PlaySource) -> Self {
+        Self {
+            audio_id,
+            user_id: None,
+            guild_id: guild_id.into(),
+            channel_id: None,
+            source,
+        }
+    }
+
+    pub fn user_id(mut self, user_id: Option<impl Into<u64>>) -> Self {
+        self.user_id = user_id.map(|val| val.into());
+        self
+    }
+
+    pub fn channel_id(mut self, channel_id: Option<impl Into<u64>>) -> Self {
+        self.channel_id = channel_id.map(|val| val.into());
+        self
+    }
+}
+
+pub struct PlayHistoryTable {
+    conn: DbConnection,
+}
+
+impl PlayHistoryTable {
+    pub const TABLE_NAME: &'static str = "play_history";
+
+    pub fn new(connection: DbConnection) -> Self {
+        Self { conn: connection }
+    }
+
+    pub fn insert_entry(&self, entry: &PlayHistoryEntry) -> Result<(), PoiseError> {
+        self.insert_entry_at(entry, chrono::Utc::now())
+    }
+
+    pub fn insert_entry_at(
+        &self,
+        entry: &PlayHistoryEntry,
+        played_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<(), PoiseError> {
+        log::info!(
+            "Play history. Audio id: {}, Source: {}",
+            entry.audio_id,
+            entry.source.as_str()
+        );
+
+        let table_name = Self::TABLE_NAME;
+        let sql = format!(
+            "
+            INSERT INTO {table_name}
+                (audio_id, user_id, guild_id, channel_id, source, played_at)
+            VALUES
+                (?1, ?2, ?3, ?4, ?5, ?6)"
+        );
+
+        self.conn
+            .execute(
+                sql.as_str(),
+                params![
+                    &entry.audio_id,
+                    &entry.user_id,
+                    &entry.guild_id,
+                    &entry.channel_id,
+                    &entry.source,
+                    &played_at,
+                ],
+            )
+            .log_err_msg("Failed to insert play history entry")?;
+
+        Ok(())
+    }
+
+    /// Audio track id & play time of every play since `since`, optionally of a single audio track
+    pub fn list_plays_since(
+        &self,
+        since: chrono::DateTime<chrono::Utc>,
+        audio_id: Option<i64>,
+    ) -> Result<Vec<(i64, chrono::DateTime<chrono::Utc>)>, PoiseError> {
+        let table_name = Self::TABLE_NAME;
+        let sql = format!(
+            "SELECT audio_id, played_at FROM {table_name}
+            WHERE played_at >= ?1 AND (?2 IS NULL OR audio_id = ?2)"
+        );
+
+        let mut stmt = self
+            .conn
+            .prepare(sql.as_str())
+            .log_err_msg("Failed preparing play history sql")?;
+
+        let plays = stmt
+            .query_map(params![&since, &audio_id], |row| {
+                Ok((row.get("audio_id")?, row.get("played_at")?))
+            })
+            .log_err_msg("Failed querying play history")?
+            .filter_map(|row| row.log_err().ok())
+            .collect();
+
+        Ok(plays)
+    }
+
+    /// Most played audio tracks in guild since `since`. Returns (audio track name, play count)
+    pub fn top_tracks(
+        &self,
+        guild_id: u64,
+        since: Option<chrono::DateTime<chrono::Utc>>,
+        limit: u64,
+    ) -> Result<Vec<(String, u64)>, PoiseError> {
+        let table_name = Self::TABLE_NAME;
+        let audio_table_name = AudioTable::TABLE_NAME;
+        let sql = format!(
+            "SELECT Audio.name, COUNT(History.id) AS plays FROM {table_name} History
+            INNER JOIN {audio_table_name} Audio ON Audio.id = History.audio_id
+            WHERE History.guild_id = ?1
+                AND Audio.deleted_at IS NULL
+                AND (?2 IS NULL OR History.played_at >= ?2)
+            GROUP BY History.audio_id
+            ORDER BY plays DESC, Audio.name ASC
+            LIMIT {limit}"
+        );
+
+        self.query_counts(sql.as_str(), params![&guild_id, &since])
+    }
+
+    /// Users who played the most audio tracks in guild since `since`. Returns (user id, play count)
+    pub fn top_players(
+        &self,
+        guild_id: u64,
+        since: Option<chrono::DateTime<chrono::Utc>>,
+        limit: u64,
+    ) -> Result<Vec<(u64, u64)>, PoiseError> {
+        let table_name = Self::TABLE_NAME;
+        let sql = format!(
+            "SELECT user_id, COUNT(id) AS plays FROM {table_name}
+            WHERE guild_id = ?1
+                AND user_id IS NOT NULL
+                AND (?2 IS NULL OR played_at >= ?2)
+            GROUP BY user_id
+            ORDER BY plays DESC, user_id ASC
+            LIMIT {limit}"
+        );
+
+        self.query_counts(sql.as_str(), params![&guild_id, &since])
+    }
+
+    /// Play counts per UTC day in guild for the `days` days up to & including `today`.
+    /// Days without plays are included with a count of 0, oldest day first
+    pub fn plays_per_day(
+        &self,
+        guild_id: u64,
+        today: chrono::NaiveDate,
+        days: u64,
+    ) -> Result<Vec<(chrono::NaiveDate, u64)>, PoiseError> {
+        let table_name = Self::TABLE_NAME;
+        let first_day = today - chrono::Days::new(days.saturating_sub(1));
+        let sql = format!(
+            "SELECT substr(played_at, 1, 10) AS day, COUNT(id) FROM {table_name}
+            WHERE guild_id = ?1 AND played_at >= ?2
+            GROUP BY day"
+        );
+
+        let counts: std::collections::HashMap<String, u64> = self
+            .query_counts(sql.as_str(), params![&guild_id, &first_day.to_string()])?
+            .into_iter()
+            .collect();
+
+        Ok(first_day
+            .iter_days()
+            .take(days as usize)
+            .map(|day| (day, counts.get(&day.to_string()).copied().unwrap_or(0)))
+            .collect())
+    }
+
+    fn query_counts<K: FromSql>(
+        &self,
+        sql: &str,
+        params: impl rusqlite::Params,
+    ) -> Result<Vec<(K, u64)>, PoiseError> {
+        let mut stmt = self
+            .conn
+            .prepare(sql)
+            .log_err_msg("Failed preparing play history sql")?;
+
+        let rows = stmt
+            .query_map(params, |row| Ok((row.get(0)?, row.get(1)?)))
+            .log_err_msg("Failed querying play history")?
+            .filter_map(|row| row.log_err().ok())
+            .collect();
+
+        Ok(rows)
+    }
+
+    /// Deletes plays of audio tracks that no longer exist. ie: purged from the trash
+    pub fn delete_orphaned_entries(conn: &rusqlite::Connection) -> Result<usize, PoiseError> {
+        let table_name = Self::TABLE_NAME;
+        let audio_table_name = AudioTable::TABLE_NAME;
+        let sql = format!(
+            "DELETE FROM {table_name} WHERE audio_id NOT IN (SELECT id FROM {audio_table_name})"
+        );
+
+        let count = conn
+            .execute(sql.as_str(), ())
+            .log_err_msg("Failed deleting orphaned play history")?;
+
+        Ok(count)
+    }
+}
+
+impl Table for PlayHistoryTable {
+    fn connection(&self) -> &DbConnection {
+        &self.conn
+    }
+
+    fn drop_table(&self) {
+        let table_name = Self::TABLE_NAME;
+        log::info!("Dropping table: {table_name}");
+        let sql = format!("DROP TABLE IF EXISTS {table_name};");
+
+        self.conn
+            .execute_batch(sql.as_str())
+            .log_err_msg("Failed dropping table")
+            .log_ok_msg(format!("Dropped table {table_name}"))
+            .unwrap();
+    }
+
+    fn create_table(&self) {
+        let table_name = Self::TABLE_NAME;
+        log::info!("Creating table: {table_name}");
+        let sql = format!(
+            "
+            CREATE TABLE IF NOT EXISTS {table_name} (
+                id INTEGER PRIMARY KEY,
+                audio_id INTEGER NOT NULL,
+                user_id INTEGER,
+                guild_id INTEGER NOT NULL,
+                channel_id INTEGER,
+                source VARCHAR(16) NOT NULL,
+                played_at VARCHAR(25) NOT NULL
+            );
+
+            CREATE INDEX IF NOT EXISTS {table_name}_guild_played_at_idx
+                ON {table_name}(guild_id, played_at);
+            CREATE INDEX IF NOT EXISTS {table_name}_audio_id_idx ON {table_name}(audio_id);
+        "
+        );
+
+        self.conn
+            .execute_batch(sql.as_str())
+            .log_err_msg("Failed create table")
+            .log_ok_msg(format!("Created table {table_name}"))
+            .unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use r2d2_sqlite::SqliteConnectionManager;
+
+    use crate::{
+        audio::AudioFile,
+        db::{audio_table::AudioTableRowInsertBuilder, UniqueAudioTableCol},
+        helpers::uuid_v4_str,
+    };
+
+    use super::*;
+
+    struct PlayHistoryRow {
+        audio_id: i64,
+        user_id: Option<u64>,
+        channel_id: Option<u64>,
+        source: PlaySource,
+    }
+
+    /// Guild's plays, most recent first
+    fn list_entries(table: &PlayHistoryTable, guild_id: u64) -> Vec<PlayHistoryRow> {
+        let table_name = PlayHistoryTable::TABLE_NAME;
+        let sql = format!(
+            "SELECT * FROM {table_name} WHERE guild_id = ? ORDER BY played_at DESC, id DESC"
+        );
+
+        table
+            .connection()
+            .prepare(sql.as_str())
+            .unwrap()
+            .query_map([guild_id], |row| {
+                Ok(PlayHistoryRow {
+                    audio_id: row.get("audio_id")?,
+                    user_id: row.get("user_id")?,
+                    channel_id: row.get("channel_id")?,
+                    source: row.get("source")?,
+                })
+            })
+            .unwrap()
+            .map(|row| row.unwrap())
+            .collect()
+    }
+
+    fn get_play_history_table() -> PlayHistoryTable {
+        let db_manager = SqliteConnectionManager::memory();
+        let db_pool = r2d2::Pool::new(db_manager).unwrap();
+        PlayHistoryTable::new(db_pool.get().unwrap())
+    }
+
+    #[test]
+    fn table_create_test() {
+        let table = get_play_history_table();
+        table.create_table();
+        table.create_table();
+    }
+
+    #[test]
+    fn insert_and_list_entries_test() {
+        let table = get_play_history_table();
+        table.create_table();
+
+        let now = chrono::Utc::now();
+        table
+            .insert_entry_at(
+                &PlayHistoryEntry::new(1, 10u64, PlaySource::Slash).user_id(Some(100u64)),
+                now - chrono::Duration::minutes(2),
+            )
+            .unwrap();
+        table
+            .insert_entry_at(
+                &PlayHistoryEntry::new(2, 10u64, PlaySource::Button)
+                    .user_id(Some(101u64))
+                    .channel_id(Some(1000u64)),
+                now - chrono::Duration::minutes(1),
+            )
+            .unwrap();
+        table
+            .insert_entry_at(&PlayHistoryEntry::new(3, 20u64, PlaySource::Random), now)
+            .unwrap();
+
+        let rows = list_entries(&table, 10);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].audio_id, 2);
+        assert_eq!(rows[0].source, PlaySource::Button);
+        assert_eq!(rows[0].channel_id, Some(1000));
+        assert_eq!(rows[1].audio_id, 1);
+        assert_eq!(rows[1].user_id, Some(100));
+        assert_eq!(rows[1].channel_id, None);
+
+        let rows = list_entries(&table, 20);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].user_id, None);
+    }
+
+    #[test]
+    fn stats_queries_test() {
+        let db_manager = SqliteConnectionManager::memory();
+        let db_pool = r2d2::Pool::new(db_manager).unwrap();
+        let audio_table = AudioTable::new(db_pool.get().unwrap());
+        let table = PlayHistoryTable::new(db_pool.get().unwrap());
+        audio_table.create_table();
+        table.create_table();
+
+        for name in ["first", "second", "third"] {
+            let audio_file = AudioFile::new(format!("/tmp/{}.mp3", uuid_v4_str()).into());
+            audio_table
+                .insert_audio_row(AudioTableRowInsertBuilder::new(name, audio_file).build())
+                .unwrap();
+        }
+
+        let now = chrono::Utc::now();
+        let two_days_ago = now - chrono::Duration::days(2);
+        // (audio id, user id, guild id, played at)
+        let plays = [
+            (1, 100u64, 10u64, now),
+            (1, 100, 10, now),
+            (2, 101, 10, now),
+            (2, 101, 10, two_days_ago),
+            (2, 101, 10, two_days_ago),
+            (3, 102, 20, now),
+        ];
+        for (audio_id, user_id, guild_id, played_at) in plays {
+            table
+                .insert_entry_at(
+                    &PlayHistoryEntry::new(audio_id, guild_id, PlaySource::Button)
+                        .user_id(Some(user_id)),
+                    played_at,
+                )
+                .unwrap();
+        }
+
+        let yesterday = Some(now - chrono::Duration::days(1));
+        assert_eq!(
+            table.top_tracks(10, yesterday, 5).unwrap(),
+            vec![("first".to_string(), 2), ("second".to_string(), 1)]
+        );
+        assert_eq!(
+            table.top_tracks(10, None, 1).unwrap(),
+            vec![("second".to_string(), 3)]
+        );
+        assert_eq!(
+            table.top_players(10, None, 5).unwrap(),
+            vec![(101, 3), (100, 2)]
+        );
+        assert_eq!(table.top_players(20, yesterday, 5).unwrap(), vec![(102, 1)]);
+
+        // trashed audio tracks drop off the leaderboard
+        audio_table
+            .trash_audio_row(UniqueAudioTableCol::Name("first".into()))
+            .unwrap();
+        assert_eq!(
+            table.top_tracks(10, yesterday, 5).unwrap(),
+            vec![("second".to_string(), 1)]
+        );
+
+        let per_day = table.plays_per_day(10, now.date_naive(), 3).unwrap();
+        assert_eq!(per_day.len(), 3);
+        assert_eq!(per_day[0], (two_days_ago.date_naive(), 2));
+        assert_eq!(per_day[1].1, 0);
+        assert_eq!(per_day[2], (now.date_naive(), 3));
+    }
+}
@@ -0,0 +1,269 @@
+use rusqlite::params;
+
+use crate::{commands::PoiseError, common::LogResult};
+
+use super::{AudioTable, DbConnection, Table};
+
+/// Audio track played on a cron schedule. ie: a "hydrate" chime every hour
+#[derive(Debug, Clone)]
+pub struct ScheduleRow {
+    pub id: i64,
+    pub guild_id: u64,
+    pub audio_id: i64,
+    /// 5 field cron expression. See `scheduler::parse_cron`
+    pub cron: String,
+    /// Voice channel to play in. Whichever voice channel the bot is in if `None`
+    pub channel_id: Option<u64>,
+    #[allow(dead_code)]
+    pub author_id: Option<u64>,
+    #[allow(dead_code)]
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl TryFrom<&rusqlite::Row<'_>> for ScheduleRow {
+    type Error = rusqlite::Error;
+
+    fn try_from(row: &rusqlite::Row<'_>) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: row.get("id")?,
+            guild_id: row.get("guild_id")?,
+            audio_id: row.get("audio_id")?,
+            cron: row.get("cron")?,
+            channel_id: row.get("channel_id")?,
+            author_id: row.get("author_id")?,
+            created_at: row.get("created_at")?,
+        })
+    }
+}
+
+pub struct SchedulesTable {
+    conn: DbConnection,
+}
+
+impl SchedulesTable {
+    pub const TABLE_NAME: &'static str = "schedules";
+
+    pub fn new(connection: DbConnection) -> Self {
+        Self { conn: connection }
+    }
+
+    pub fn insert_schedule(
+        &self,
+        guild_id: u64,
+        audio_id: i64,
+        cron: impl AsRef<str>,
+        channel_id: Option<u64>,
+        author_id: Option<u64>,
+    ) -> Result<i64, PoiseError> {
+        let cron = cron.as_ref();
+        log::info!("Inserting schedule. Guild id: {guild_id}, Audio id: {audio_id}, Cron: {cron}");
+
+        let table_name = Self::TABLE_NAME;
+        let sql = format!(
+            "INSERT INTO {table_name} (guild_id, audio_id, cron, channel_id, author_id, created_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)"
+        );
+
+        self.conn
+            .execute(
+                sql.as_str(),
+                params![
+                    &guild_id,
+                    &audio_id,
+                    cron,
+                    &channel_id,
+                    &author_id,
+                    &chrono::Utc::now()
+                ],
+            )
+            .log_err_msg("Failed inserting schedule")?;
+
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// Deletes guild's schedule. Returns false if guild has no such schedule
+    pub fn delete_schedule(&self, guild_id: u64, schedule_id: i64) -> Result<bool, PoiseError> {
+        log::info!("Deleting schedule. Guild id: {guild_id}, Id: {schedule_id}");
+
+        let table_name = Self::TABLE_NAME;
+        let sql = format!("DELETE FROM {table_name} WHERE guild_id = ?1 AND id = ?2");
+
+        let count = self
+            .conn
+            .execute(sql.as_str(), params![&guild_id, &schedule_id])
+            .log_err_msg("Failed deleting schedule")?;
+
+        Ok(count > 0)
+    }
+
+    /// Guild's schedules & the names of their audio tracks, oldest first
+    pub fn list_schedules(&self, guild_id: u64) -> Result<Vec<(ScheduleRow, String)>, PoiseError> {
+        let table_name = Self::TABLE_NAME;
+        let audio_table_name = AudioTable::TABLE_NAME;
+        let sql = format!(
+            "SELECT Schedules.*, Audio.name AS audio_name FROM {table_name} Schedules
+            INNER JOIN {audio_table_name} Audio ON Audio.id = Schedules.audio_id
+            WHERE Schedules.guild_id = ?1
+            ORDER BY Schedules.id ASC"
+        );
+
+        let mut stmt = self
+            .conn
+            .prepare(sql.as_str())
+            .log_err_msg("Failed preparing list schedules")?;
+
+        let rows = stmt
+            .query_map(params![&guild_id], |row| {
+                Ok((ScheduleRow::try_from(row)?, row.get("audio_name")?))
+            })
+            .log_err_msg("Failed listing schedules")?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(rows)
+    }
+
+    /// Schedules of every guild
+    pub fn list_all_schedules(&self) -> Result<Vec<ScheduleRow>, PoiseError> {
+        let table_name = Self::TABLE_NAME;
+        let sql = format!("SELECT * FROM {table_name} ORDER BY id ASC");
+
+        let mut stmt = self
+            .conn
+            .prepare(sql.as_str())
+            .log_err_msg("Failed preparing list all schedules")?;
+
+        let rows = stmt
+            .query_map((), |row| ScheduleRow::try_from(row))
+            .log_err_msg("Failed listing all schedules")?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(rows)
+    }
+
+    /// Deletes schedules of audio tracks that no longer exist. ie: purged from the trash
+    pub fn delete_orphaned_schedules(conn: &rusqlite::Connection) -> Result<usize, PoiseError> {
+        let table_name = Self::TABLE_NAME;
+        let audio_table_name = AudioTable::TABLE_NAME;
+        let sql = format!(
+            "DELETE FROM {table_name} WHERE audio_id NOT IN (SELECT id FROM {audio_table_name})"
+        );
+
+        let count = conn
+            .execute(sql.as_str(), ())
+            .log_err_msg("Failed deleting orphaned schedules")?;
+
+        Ok(count)
+    }
+}
+
+impl Table for SchedulesTable {
+    fn connection(&self) -> &DbConnection {
+        &self.conn
+    }
+
+    fn drop_table(&self) {
+        let table_name = Self::TABLE_NAME;
+        log::info!("Dropping table: {table_name}");
+        let sql = format!("DROP TABLE IF EXISTS {table_name};");
+
+        self.conn
+            .execute_batch(sql.as_str())
+            .log_err_msg("Failed dropping table")
+            .log_ok_msg(format!("Dropped table {table_name}"))
+            .unwrap();
+    }
+
+    fn create_table(&self) {
+        let table_name = Self::TABLE_NAME;
+        log::info!("Creating table: {table_name}");
+        let sql = format!(
+            "
+            CREATE TABLE IF NOT EXISTS {table_name} (
+                id INTEGER PRIMARY KEY,
+                guild_id INTEGER NOT NULL,
+                audio_id INTEGER NOT NULL,
+                cron VARCHAR(100) NOT NULL,
+                channel_id INTEGER,
+                author_id INTEGER,
+                created_at VARCHAR(25) NOT NULL
+            );
+
+            CREATE INDEX IF NOT EXISTS {table_name}_guild_id_idx ON {table_name}(guild_id);
+        "
+        );
+
+        self.conn
+            .execute_batch(sql.as_str())
+            .log_err_msg("Failed create table")
+            .log_ok_msg(format!("Created table {table_name}"))
+            .unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use r2d2_sqlite::SqliteConnectionManager;
+
+    use crate::{
+        audio::AudioFile, db::audio_table::AudioTableRowInsertBuilder, helpers::uuid_v4_str,
+    };
+
+    use super::*;
+
+    #[test]
+    fn table_create_test() {
+        let db_manager = SqliteConnectionManager::memory();
+        let db_pool = r2d2::Pool::new(db_manager).unwrap();
+        let table = SchedulesTable::new(db_pool.get().unwrap());
+        table.create_table();
+        table.create_table();
+    }
+
+    #[test]
+    fn insert_list_delete_schedules_test() {
+        let db_manager = SqliteConnectionManager::memory();
+        let db_pool = r2d2::Pool::new(db_manager).unwrap();
+        let audio_table = AudioTable::new(db_pool.get().unwrap());
+        let table = SchedulesTable::new(db_pool.get().unwrap());
+        audio_table.create_table();
+        table.create_table();
+
+        let audio_file = AudioFile::new(format!("/tmp/{}.mp3", uuid_v4_str()).into());
+        audio_table
+            .insert_audio_row(AudioTableRowInsertBuilder::new("hydrate", audio_file).build())
+            .unwrap();
+
+        let hourly = table
+            .insert_schedule(1, 1, "0 * * * *", None, Some(10))
+            .unwrap();
+        table
+            .insert_schedule(1, 1, "0 21 * * FRI", Some(20), Some(10))
+            .unwrap();
+        table
+            .insert_schedule(2, 1, "*/5 * * * *", None, None)
+            .unwrap();
+
+        let schedules = table.list_schedules(1).unwrap();
+        assert_eq!(schedules.len(), 2);
+        assert_eq!(schedules[0].0.cron, "0 * * * *");
+        assert_eq!(schedules[0].1, "hydrate");
+        assert_eq!(schedules[1].0.channel_id, Some(20));
+        assert_eq!(table.list_all_schedules().unwrap().len(), 3);
+
+        // can't delete other guilds' schedules
+        assert!(!table.delete_schedule(2, hourly).unwrap());
+        assert!(table.delete_schedule(1, hourly).unwrap());
+        assert!(!table.delete_schedule(1, hourly).unwrap());
+        assert_eq!(table.list_schedules(1).unwrap().len(), 1);
+
+        // schedules of purged tracks are deleted
+        table
+            .insert_schedule(1, 99, "0 * * * *", None, None)
+            .unwrap();
+        assert_eq!(
+            SchedulesTable::delete_orphaned_schedules(&table.conn).unwrap(),
+            1
+        );
+        assert_eq!(table.list_all_schedules().unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,546 @@
+use rusqlite::types::Value;
+
+use crate::errors::SearchQueryError;
+
+use super::{AudioTable, Tags, TagsTable};
+
+/// Comparison of a numeric search filter. ie: `played:>10`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Comparison {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+}
+
+impl Comparison {
+    /// Splits leading comparison operator from value. ie: `<=3s` -> (`Le`, `3s`)
+    fn split(value: &str) -> (Self, &str) {
+        if let Some(rest) = value.strip_prefix("<=") {
+            (Self::Le, rest)
+        } else if let Some(rest) = value.strip_prefix(">=") {
+            (Self::Ge, rest)
+        } else if let Some(rest) = value.strip_prefix('<') {
+            (Self::Lt, rest)
+        } else if let Some(rest) = value.strip_prefix('>') {
+            (Self::Gt, rest)
+        } else if let Some(rest) = value.strip_prefix('=') {
+            (Self::Eq, rest)
+        } else {
+            (Self::Eq, value)
+        }
+    }
+
+    fn to_sql_str(self) -> &'static str {
+        match self {
+            Self::Lt => "<",
+            Self::Le => "<=",
+            Self::Gt => ">",
+            Self::Ge => ">=",
+            Self::Eq => "=",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Author {
+    /// ie: `by:<@1234>`
+    Id(u64),
+    /// Username or display name. ie: `by:@krywicki`
+    Name(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SearchFilter {
+    /// Word or `"quoted phrase"` in names & tags
+    Text(String),
+    /// ie: `tag:starwars`
+    Tag(String),
+    /// ie: `by:@user`
+    By(Author),
+    /// Duration in milliseconds. ie: `duration:<3s`
+    Duration(Comparison, u64),
+    /// ie: `pinned:true`
+    Pinned(bool),
+    /// Play count. ie: `played:>10`
+    Played(Comparison, i64),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchTerm {
+    pub filter: SearchFilter,
+    /// Excluded with a leading `-`. ie: `-vader`
+    pub negated: bool,
+}
+
+/// Search of audio tracks, parsed from the `/sounds display {search}` query language.
+///
+/// Terms are space separated & must all match, unless joined by `OR`. ie:
+/// `tag:starwars -vader duration:<3s`, `jaws OR shark`, `by:@user pinned:true played:>10`
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SearchQuery {
+    /// Terms of each group are OR'd, and groups are AND'd
+    pub groups: Vec<Vec<SearchTerm>>,
+}
+
+/// Search query compiled to sql, with the audio table aliased as `Audio`
+#[derive(Debug, PartialEq)]
+pub struct SearchSql {
+    /// FTS5 MATCH expression of groups made up only of included text terms
+    pub fts_match: Option<String>,
+    /// Conditions of every other group
+    pub where_sql: Vec<String>,
+    /// Named params of `where_sql`. ie: `:search_0`
+    pub params: Vec<(String, Value)>,
+}
+
+impl SearchQuery {
+    pub fn parse(query: impl AsRef<str>) -> Result<Self, SearchQueryError> {
+        let mut groups: Vec<Vec<SearchTerm>> = vec![];
+        let mut or_pending = false;
+
+        for token in tokenize(query.as_ref()) {
+            if token == "OR" {
+                // dangling `OR`s are ignored
+                or_pending = !groups.is_empty();
+                continue;
+            }
+
+            let term = parse_term(&token)?;
+            match groups.last_mut() {
+                Some(group) if or_pending => group.push(term),
+                _ => groups.push(vec![term]),
+            }
+            or_pending = false;
+        }
+
+        Ok(Self { groups })
+    }
+
+    pub fn to_sql(&self) -> SearchSql {
+        let mut fts_groups: Vec<String> = vec![];
+        let mut where_sql: Vec<String> = vec![];
+        let mut params: Vec<(String, Value)> = vec![];
+
+        for group in self.groups.iter() {
+            let fts_only = group
+                .iter()
+                .all(|term| !term.negated && matches!(term.filter, SearchFilter::Text(_)));
+
+            if fts_only {
+                let phrases: Vec<_> = group
+                    .iter()
+                    .filter_map(|term| match &term.filter {
+                        SearchFilter::Text(text) => Some(fts_phrase(text)),
+                        _ => None,
+                    })
+                    .collect();
+
+                fts_groups.push(match phrases.len() {
+                    1 => phrases.join(""),
+                    _ => format!("({})", phrases.join(" OR ")),
+                });
+            } else {
+                let conditions: Vec<_> = group
+                    .iter()
+                    .map(|term| term_sql(term, &mut params))
+                    .collect();
+
+                where_sql.push(match conditions.len() {
+                    1 => conditions.join(""),
+                    _ => format!("({})", conditions.join(" OR ")),
+                });
+            }
+        }
+
+        SearchSql {
+            fts_match: (!fts_groups.is_empty()).then(|| fts_groups.join(" AND ")),
+            where_sql,
+            params,
+        }
+    }
+}
+
+/// Splits query on whitespace, keeping `"quoted phrases"` together, quotes included
+fn tokenize(query: &str) -> Vec<String> {
+    let mut tokens = vec![];
+    let mut token = String::new();
+    let mut in_quotes = false;
+
+    for c in query.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                token.push(c);
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if !token.is_empty() {
+                    tokens.push(std::mem::take(&mut token));
+                }
+            }
+            c => token.push(c),
+        }
+    }
+
+    if !token.is_empty() {
+        tokens.push(token);
+    }
+
+    tokens
+}
+
+fn parse_term(token: &str) -> Result<SearchTerm, SearchQueryError> {
+    let (negated, token) = match token.strip_prefix('-') {
+        Some(rest) if !rest.is_empty() => (true, rest),
+        _ => (false, token),
+    };
+
+    let filter = match token.split_once(':') {
+        Some((key, value)) if !token.starts_with('"') => match key.to_lowercase().as_str() {
+            "tag" => SearchFilter::Tag(parse_tag(value)?),
+            "by" => SearchFilter::By(parse_author(value)?),
+            "duration" => {
+                let (cmp, value) = Comparison::split(value);
+                SearchFilter::Duration(cmp, parse_duration_ms(value)?)
+            }
+            "pinned" => SearchFilter::Pinned(parse_bool(value)?),
+            "played" => {
+                let (cmp, value) = Comparison::split(value);
+                let count: u32 = value.parse().map_err(|_| SearchQueryError::PlayCount {
+                    value: value.into(),
+                })?;
+                SearchFilter::Played(cmp, count.into())
+            }
+            // not a filter. ie: `re:zero`
+            _ => SearchFilter::Text(token.into()),
+        },
+        _ => SearchFilter::Text(token.replace('"', "")),
+    };
+
+    Ok(SearchTerm { filter, negated })
+}
+
+fn parse_tag(value: &str) -> Result<String, SearchQueryError> {
+    match Tags::from(value).as_slice() {
+        [tag] if !tag.is_empty() => Ok(tag.clone()),
+        _ => Err(SearchQueryError::Tag {
+            value: value.into(),
+        }),
+    }
+}
+
+fn parse_author(value: &str) -> Result<Author, SearchQueryError> {
+    // discord mentions. ie: `<@1234>` or `<@!1234>`
+    let mention_id = value
+        .strip_prefix("<@")
+        .and_then(|val| val.strip_suffix('>'))
+        .map(|val| val.trim_start_matches('!'));
+
+    match mention_id {
+        Some(id) => id
+            .parse()
+            .map(Author::Id)
+            .map_err(|_| SearchQueryError::Author {
+                value: value.into(),
+            }),
+        None => match value.trim_start_matches('@') {
+            "" => Err(SearchQueryError::Author {
+                value: value.into(),
+            }),
+            name => Ok(Author::Name(name.into())),
+        },
+    }
+}
+
+/// Duration in milliseconds. ie: `500ms`, `3s`, `1.5m`. Defaults to seconds without a unit
+fn parse_duration_ms(value: &str) -> Result<u64, SearchQueryError> {
+    let (number, unit_ms) = if let Some(number) = value.strip_suffix("ms") {
+        (number, 1.0)
+    } else if let Some(number) = value.strip_suffix('s') {
+        (number, 1_000.0)
+    } else if let Some(number) = value.strip_suffix('m') {
+        (number, 60_000.0)
+    } else {
+        (value, 1_000.0)
+    };
+
+    match number.parse::<f64>() {
+        Ok(number) if number.is_finite() && number >= 0.0 => Ok((number * unit_ms).round() as u64),
+        _ => Err(SearchQueryError::Duration {
+            value: value.into(),
+        }),
+    }
+}
+
+fn parse_bool(value: &str) -> Result<bool, SearchQueryError> {
+    match value.to_lowercase().as_str() {
+        "true" | "yes" | "1" => Ok(true),
+        "false" | "no" | "0" => Ok(false),
+        _ => Err(SearchQueryError::Pinned {
+            value: value.into(),
+        }),
+    }
+}
+
+/// FTS5 phrase of text, so it's matched literally
+pub fn fts_phrase(text: impl AsRef<str>) -> String {
+    format!("\"{}\"", text.as_ref().replace('"', "\"\""))
+}
+
+fn term_sql(term: &SearchTerm, params: &mut Vec<(String, Value)>) -> String {
+    let param = format!(":search_{}", params.len());
+    let fts_table_name = AudioTable::FTS5_TABLE_NAME;
+    let tags_table_name = TagsTable::TABLE_NAME;
+    let audio_tags_table_name = TagsTable::AUDIO_TAGS_TABLE_NAME;
+
+    let (condition, value) = match &term.filter {
+        SearchFilter::Text(text) => (
+            format!("Audio.id IN (SELECT rowid FROM {fts_table_name}({param}))"),
+            Value::Text(fts_phrase(text)),
+        ),
+        SearchFilter::Tag(tag) => (
+            format!(
+                "EXISTS (SELECT 1 FROM {audio_tags_table_name} SearchAudioTag
+                    INNER JOIN {tags_table_name} SearchTag ON SearchTag.id = SearchAudioTag.tag_id
+                    WHERE SearchAudioTag.audio_id = Audio.id AND SearchTag.name = {param})"
+            ),
+            Value::Text(tag.clone()),
+        ),
+        SearchFilter::By(Author::Id(id)) => (
+            format!("Audio.author_id = {param}"),
+            Value::Integer(*id as i64),
+        ),
+        SearchFilter::By(Author::Name(name)) => (
+            format!(
+                "(Audio.author_name = {param} COLLATE NOCASE
+                    OR Audio.author_global_name = {param} COLLATE NOCASE)"
+            ),
+            Value::Text(name.clone()),
+        ),
+        SearchFilter::Duration(cmp, duration_ms) => (
+            format!("Audio.duration_ms {} {param}", cmp.to_sql_str()),
+            Value::Integer(*duration_ms as i64),
+        ),
+        SearchFilter::Pinned(pinned) => (
+            format!("Audio.pinned = {param}"),
+            Value::Integer(*pinned as i64),
+        ),
+        SearchFilter::Played(cmp, count) => (
+            format!("Audio.play_count {} {param}", cmp.to_sql_str()),
+            Value::Integer(*count),
+        ),
+    };
+
+    params.push((param, value));
+
+    if term.negated {
+        // unknown values (ie: NULL durations) don't match, so they're kept when excluding
+        format!("NOT IFNULL({condition}, FALSE)")
+    } else {
+        condition
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(value: &str) -> SearchTerm {
+        SearchTerm {
+            filter: SearchFilter::Text(value.into()),
+            negated: false,
+        }
+    }
+
+    fn not(mut term: SearchTerm) -> SearchTerm {
+        term.negated = true;
+        term
+    }
+
+    fn filter(filter: SearchFilter) -> SearchTerm {
+        SearchTerm {
+            filter,
+            negated: false,
+        }
+    }
+
+    #[test]
+    fn tokenize_test() {
+        assert_eq!(tokenize("  star   wars "), vec!["star", "wars"]);
+        assert_eq!(
+            tokenize("\"star wars\" -\"darth vader\" tag:jedi"),
+            vec!["\"star wars\"", "-\"darth vader\"", "tag:jedi"]
+        );
+        assert_eq!(tokenize("\"unclosed quote"), vec!["\"unclosed quote"]);
+        assert_eq!(tokenize(""), Vec::<String>::new());
+    }
+
+    #[test]
+    fn parse_text_test() {
+        assert_eq!(SearchQuery::parse("").unwrap().groups.len(), 0);
+        assert_eq!(
+            SearchQuery::parse("star \"darth vader\" -jaws").unwrap(),
+            SearchQuery {
+                groups: vec![
+                    vec![text("star")],
+                    vec![text("darth vader")],
+                    vec![not(text("jaws"))]
+                ]
+            }
+        );
+
+        // lone dash & unknown keys are text
+        assert_eq!(
+            SearchQuery::parse("- re:zero").unwrap(),
+            SearchQuery {
+                groups: vec![vec![text("-")], vec![text("re:zero")]]
+            }
+        );
+
+        // quoted filters are text
+        assert_eq!(
+            SearchQuery::parse("\"tag:jedi\"").unwrap(),
+            SearchQuery {
+                groups: vec![vec![text("tag:jedi")]]
+            }
+        );
+    }
+
+    #[test]
+    fn parse_or_test() {
+        assert_eq!(
+            SearchQuery::parse("jaws OR shark OR -tag:movie vader").unwrap(),
+            SearchQuery {
+                groups: vec![
+                    vec![
+                        text("jaws"),
+                        text("shark"),
+                        not(filter(SearchFilter::Tag("movie".into())))
+                    ],
+                    vec![text("vader")]
+                ]
+            }
+        );
+
+        // dangling & lowercase `or`
+        assert_eq!(
+            SearchQuery::parse("OR jaws or shark OR").unwrap(),
+            SearchQuery {
+                groups: vec![vec![text("jaws")], vec![text("or")], vec![text("shark")]]
+            }
+        );
+    }
+
+    #[test]
+    fn parse_filters_test() {
+        let query = SearchQuery::parse(
+            "tag:StarWars by:@krywicki by:<@!1234> duration:<3s duration:>=1.5m duration:500ms \
+            pinned:true PINNED:no played:>10 played:3",
+        )
+        .unwrap();
+
+        let filters: Vec<_> = query
+            .groups
+            .into_iter()
+            .map(|group| group[0].filter.clone())
+            .collect();
+
+        assert_eq!(
+            filters,
+            vec![
+                SearchFilter::Tag("StarWars".into()),
+                SearchFilter::By(Author::Name("krywicki".into())),
+                SearchFilter::By(Author::Id(1234)),
+                SearchFilter::Duration(Comparison::Lt, 3_000),
+                SearchFilter::Duration(Comparison::Ge, 90_000),
+                SearchFilter::Duration(Comparison::Eq, 500),
+                SearchFilter::Pinned(true),
+                SearchFilter::Pinned(false),
+                SearchFilter::Played(Comparison::Gt, 10),
+                SearchFilter::Played(Comparison::Eq, 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_errors_test() {
+        for query in [
+            "tag:",
+            "tag:!!",
+            "by:",
+            "by:@",
+            "by:<@abc>",
+            "duration:<",
+            "duration:fast",
+            "duration:-3s",
+            "pinned:maybe",
+            "played:>lots",
+            "played:-1",
+        ] {
+            assert!(SearchQuery::parse(query).is_err(), "{query}");
+        }
+    }
+
+    #[test]
+    fn fts_sql_test() {
+        let sql = SearchQuery::parse("star OR \"darth \"\"vader\" luke")
+            .unwrap()
+            .to_sql();
+
+        assert_eq!(
+            sql.fts_match,
+            Some("(\"star\" OR \"darth vader\") AND \"luke\"".into())
+        );
+        assert!(sql.where_sql.is_empty());
+        assert!(sql.params.is_empty());
+
+        let sql = SearchQuery::parse("").unwrap().to_sql();
+        assert_eq!(sql.fts_match, None);
+        assert!(sql.where_sql.is_empty());
+    }
+
+    #[test]
+    fn where_sql_test() {
+        let sql =
+            SearchQuery::parse("star -vader by:<@42> pinned:false played:<=2 OR duration:>1s")
+                .unwrap()
+                .to_sql();
+
+        assert_eq!(sql.fts_match, Some("\"star\"".into()));
+        assert_eq!(
+            sql.where_sql,
+            vec![
+                "NOT IFNULL(Audio.id IN (SELECT rowid FROM fts5_audio(:search_0)), FALSE)",
+                "Audio.author_id = :search_1",
+                "Audio.pinned = :search_2",
+                "(Audio.play_count <= :search_3 OR Audio.duration_ms > :search_4)",
+            ]
+        );
+        assert_eq!(
+            sql.params,
+            vec![
+                (":search_0".into(), Value::Text("\"vader\"".into())),
+                (":search_1".into(), Value::Integer(42)),
+                (":search_2".into(), Value::Integer(0)),
+                (":search_3".into(), Value::Integer(2)),
+                (":search_4".into(), Value::Integer(1_000)),
+            ]
+        );
+
+        // text OR'd with a filter can't be a single fts match
+        let sql = SearchQuery::parse("jaws OR tag:shark").unwrap().to_sql();
+        assert_eq!(sql.fts_match, None);
+        assert_eq!(sql.where_sql.len(), 1);
+        assert!(sql.where_sql[0]
+            .starts_with("(Audio.id IN (SELECT rowid FROM fts5_audio(:search_0)) OR EXISTS ("));
+        assert!(sql.where_sql[0].ends_with("SearchTag.name = :search_1))"));
+
+        let sql = SearchQuery::parse("by:Krywicki").unwrap().to_sql();
+        assert_eq!(sql.where_sql[0].matches(":search_0").count(), 2);
+        assert_eq!(
+            sql.params,
+            vec![(":search_0".into(), Value::Text("Krywicki".into()))]
+        );
+    }
+}
@@ -0,0 +1,521 @@
+use rusqlite::{params, OptionalExtension};
+
+use crate::{commands::PoiseError, common::LogResult};
+
+use super::{AudioTable, DbConnection, Table, Tags};
+
+/// Normalized tags of audio tracks. Kept in sync with the `audio.tags` column by triggers, so the
+/// column (and its FTS5 index) remains the source used for searching
+pub struct TagsTable {
+    conn: DbConnection,
+}
+
+impl TagsTable {
+    pub const TABLE_NAME: &'static str = "tags";
+    pub const AUDIO_TAGS_TABLE_NAME: &'static str = "audio_tags";
+
+    pub fn new(connection: DbConnection) -> Self {
+        Self { conn: connection }
+    }
+
+    /// Tags & the number of non-trashed audio tracks tagged with each, most used first
+    pub fn list_tags(&self, limit: Option<usize>) -> Result<Vec<(String, u64)>, PoiseError> {
+        let table_name = Self::TABLE_NAME;
+        let audio_tags_table_name = Self::AUDIO_TAGS_TABLE_NAME;
+        let audio_table_name = AudioTable::TABLE_NAME;
+        let limit_sql = limit
+            .map(|limit| format!("LIMIT {limit}"))
+            .unwrap_or_default();
+
+        let sql = format!(
+            "SELECT Tag.name, COUNT(Audio.id) AS audio_count FROM {table_name} Tag
+            INNER JOIN {audio_tags_table_name} AudioTag ON AudioTag.tag_id = Tag.id
+            INNER JOIN {audio_table_name} Audio ON Audio.id = AudioTag.audio_id
+            WHERE Audio.deleted_at IS NULL
+            GROUP BY Tag.id
+            ORDER BY audio_count DESC, Tag.name ASC
+            {limit_sql}"
+        );
+
+        let mut stmt = self
+            .conn
+            .prepare(sql.as_str())
+            .log_err_msg("Failed preparing list tags")?;
+
+        let rows = stmt
+            .query_map((), |row| Ok((row.get(0)?, row.get(1)?)))
+            .log_err_msg("Failed listing tags")?
+            .filter_map(|row| row.log_err_msg("Failed reading tag row").ok())
+            .collect();
+
+        Ok(rows)
+    }
+
+    /// Names of tags containing `partial`
+    pub fn autocomplete_tag_names(
+        &self,
+        partial: impl AsRef<str>,
+        limit: Option<usize>,
+    ) -> Vec<String> {
+        let text = partial.as_ref();
+        let limit = limit.unwrap_or(5);
+        let table_name = Self::TABLE_NAME;
+
+        let sql = format!(
+            "SELECT name FROM {table_name}
+            WHERE name LIKE '%' || ? || '%'
+            ORDER BY name ASC LIMIT {limit}"
+        );
+        let mut stmt = self
+            .conn
+            .prepare(sql.as_str())
+            .expect("Autocomplete tags sql invalid");
+
+        let rows = stmt.query_map([text], |row| row.get("name"));
+        match rows {
+            Ok(rows) => rows.filter_map(|row| row.ok()).collect(),
+            Err(err) => {
+                log::error!("Autocomplete tags sql query error - {err}");
+                vec![]
+            }
+        }
+    }
+
+    pub fn find_tag_id(&self, name: impl AsRef<str>) -> Result<Option<i64>, PoiseError> {
+        let table_name = Self::TABLE_NAME;
+        let sql = format!("SELECT id FROM {table_name} WHERE name = ?1");
+
+        let tag_id = self
+            .conn
+            .query_row(sql.as_str(), [name.as_ref()], |row| row.get(0))
+            .optional()
+            .log_err_msg("Failed finding tag")?;
+
+        Ok(tag_id)
+    }
+
+    pub fn find_tag_name(&self, id: i64) -> Result<Option<String>, PoiseError> {
+        let table_name = Self::TABLE_NAME;
+        let sql = format!("SELECT name FROM {table_name} WHERE id = ?1");
+
+        let name = self
+            .conn
+            .query_row(sql.as_str(), [id], |row| row.get(0))
+            .optional()
+            .log_err_msg("Failed finding tag name")?;
+
+        Ok(name)
+    }
+
+    /// Renames tag on every audio track. Returns the number of audio tracks retagged
+    pub fn rename_tag(
+        &self,
+        name: impl AsRef<str>,
+        new_name: impl AsRef<str>,
+    ) -> Result<usize, PoiseError> {
+        let name = name.as_ref();
+        let new_name = Self::clean_tag_name(new_name)?;
+        log::info!("Renaming tag '{name}' to '{new_name}'");
+
+        let tag_id = self
+            .find_tag_id(name)?
+            .ok_or(format!("Tag `{name}` doesn't exist"))?;
+
+        if let Some(new_tag_id) = self.find_tag_id(&new_name)? {
+            if new_tag_id != tag_id {
+                return Err(
+                    format!("Tag `{new_name}` already exists. Merge the tags instead").into(),
+                );
+            }
+        }
+
+        let table_name = Self::TABLE_NAME;
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .log_err_msg("Failed starting rename tag transaction")?;
+
+        tx.execute(
+            format!("UPDATE {table_name} SET name = ?1 WHERE id = ?2").as_str(),
+            params![&new_name, &tag_id],
+        )
+        .log_err_msg("Failed renaming tag")?;
+
+        let count = Self::write_audio_tags_column(&tx, tag_id)?;
+        tx.commit().log_err_msg("Failed committing tag rename")?;
+
+        Ok(count)
+    }
+
+    /// Retags audio tracks tagged `name` with `into_name`, removing tag `name`.
+    /// Returns the number of audio tracks retagged
+    pub fn merge_tags(
+        &self,
+        name: impl AsRef<str>,
+        into_name: impl AsRef<str>,
+    ) -> Result<usize, PoiseError> {
+        let name = name.as_ref();
+        let into_name = Self::clean_tag_name(into_name)?;
+        log::info!("Merging tag '{name}' into '{into_name}'");
+
+        let tag_id = self
+            .find_tag_id(name)?
+            .ok_or(format!("Tag `{name}` doesn't exist"))?;
+
+        let into_tag_id = match self.find_tag_id(&into_name)? {
+            Some(into_tag_id) if into_tag_id == tag_id => {
+                return Err("Can't merge a tag into itself".into())
+            }
+            Some(into_tag_id) => into_tag_id,
+            // nothing to merge into, so it's just a rename
+            None => return self.rename_tag(name, into_name),
+        };
+
+        let table_name = Self::TABLE_NAME;
+        let audio_tags_table_name = Self::AUDIO_TAGS_TABLE_NAME;
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .log_err_msg("Failed starting merge tags transaction")?;
+
+        tx.execute(
+            format!(
+                "INSERT OR IGNORE INTO {audio_tags_table_name} (audio_id, tag_id, position)
+                SELECT audio_id, ?2, position FROM {audio_tags_table_name} WHERE tag_id = ?1"
+            )
+            .as_str(),
+            params![&tag_id, &into_tag_id],
+        )
+        .log_err_msg("Failed merging audio tags")?;
+
+        tx.execute(
+            format!("DELETE FROM {audio_tags_table_name} WHERE tag_id = ?1").as_str(),
+            params![&tag_id],
+        )
+        .log_err_msg("Failed deleting merged audio tags")?;
+
+        tx.execute(
+            format!("DELETE FROM {table_name} WHERE id = ?1").as_str(),
+            params![&tag_id],
+        )
+        .log_err_msg("Failed deleting merged tag")?;
+
+        let count = Self::write_audio_tags_column(&tx, into_tag_id)?;
+        tx.commit().log_err_msg("Failed committing tag merge")?;
+
+        Ok(count)
+    }
+
+    fn clean_tag_name(name: impl AsRef<str>) -> Result<String, PoiseError> {
+        match Tags::from(name.as_ref()).as_slice() {
+            [tag] if !tag.is_empty() && !tag.contains(' ') => Ok(tag.clone()),
+            _ => Err(format!("`{}` isn't a valid tag", name.as_ref()).into()),
+        }
+    }
+
+    /// Rewrites `audio.tags` of audio tracks tagged with tag, from their normalized tags.
+    /// The audio table triggers then re-index them for searching
+    fn write_audio_tags_column(
+        conn: &rusqlite::Connection,
+        tag_id: i64,
+    ) -> Result<usize, PoiseError> {
+        let table_name = Self::TABLE_NAME;
+        let audio_tags_table_name = Self::AUDIO_TAGS_TABLE_NAME;
+        let audio_table_name = AudioTable::TABLE_NAME;
+
+        let sql = format!(
+            "UPDATE {audio_table_name} SET tags = (
+                SELECT group_concat(name, ' ') FROM (
+                    SELECT Tag.name FROM {audio_tags_table_name} AudioTag
+                    INNER JOIN {table_name} Tag ON Tag.id = AudioTag.tag_id
+                    WHERE AudioTag.audio_id = {audio_table_name}.id
+                    ORDER BY AudioTag.position ASC
+                )
+            )
+            WHERE id IN (SELECT audio_id FROM {audio_tags_table_name} WHERE tag_id = ?1)"
+        );
+
+        let count = conn
+            .execute(sql.as_str(), params![&tag_id])
+            .log_err_msg("Failed writing audio tags")?;
+
+        Ok(count)
+    }
+}
+
+impl Table for TagsTable {
+    fn connection(&self) -> &DbConnection {
+        &self.conn
+    }
+
+    fn drop_table(&self) {
+        let table_name = Self::TABLE_NAME;
+        let audio_tags_table_name = Self::AUDIO_TAGS_TABLE_NAME;
+        log::info!("Dropping tables: {table_name}, {audio_tags_table_name}");
+        let sql = format!(
+            "
+            DROP TRIGGER IF EXISTS {audio_tags_table_name}_insert;
+            DROP TRIGGER IF EXISTS {audio_tags_table_name}_update;
+            DROP TRIGGER IF EXISTS {audio_tags_table_name}_delete;
+            DROP TABLE IF EXISTS {audio_tags_table_name};
+            DROP TABLE IF EXISTS {table_name};
+        "
+        );
+
+        self.conn
+            .execute_batch(sql.as_str())
+            .log_err_msg("Failed dropping table")
+            .log_ok_msg(format!(
+                "Dropped tables {table_name}, {audio_tags_table_name}"
+            ))
+            .unwrap();
+    }
+
+    /// Requires the audio table. Tags of audio tracks added before tags were normalized are
+    /// migrated from the `audio.tags` column
+    fn create_table(&self) {
+        let table_name = Self::TABLE_NAME;
+        let audio_tags_table_name = Self::AUDIO_TAGS_TABLE_NAME;
+        let audio_table_name = AudioTable::TABLE_NAME;
+        log::info!("Creating tables: {table_name}, {audio_tags_table_name}");
+
+        // `audio.tags` only holds space separated tags of `[a-zA-Z0-9-_ ]`, so it's split by
+        // reading it as a json array. ie: `key` is the tag's position, `value` its name
+        let split_tags_sql =
+            |tags: &str| format!("json_each('[\"' || replace({tags}, ' ', '\",\"') || '\"]')");
+        let new_tags_sql = split_tags_sql("new.tags");
+        let audio_tags_sql = split_tags_sql("Audio.tags");
+
+        let sql = format!(
+            "
+            BEGIN;
+                CREATE TABLE IF NOT EXISTS {table_name} (
+                    id INTEGER PRIMARY KEY,
+                    name VARCHAR(256) NOT NULL UNIQUE COLLATE NOCASE
+                );
+
+                CREATE TABLE IF NOT EXISTS {audio_tags_table_name} (
+                    audio_id INTEGER NOT NULL,
+                    tag_id INTEGER NOT NULL,
+                    position INTEGER NOT NULL DEFAULT 0,
+                    PRIMARY KEY (audio_id, tag_id)
+                );
+
+                CREATE INDEX IF NOT EXISTS {audio_tags_table_name}_tag_id_idx
+                    ON {audio_tags_table_name}(tag_id);
+
+                CREATE TRIGGER IF NOT EXISTS {audio_tags_table_name}_insert
+                AFTER INSERT ON {audio_table_name} BEGIN
+                    INSERT OR IGNORE INTO {table_name} (name)
+                        SELECT value FROM {new_tags_sql} WHERE value != '';
+
+                    INSERT OR IGNORE INTO {audio_tags_table_name} (audio_id, tag_id, position)
+                        SELECT new.id, Tag.id, NewTag.key FROM {new_tags_sql} NewTag
+                        INNER JOIN {table_name} Tag ON Tag.name = NewTag.value;
+                END;
+
+                CREATE TRIGGER IF NOT EXISTS {audio_tags_table_name}_update
+                AFTER UPDATE OF tags ON {audio_table_name} BEGIN
+                    DELETE FROM {audio_tags_table_name} WHERE audio_id = old.id;
+
+                    INSERT OR IGNORE INTO {table_name} (name)
+                        SELECT value FROM {new_tags_sql} WHERE value != '';
+
+                    INSERT OR IGNORE INTO {audio_tags_table_name} (audio_id, tag_id, position)
+                        SELECT new.id, Tag.id, NewTag.key FROM {new_tags_sql} NewTag
+                        INNER JOIN {table_name} Tag ON Tag.name = NewTag.value;
+
+                    DELETE FROM {table_name}
+                        WHERE id NOT IN (SELECT tag_id FROM {audio_tags_table_name});
+                END;
+
+                CREATE TRIGGER IF NOT EXISTS {audio_tags_table_name}_delete
+                AFTER DELETE ON {audio_table_name} BEGIN
+                    DELETE FROM {audio_tags_table_name} WHERE audio_id = old.id;
+
+                    DELETE FROM {table_name}
+                        WHERE id NOT IN (SELECT tag_id FROM {audio_tags_table_name});
+                END;
+
+                INSERT OR IGNORE INTO {table_name} (name)
+                    SELECT AudioTag.value FROM {audio_table_name} Audio, {audio_tags_sql} AudioTag
+                    WHERE AudioTag.value != ''
+                        AND NOT EXISTS (SELECT 1 FROM {audio_tags_table_name});
+
+                INSERT OR IGNORE INTO {audio_tags_table_name} (audio_id, tag_id, position)
+                    SELECT Audio.id, Tag.id, AudioTag.key
+                    FROM {audio_table_name} Audio, {audio_tags_sql} AudioTag
+                    INNER JOIN {table_name} Tag ON Tag.name = AudioTag.value
+                    WHERE NOT EXISTS (SELECT 1 FROM {audio_tags_table_name});
+            COMMIT;
+        "
+        );
+
+        self.conn
+            .execute_batch(sql.as_str())
+            .log_err_msg("Failed create table")
+            .log_ok_msg(format!(
+                "Created tables {table_name}, {audio_tags_table_name}"
+            ))
+            .unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use r2d2_sqlite::SqliteConnectionManager;
+
+    use crate::{
+        audio::AudioFile,
+        db::{audio_table::AudioTableRowInsertBuilder, UniqueAudioTableCol},
+        helpers::uuid_v4_str,
+    };
+
+    use super::*;
+
+    fn insert_audio_row(table: &AudioTable, name: &str, tags: &str) {
+        let audio_file = AudioFile::new(format!("/tmp/{}.mp3", uuid_v4_str()).into());
+        table
+            .insert_audio_row(
+                AudioTableRowInsertBuilder::new(name, audio_file)
+                    .tags(tags)
+                    .build(),
+            )
+            .unwrap();
+    }
+
+    fn audio_tags(table: &AudioTable, name: &str) -> String {
+        table
+            .find_audio_row(UniqueAudioTableCol::Name(name.into()))
+            .unwrap()
+            .tags
+            .to_string()
+    }
+
+    #[test]
+    fn table_create_test() {
+        let db_manager = SqliteConnectionManager::memory();
+        let db_pool = r2d2::Pool::new(db_manager).unwrap();
+        AudioTable::new(db_pool.get().unwrap()).create_table();
+        let table = TagsTable::new(db_pool.get().unwrap());
+        table.create_table();
+        table.create_table();
+    }
+
+    #[test]
+    fn migrate_tags_column_test() {
+        let db_manager = SqliteConnectionManager::memory();
+        let db_pool = r2d2::Pool::new(db_manager).unwrap();
+        let audio_table = AudioTable::new(db_pool.get().unwrap());
+        let table = TagsTable::new(db_pool.get().unwrap());
+        audio_table.create_table();
+
+        insert_audio_row(&audio_table, "vader", "star-wars villain");
+        insert_audio_row(&audio_table, "yoda", "star-wars");
+        insert_audio_row(&audio_table, "jaws", "");
+
+        table.create_table();
+        assert_eq!(
+            table.list_tags(None).unwrap(),
+            vec![("star-wars".into(), 2), ("villain".into(), 1)]
+        );
+
+        // migration only runs once
+        table.create_table();
+        assert_eq!(table.list_tags(None).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn tags_sync_test() {
+        let db_manager = SqliteConnectionManager::memory();
+        let db_pool = r2d2::Pool::new(db_manager).unwrap();
+        let audio_table = AudioTable::new(db_pool.get().unwrap());
+        let table = TagsTable::new(db_pool.get().unwrap());
+        audio_table.create_table();
+        table.create_table();
+
+        insert_audio_row(&audio_table, "vader", "star-wars villain");
+        insert_audio_row(&audio_table, "yoda", "star-wars jedi");
+
+        let mut row = audio_table
+            .find_audio_row(UniqueAudioTableCol::Name("yoda".into()))
+            .unwrap();
+        row.tags = Tags::from("jedi master");
+        audio_table.update_audio_row(&row).unwrap();
+
+        assert_eq!(
+            table.list_tags(None).unwrap(),
+            vec![
+                ("jedi".into(), 1),
+                ("master".into(), 1),
+                ("star-wars".into(), 1),
+                ("villain".into(), 1)
+            ]
+        );
+        assert_eq!(table.autocomplete_tag_names("a", None).len(), 3);
+
+        // trashed sounds aren't counted
+        audio_table
+            .trash_audio_row(UniqueAudioTableCol::Name("vader".into()))
+            .unwrap();
+        assert_eq!(table.list_tags(None).unwrap().len(), 2);
+
+        // tags of deleted sounds are removed
+        audio_table
+            .connection()
+            .execute("DELETE FROM audio WHERE name = 'vader'", ())
+            .unwrap();
+        assert_eq!(
+            table.autocomplete_tag_names("", None),
+            vec!["jedi", "master"]
+        );
+    }
+
+    #[test]
+    fn rename_and_merge_tags_test() {
+        let db_manager = SqliteConnectionManager::memory();
+        let db_pool = r2d2::Pool::new(db_manager).unwrap();
+        let audio_table = AudioTable::new(db_pool.get().unwrap());
+        let table = TagsTable::new(db_pool.get().unwrap());
+        audio_table.create_table();
+        table.create_table();
+
+        insert_audio_row(&audio_table, "vader", "starwars villain");
+        insert_audio_row(&audio_table, "yoda", "star-wars jedi");
+        insert_audio_row(&audio_table, "luke", "jedi starwars");
+
+        let villain_id = table.find_tag_id("villain").unwrap().unwrap();
+        assert_eq!(table.rename_tag("villain", "sith").unwrap(), 1);
+        assert_eq!(audio_tags(&audio_table, "vader"), "starwars sith");
+        assert_eq!(
+            table.find_tag_name(villain_id).unwrap(),
+            Some("sith".into())
+        );
+        assert!(table.rename_tag("sith", "jedi").is_err());
+        assert!(table.rename_tag("missing", "tag").is_err());
+        assert!(table.rename_tag("sith", "!!").is_err());
+
+        assert_eq!(table.merge_tags("starwars", "star-wars").unwrap(), 3);
+        assert_eq!(audio_tags(&audio_table, "vader"), "star-wars sith");
+        assert_eq!(audio_tags(&audio_table, "yoda"), "star-wars jedi");
+        assert_eq!(audio_tags(&audio_table, "luke"), "jedi star-wars");
+        assert_eq!(
+            table.list_tags(None).unwrap(),
+            vec![
+                ("star-wars".into(), 3),
+                ("jedi".into(), 2),
+                ("sith".into(), 1)
+            ]
+        );
+
+        // search index is kept in sync
+        assert_eq!(
+            audio_table.fts_autocomplete_track_names("starwars", None),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            audio_table.fts_autocomplete_track_names("sith", None).len(),
+            1
+        );
+    }
+}
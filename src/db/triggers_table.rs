@@ -0,0 +1,403 @@
+use rusqlite::{params, types::FromSql, ToSql};
+
+use crate::{commands::PoiseError, common::LogResult};
+
+use super::{AudioTable, DbConnection, Table};
+
+/// How a text trigger's pattern matches messages
+#[derive(Debug, Clone, Copy, PartialEq, poise::ChoiceParameter)]
+pub enum TriggerKind {
+    /// Whole word, ignoring case
+    #[name = "Word"]
+    Word,
+    /// Text anywhere in the message, ignoring case
+    #[name = "Phrase"]
+    Phrase,
+    #[name = "Regex"]
+    Regex,
+}
+
+impl TriggerKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Word => "word",
+            Self::Phrase => "phrase",
+            Self::Regex => "regex",
+        }
+    }
+}
+
+impl TryFrom<&str> for TriggerKind {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "word" => Ok(Self::Word),
+            "phrase" => Ok(Self::Phrase),
+            "regex" => Ok(Self::Regex),
+            val => Err(format!("Unknown trigger kind: {val}")),
+        }
+    }
+}
+
+impl ToSql for TriggerKind {
+    fn to_sql(&self) -> rusqlite::Result<rusqlite::types::ToSqlOutput<'_>> {
+        self.as_str().to_sql()
+    }
+}
+
+impl FromSql for TriggerKind {
+    fn column_result(value: rusqlite::types::ValueRef<'_>) -> rusqlite::types::FromSqlResult<Self> {
+        TriggerKind::try_from(value.as_str()?)
+            .map_err(|err| rusqlite::types::FromSqlError::Other(err.into()))
+    }
+}
+
+/// Audio track played when a message in a text channel matches the pattern
+#[derive(Debug, Clone)]
+pub struct TriggerRow {
+    pub id: i64,
+    #[allow(dead_code)]
+    pub guild_id: u64,
+    /// Text channel whose messages are matched
+    pub channel_id: u64,
+    pub kind: TriggerKind,
+    pub pattern: String,
+    pub audio_id: i64,
+    pub cooldown_secs: u64,
+    pub last_triggered_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[allow(dead_code)]
+    pub author_id: Option<u64>,
+    #[allow(dead_code)]
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl TriggerRow {
+    /// True if trigger played less than its cooldown before `now`
+    pub fn is_on_cooldown(&self, now: chrono::DateTime<chrono::Utc>) -> bool {
+        match self.last_triggered_at {
+            Some(last_triggered_at) => {
+                now - last_triggered_at < chrono::Duration::seconds(self.cooldown_secs as i64)
+            }
+            None => false,
+        }
+    }
+}
+
+impl TryFrom<&rusqlite::Row<'_>> for TriggerRow {
+    type Error = rusqlite::Error;
+
+    fn try_from(row: &rusqlite::Row<'_>) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: row.get("id")?,
+            guild_id: row.get("guild_id")?,
+            channel_id: row.get("channel_id")?,
+            kind: row.get("kind")?,
+            pattern: row.get("pattern")?,
+            audio_id: row.get("audio_id")?,
+            cooldown_secs: row.get("cooldown_secs")?,
+            last_triggered_at: row.get("last_triggered_at")?,
+            author_id: row.get("author_id")?,
+            created_at: row.get("created_at")?,
+        })
+    }
+}
+
+/// Trigger to insert
+pub struct TriggerInsert {
+    pub guild_id: u64,
+    pub channel_id: u64,
+    pub kind: TriggerKind,
+    pub pattern: String,
+    pub audio_id: i64,
+    pub cooldown_secs: u64,
+    pub author_id: Option<u64>,
+}
+
+pub struct TriggersTable {
+    conn: DbConnection,
+}
+
+impl TriggersTable {
+    pub const TABLE_NAME: &'static str = "triggers";
+
+    pub fn new(connection: DbConnection) -> Self {
+        Self { conn: connection }
+    }
+
+    pub fn insert_trigger(&self, trigger: &TriggerInsert) -> Result<i64, PoiseError> {
+        log::info!(
+            "Inserting trigger. Guild id: {}, Channel id: {}, Pattern: {}",
+            trigger.guild_id,
+            trigger.channel_id,
+            trigger.pattern
+        );
+
+        let table_name = Self::TABLE_NAME;
+        let sql = format!(
+            "INSERT INTO {table_name}
+                (guild_id, channel_id, kind, pattern, audio_id, cooldown_secs, author_id, created_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)"
+        );
+
+        self.conn
+            .execute(
+                sql.as_str(),
+                params![
+                    &trigger.guild_id,
+                    &trigger.channel_id,
+                    &trigger.kind,
+                    &trigger.pattern,
+                    &trigger.audio_id,
+                    &trigger.cooldown_secs,
+                    &trigger.author_id,
+                    &chrono::Utc::now()
+                ],
+            )
+            .log_err_msg("Failed inserting trigger")?;
+
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// Deletes guild's trigger. Returns false if guild has no such trigger
+    pub fn delete_trigger(&self, guild_id: u64, trigger_id: i64) -> Result<bool, PoiseError> {
+        log::info!("Deleting trigger. Guild id: {guild_id}, Id: {trigger_id}");
+
+        let table_name = Self::TABLE_NAME;
+        let sql = format!("DELETE FROM {table_name} WHERE guild_id = ?1 AND id = ?2");
+
+        let count = self
+            .conn
+            .execute(sql.as_str(), params![&guild_id, &trigger_id])
+            .log_err_msg("Failed deleting trigger")?;
+
+        Ok(count > 0)
+    }
+
+    /// Guild's triggers & the names of their audio tracks, optionally of a single text channel
+    pub fn list_triggers(
+        &self,
+        guild_id: u64,
+        channel_id: Option<u64>,
+    ) -> Result<Vec<(TriggerRow, String)>, PoiseError> {
+        let table_name = Self::TABLE_NAME;
+        let audio_table_name = AudioTable::TABLE_NAME;
+        let sql = format!(
+            "SELECT Triggers.*, Audio.name AS audio_name FROM {table_name} Triggers
+            INNER JOIN {audio_table_name} Audio ON Audio.id = Triggers.audio_id
+            WHERE Triggers.guild_id = ?1 AND (?2 IS NULL OR Triggers.channel_id = ?2)
+            ORDER BY Triggers.id ASC"
+        );
+
+        let mut stmt = self
+            .conn
+            .prepare(sql.as_str())
+            .log_err_msg("Failed preparing list triggers")?;
+
+        let rows = stmt
+            .query_map(params![&guild_id, &channel_id], |row| {
+                Ok((TriggerRow::try_from(row)?, row.get("audio_name")?))
+            })
+            .log_err_msg("Failed listing triggers")?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(rows)
+    }
+
+    /// Triggers matching messages of text channel, whose audio tracks aren't in the trash
+    pub fn find_channel_triggers(
+        &self,
+        guild_id: u64,
+        channel_id: u64,
+    ) -> Result<Vec<TriggerRow>, PoiseError> {
+        let table_name = Self::TABLE_NAME;
+        let audio_table_name = AudioTable::TABLE_NAME;
+        let sql = format!(
+            "SELECT Triggers.* FROM {table_name} Triggers
+            INNER JOIN {audio_table_name} Audio ON Audio.id = Triggers.audio_id
+            WHERE Triggers.guild_id = ?1 AND Triggers.channel_id = ?2 AND Audio.deleted_at IS NULL
+            ORDER BY Triggers.id ASC"
+        );
+
+        let mut stmt = self
+            .conn
+            .prepare(sql.as_str())
+            .log_err_msg("Failed preparing find channel triggers")?;
+
+        let rows = stmt
+            .query_map(params![&guild_id, &channel_id], |row| {
+                TriggerRow::try_from(row)
+            })
+            .log_err_msg("Failed finding channel triggers")?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(rows)
+    }
+
+    /// Records when trigger played, to start its cooldown
+    pub fn update_last_triggered_at(
+        &self,
+        trigger_id: i64,
+        triggered_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<(), PoiseError> {
+        let table_name = Self::TABLE_NAME;
+        let sql = format!("UPDATE {table_name} SET last_triggered_at = ?2 WHERE id = ?1");
+
+        self.conn
+            .execute(sql.as_str(), params![&trigger_id, &triggered_at])
+            .log_err_msg("Failed updating trigger last triggered at")?;
+
+        Ok(())
+    }
+
+    /// Deletes triggers of audio tracks that no longer exist. ie: purged from the trash
+    pub fn delete_orphaned_triggers(conn: &rusqlite::Connection) -> Result<usize, PoiseError> {
+        let table_name = Self::TABLE_NAME;
+        let audio_table_name = AudioTable::TABLE_NAME;
+        let sql = format!(
+            "DELETE FROM {table_name} WHERE audio_id NOT IN (SELECT id FROM {audio_table_name})"
+        );
+
+        let count = conn
+            .execute(sql.as_str(), ())
+            .log_err_msg("Failed deleting orphaned triggers")?;
+
+        Ok(count)
+    }
+}
+
+impl Table for TriggersTable {
+    fn connection(&self) -> &DbConnection {
+        &self.conn
+    }
+
+    fn drop_table(&self) {
+        let table_name = Self::TABLE_NAME;
+        log::info!("Dropping table: {table_name}");
+        let sql = format!("DROP TABLE IF EXISTS {table_name};");
+
+        self.conn
+            .execute_batch(sql.as_str())
+            .log_err_msg("Failed dropping table")
+            .log_ok_msg(format!("Dropped table {table_name}"))
+            .unwrap();
+    }
+
+    fn create_table(&self) {
+        let table_name = Self::TABLE_NAME;
+        log::info!("Creating table: {table_name}");
+        let sql = format!(
+            "
+            CREATE TABLE IF NOT EXISTS {table_name} (
+                id INTEGER PRIMARY KEY,
+                guild_id INTEGER NOT NULL,
+                channel_id INTEGER NOT NULL,
+                kind VARCHAR(10) NOT NULL,
+                pattern VARCHAR(200) NOT NULL,
+                audio_id INTEGER NOT NULL,
+                cooldown_secs INTEGER NOT NULL,
+                last_triggered_at VARCHAR(25) DEFAULT NULL,
+                author_id INTEGER,
+                created_at VARCHAR(25) NOT NULL
+            );
+
+            CREATE INDEX IF NOT EXISTS {table_name}_channel_idx
+                ON {table_name}(guild_id, channel_id);
+        "
+        );
+
+        self.conn
+            .execute_batch(sql.as_str())
+            .log_err_msg("Failed create table")
+            .log_ok_msg(format!("Created table {table_name}"))
+            .unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use r2d2_sqlite::SqliteConnectionManager;
+
+    use crate::{
+        audio::AudioFile, db::audio_table::AudioTableRowInsertBuilder, helpers::uuid_v4_str,
+    };
+
+    use super::*;
+
+    fn trigger_insert(channel_id: u64, audio_id: i64) -> TriggerInsert {
+        TriggerInsert {
+            guild_id: 1,
+            channel_id,
+            kind: TriggerKind::Word,
+            pattern: "bruh".into(),
+            audio_id,
+            cooldown_secs: 30,
+            author_id: Some(10),
+        }
+    }
+
+    #[test]
+    fn table_create_test() {
+        let db_manager = SqliteConnectionManager::memory();
+        let db_pool = r2d2::Pool::new(db_manager).unwrap();
+        let table = TriggersTable::new(db_pool.get().unwrap());
+        table.create_table();
+        table.create_table();
+    }
+
+    #[test]
+    fn insert_find_delete_triggers_test() {
+        let db_manager = SqliteConnectionManager::memory();
+        let db_pool = r2d2::Pool::new(db_manager).unwrap();
+        let audio_table = AudioTable::new(db_pool.get().unwrap());
+        let table = TriggersTable::new(db_pool.get().unwrap());
+        audio_table.create_table();
+        table.create_table();
+
+        let audio_file = AudioFile::new(format!("/tmp/{}.mp3", uuid_v4_str()).into());
+        audio_table
+            .insert_audio_row(AudioTableRowInsertBuilder::new("bruh", audio_file).build())
+            .unwrap();
+
+        let first = table.insert_trigger(&trigger_insert(100, 1)).unwrap();
+        table
+            .insert_trigger(&TriggerInsert {
+                kind: TriggerKind::Regex,
+                pattern: "^l+o+l+$".into(),
+                ..trigger_insert(100, 1)
+            })
+            .unwrap();
+        table.insert_trigger(&trigger_insert(200, 1)).unwrap();
+
+        let triggers = table.find_channel_triggers(1, 100).unwrap();
+        assert_eq!(triggers.len(), 2);
+        assert_eq!(triggers[1].kind, TriggerKind::Regex);
+        assert_eq!(triggers[1].pattern, "^l+o+l+$");
+        assert!(table.find_channel_triggers(2, 100).unwrap().is_empty());
+
+        assert_eq!(table.list_triggers(1, None).unwrap().len(), 3);
+        let triggers = table.list_triggers(1, Some(200)).unwrap();
+        assert_eq!(triggers.len(), 1);
+        assert_eq!(triggers[0].1, "bruh");
+
+        let now = chrono::Utc::now();
+        table.update_last_triggered_at(first, now).unwrap();
+        let trigger = &table.find_channel_triggers(1, 100).unwrap()[0];
+        assert_eq!(trigger.last_triggered_at, Some(now));
+        assert!(trigger.is_on_cooldown(now + chrono::Duration::seconds(29)));
+        assert!(!trigger.is_on_cooldown(now + chrono::Duration::seconds(30)));
+
+        // can't delete other guilds' triggers
+        assert!(!table.delete_trigger(2, first).unwrap());
+        assert!(table.delete_trigger(1, first).unwrap());
+        assert_eq!(table.find_channel_triggers(1, 100).unwrap().len(), 1);
+
+        // triggers of purged tracks are deleted
+        table.insert_trigger(&trigger_insert(100, 99)).unwrap();
+        assert_eq!(
+            TriggersTable::delete_orphaned_triggers(&table.conn).unwrap(),
+            1
+        );
+        assert_eq!(table.list_triggers(1, None).unwrap().len(), 2);
+    }
+}
@@ -8,3 +8,27 @@ pub enum AudioError {
     #[error("Bot not in voice channel.")]
     NotInVoiceChannel,
 }
+
+#[derive(Error, Debug)]
+pub enum QuotaError {
+    #[error("Upload quota reached. You've already added {max} sounds.")]
+    SoundsPerUser { max: u64 },
+    #[error("Upload quota reached. The soundboard library is at its {max} byte limit.")]
+    LibrarySize { max: u64 },
+    #[error("Upload quota reached. You can only add {max} sounds per day.")]
+    UploadsPerDay { max: u64 },
+}
+
+#[derive(Error, Debug)]
+pub enum SearchQueryError {
+    #[error("Invalid tag `{value}`. ie: `tag:starwars`")]
+    Tag { value: String },
+    #[error("Invalid user `{value}`. ie: `by:@user`")]
+    Author { value: String },
+    #[error("Invalid duration `{value}`. ie: `duration:<3s`, `duration:>500ms`")]
+    Duration { value: String },
+    #[error("Invalid pinned value `{value}`. ie: `pinned:true`, `pinned:false`")]
+    Pinned { value: String },
+    #[error("Invalid play count `{value}`. ie: `played:>10`")]
+    PlayCount { value: String },
+}
@@ -1,15 +1,22 @@
 use serenity::all::{
-    Attachment, CacheHttp, ComponentInteraction, ComponentInteractionDataKind, Context,
+    Attachment, CacheHttp, ChannelId, ComponentInteraction, ComponentInteractionDataKind, Context,
     CreateActionRow, CreateButton, CreateInteractionResponse, CreateInteractionResponseMessage,
-    CreateMessage, CreateQuickModal, FullEvent, Interaction, Message, VoiceState,
+    CreateMessage, CreateQuickModal, FullEvent, GuildId, Interaction, Message, Reaction, UserId,
+    VoiceState,
 };
 
 use crate::{
     commands::PoiseResult,
     common::{LogResult, UserData},
-    db::{self, AudioTable, SettingsTable, Table, Tags},
-    helpers::{self, ButtonCustomId, DisplayMenuItemCustomId, PaginateId, SongbirdHelper},
-    FrameworkContext,
+    db::{
+        self, AudioTable, AuditLogTable, CollectionsTable, EmojiBindingsTable, FavoritesTable,
+        GuildSettingsTable, MemberSound, MemberSoundsTable, PanelsTable, PlayHistoryTable,
+        SchedulesTable, SettingsTable, Table, Tags, TagsTable, TriggersTable,
+    },
+    helpers::{
+        self, AuditLogPageId, ButtonCustomId, DisplayMenuItemCustomId, PaginateId, SongbirdHelper,
+    },
+    sound_info, stats, tasks, triggers, FrameworkContext,
 };
 
 pub async fn event_handler(
@@ -31,6 +38,9 @@ pub async fn event_handler(
         FullEvent::Message { new_message } => {
             handle_message(ctx, framework, data, new_message).await?
         }
+        FullEvent::ReactionAdd { add_reaction } => {
+            handle_reaction_add(ctx, data, add_reaction).await?
+        }
         _ => {}
     }
 
@@ -38,7 +48,7 @@ pub async fn event_handler(
 }
 
 pub async fn handle_ready(
-    _ctx: &Context,
+    ctx: &Context,
     ready: &serenity::model::gateway::Ready,
     _framework: FrameworkContext<'_>,
     data: &UserData,
@@ -60,16 +70,41 @@ pub async fn handle_ready(
 
     AudioTable::new(data.db_connection()).create_table();
     SettingsTable::new(data.db_connection()).create_table();
+    AuditLogTable::new(data.db_connection()).create_table();
+    GuildSettingsTable::new(data.db_connection()).create_table();
+    PlayHistoryTable::new(data.db_connection()).create_table();
+    FavoritesTable::new(data.db_connection()).create_table();
+    CollectionsTable::new(data.db_connection()).create_table();
+    TagsTable::new(data.db_connection()).create_table();
+    MemberSoundsTable::new(data.db_connection()).create_table();
+    SchedulesTable::new(data.db_connection()).create_table();
+    TriggersTable::new(data.db_connection()).create_table();
+    EmojiBindingsTable::new(data.db_connection()).create_table();
+    PanelsTable::new(data.db_connection()).create_table();
+
+    tasks::start_background_tasks(ctx, data, helpers::songbird_get(ctx).await);
+
+    // recovers panels whose messages were deleted while the bot was offline
+    helpers::refresh_sound_panels(&ctx.http, data).await;
 
     Ok(())
 }
 
 pub async fn handle_message(
-    _ctx: &Context,
+    ctx: &Context,
     _framework: FrameworkContext<'_>,
     data: &UserData,
     new_message: &Message,
 ) -> PoiseResult {
+    if !new_message.author.bot && data.config.enable_text_triggers {
+        if let Some(guild_id) = new_message.guild_id {
+            handle_text_triggers(ctx, data, guild_id, new_message)
+                .await
+                .log_err_msg("Failed handling text triggers")
+                .ok();
+        }
+    }
+
     // handle mp3 file
 
     if let Some(attachment) = new_message.attachments.first() {
@@ -82,7 +117,7 @@ pub async fn handle_message(
         {
             "audio/mpeg" | "audio/mpeg3" | "x-mpeg-3" => {
                 if (attachment.size as u64) < crate::audio::MAX_AUDIO_FILE_LENGTH_BYTES {
-                    handle_attached_mp3_message(_ctx, _framework, data, new_message, &attachment)
+                    handle_attached_mp3_message(ctx, _framework, data, new_message, attachment)
                         .await?
                 }
             }
@@ -93,6 +128,122 @@ pub async fn handle_message(
     Ok(())
 }
 
+/// Plays sound of the first text trigger of the message's channel it matches, if the bot is in a voice channel
+pub async fn handle_text_triggers(
+    ctx: &Context,
+    data: &UserData,
+    guild_id: GuildId,
+    new_message: &Message,
+) -> PoiseResult {
+    let triggers = data
+        .triggers_table()
+        .find_channel_triggers(guild_id.into(), new_message.channel_id.into())?;
+    if triggers.is_empty() {
+        return Ok(());
+    }
+
+    let now = chrono::Utc::now();
+    let Some(trigger) = triggers::find_match(
+        &data.trigger_regex_cache,
+        &triggers,
+        &new_message.content,
+        now,
+    ) else {
+        return Ok(());
+    };
+
+    let Some(voice_channel_id) = helpers::get_bot_voice_channel_id(ctx, guild_id).await else {
+        log::debug!(
+            "Text trigger {} matched, but bot isn't in a voice channel",
+            trigger.id
+        );
+        return Ok(());
+    };
+
+    let row = data
+        .audio_table()
+        .find_audio_row(db::UniqueAudioTableCol::Id(trigger.audio_id))
+        .ok_or(format!("Unable to locate audio track {}", trigger.audio_id))?;
+
+    log::info!("Text trigger {} playing: {}", trigger.id, row.name);
+    helpers::songbird_get(ctx)
+        .await
+        .play_audio(guild_id, voice_channel_id, &row.audio_file)
+        .await?;
+
+    data.triggers_table()
+        .update_last_triggered_at(trigger.id, now)?;
+    data.record_play(
+        db::PlayHistoryEntry::new(row.id, guild_id, db::PlaySource::Trigger)
+            .user_id(Some(new_message.author.id))
+            .channel_id(Some(voice_channel_id)),
+    )
+    .log_err()
+    .ok();
+
+    Ok(())
+}
+
+/// Plays audio track bound to the reaction's emoji, joining the reacting member's voice channel if need be
+pub async fn handle_reaction_add(
+    ctx: &Context,
+    data: &UserData,
+    reaction: &Reaction,
+) -> PoiseResult {
+    let (Some(guild_id), Some(user_id)) = (reaction.guild_id, reaction.user_id) else {
+        return Ok(());
+    };
+
+    let is_bot = reaction
+        .member
+        .as_ref()
+        .map(|member| member.user.bot)
+        .unwrap_or(user_id == ctx.cache.current_user().id);
+    if is_bot {
+        return Ok(());
+    }
+
+    let Some(audio_id) = data
+        .emoji_bindings_table()
+        .find_audio_id(guild_id.into(), &reaction.emoji)?
+    else {
+        return Ok(());
+    };
+
+    let Some(row) = data
+        .audio_table()
+        .find_audio_row(db::UniqueAudioTableCol::Id(audio_id))
+    else {
+        log::info!("Emoji {} is bound to a track in the trash", reaction.emoji);
+        return Ok(());
+    };
+
+    helpers::auto_join_user_voice_channel(ctx, data, guild_id, user_id).await?;
+    if !helpers::is_bot_in_voice_channel(ctx, guild_id).await {
+        log::debug!(
+            "Emoji {} reacted, but bot isn't in a voice channel",
+            reaction.emoji
+        );
+        return Ok(());
+    }
+
+    log::info!("Emoji {} reaction playing: {}", reaction.emoji, row.name);
+    helpers::songbird_get(ctx)
+        .await
+        .play_audio(guild_id, reaction.channel_id, &row.audio_file)
+        .await?;
+
+    data.record_play(
+        db::PlayHistoryEntry::new(row.id, guild_id, db::PlaySource::Reaction)
+            .user_id(Some(user_id))
+            .channel_id(helpers::get_bot_voice_channel_id(ctx, guild_id).await),
+    )
+    .log_err()
+    .ok();
+
+    Ok(())
+}
+
 pub async fn handle_attached_mp3_message(
     ctx: &Context,
     _framework: FrameworkContext<'_>,
@@ -133,7 +284,7 @@ pub async fn handle_voice_state_update(
     old: &Option<VoiceState>,
     new: &VoiceState,
     _framework: FrameworkContext<'_>,
-    _data: &UserData,
+    data: &UserData,
 ) -> PoiseResult {
     // Users with old.channel_id == None are joining a voice channel for the first time
     // Users with new.channel_id == None are leaving a voice channel
@@ -154,6 +305,169 @@ pub async fn handle_voice_state_update(
         }
         _ => {}
     }
+
+    let old_channel_id = old.as_ref().and_then(|old| old.channel_id);
+    if let (Some(guild_id), Some(channel_id)) = (new.guild_id, new.channel_id) {
+        let is_bot = new.member.as_ref().is_some_and(|member| member.user.bot);
+        if !is_bot && old_channel_id != new.channel_id {
+            auto_join_member_voice_channel(ctx, data, guild_id, channel_id)
+                .await
+                .log_err_msg("Failed auto joining voice channel")
+                .ok();
+        }
+    }
+
+    // members entering the bot's voice channel play their intro, and leaving it their outro
+    if let Some(guild_id) = new.guild_id {
+        if old_channel_id != new.channel_id {
+            if let Some(channel_id) = old_channel_id {
+                play_member_sound(
+                    ctx,
+                    data,
+                    MemberSound::Outro,
+                    guild_id,
+                    channel_id,
+                    new.user_id,
+                )
+                .await
+                .log_err_msg("Failed playing member outro")
+                .ok();
+            }
+
+            if let Some(channel_id) = new.channel_id {
+                play_member_sound(
+                    ctx,
+                    data,
+                    MemberSound::Intro,
+                    guild_id,
+                    channel_id,
+                    new.user_id,
+                )
+                .await
+                .log_err_msg("Failed playing member intro")
+                .ok();
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Joins voice channel the first member entered, if the guild has auto join enabled for it and
+/// the bot isn't in a voice channel of the guild yet
+async fn auto_join_member_voice_channel(
+    ctx: &Context,
+    data: &UserData,
+    guild_id: GuildId,
+    channel_id: ChannelId,
+) -> PoiseResult {
+    let settings = data
+        .guild_settings_table()
+        .get_guild_settings(guild_id.into())?;
+    if !settings.is_auto_join_channel(channel_id.into()) {
+        return Ok(());
+    }
+
+    if helpers::is_bot_in_voice_channel(ctx, guild_id).await {
+        return Ok(());
+    }
+
+    if helpers::count_voice_channel_listeners(ctx, guild_id, channel_id) != 1 {
+        return Ok(());
+    }
+
+    log::info!("Auto joining Guild ID: {guild_id}, Voice Channel ID: {channel_id}");
+    helpers::join_voice_channel(ctx, data, guild_id, channel_id, None).await
+}
+
+/// Plays intro or outro of member that entered or left the bot's voice channel, if they have one
+/// that isn't on cooldown and the guild has it enabled. Sounds are cut off at the guild's max
+/// intro duration
+async fn play_member_sound(
+    ctx: &Context,
+    data: &UserData,
+    sound: MemberSound,
+    guild_id: GuildId,
+    channel_id: ChannelId,
+    user_id: UserId,
+) -> PoiseResult {
+    if user_id == ctx.cache.current_user().id {
+        return Ok(());
+    }
+
+    if helpers::get_bot_voice_channel_id(ctx, guild_id).await != Some(channel_id) {
+        return Ok(());
+    }
+
+    // no one is left to hear an outro, and the bot is leaving
+    if sound == MemberSound::Outro && helpers::is_bot_alone_in_voice_channel(ctx, guild_id).await? {
+        return Ok(());
+    }
+
+    let settings = data
+        .guild_settings_table()
+        .get_guild_settings(guild_id.into())?;
+    if !settings.is_member_sound_enabled(sound) {
+        return Ok(());
+    }
+
+    let member_sounds_table = data.member_sounds_table();
+    let Some(member_sounds) =
+        member_sounds_table.find_member_sounds(guild_id.into(), user_id.into())?
+    else {
+        return Ok(());
+    };
+
+    let Some(audio_id) = member_sounds.audio_id(sound) else {
+        return Ok(());
+    };
+
+    let now = chrono::Utc::now();
+    if member_sounds.is_on_cooldown(sound, settings.intro_cooldown(), now) {
+        log::info!(
+            "Member {} on cooldown. Guild id: {guild_id}, User id: {user_id}",
+            sound.as_str()
+        );
+        return Ok(());
+    }
+
+    let row = data
+        .audio_table()
+        .find_audio_row(db::UniqueAudioTableCol::Id(audio_id))
+        .ok_or(format!(
+            "Unable to locate {} audio track id: {audio_id}",
+            sound.as_str()
+        ))?;
+
+    log::info!(
+        "Playing {}: {}. Guild id: {guild_id}, User id: {user_id}",
+        sound.as_str(),
+        row.name
+    );
+    let manager = helpers::songbird_get(ctx).await;
+    let track_handle = manager
+        .play_audio(guild_id, channel_id, &row.audio_file)
+        .await?;
+
+    let max_duration = settings.max_intro_duration();
+    tokio::spawn(async move {
+        tokio::time::sleep(max_duration).await;
+        // errors once the sound already ended on its own
+        track_handle.stop().ok();
+    });
+
+    let source = match sound {
+        MemberSound::Intro => db::PlaySource::Intro,
+        MemberSound::Outro => db::PlaySource::Outro,
+    };
+
+    member_sounds_table.update_last_played_at(sound, guild_id.into(), user_id.into(), now)?;
+    data.record_play(
+        db::PlayHistoryEntry::new(row.id, guild_id, source)
+            .user_id(Some(user_id))
+            .channel_id(Some(channel_id)),
+    )?;
+
     Ok(())
 }
 
@@ -222,6 +536,24 @@ pub async fn handle_btn_interaction(
             handle_play_audio_btn(ctx, interaction, component, framework, data, audio_track_id)
                 .await?;
         }
+        ButtonCustomId::PreviewAudio(audio_track_id) => {
+            handle_preview_audio_btn(ctx, interaction, component, framework, data, audio_track_id)
+                .await?;
+        }
+        ButtonCustomId::ToggleFavorite(audio_track_id) => {
+            handle_toggle_favorite_btn(
+                ctx,
+                interaction,
+                component,
+                framework,
+                data,
+                audio_track_id,
+            )
+            .await?;
+        }
+        ButtonCustomId::PlayCollection(name) => {
+            handle_play_collection_btn(ctx, interaction, component, framework, data, name).await?;
+        }
         ButtonCustomId::PlayRandom => {
             handle_play_random_btn(ctx, interaction, component, framework, data).await?;
         }
@@ -238,6 +570,13 @@ pub async fn handle_btn_interaction(
         ButtonCustomId::IgnoreMp3File => {
             handle_ignore_mp3_file_btn(ctx, interaction, component, framework, data).await?;
         }
+        ButtonCustomId::AuditLogPage(page_id) => {
+            handle_audit_log_page_btn(ctx, interaction, component, framework, data, page_id)
+                .await?;
+        }
+        ButtonCustomId::Stats(window) => {
+            handle_stats_btn(ctx, interaction, component, framework, data, window).await?;
+        }
         ButtonCustomId::Unknown(value) => {
             return Err(format!(
                 "Unrecognized button custom_id for component interaction. Value={value}"
@@ -266,6 +605,9 @@ pub async fn handle_string_select_interaction(
             handle_display_select_menu(ctx, interaction, component, framework, data, &values)
                 .await?;
         }
+        helpers::TAG_MENU_CUSTOM_ID => {
+            handle_tag_select_menu(ctx, interaction, component, framework, data, values).await?;
+        }
         val => {
             component
                 .create_response(&ctx.http, CreateInteractionResponse::Acknowledge)
@@ -309,6 +651,24 @@ pub async fn handle_display_select_menu(
             handle_display_recently_added_menu_select(ctx, interaction, component, framework, data)
                 .await?;
         }
+        DisplayMenuItemCustomId::DisplayTrending => {
+            handle_display_trending_menu_select(ctx, interaction, component, framework, data)
+                .await?;
+        }
+        DisplayMenuItemCustomId::DisplayFavorites => {
+            handle_display_favorites_menu_select(ctx, interaction, component, framework, data)
+                .await?;
+        }
+        DisplayMenuItemCustomId::DisplayRecentlyPlayed => {
+            handle_display_recently_played_menu_select(
+                ctx,
+                interaction,
+                component,
+                framework,
+                data,
+            )
+            .await?;
+        }
         DisplayMenuItemCustomId::Unknown(value) => {
             return Err(format!(
                 "Unrecognized button custom_id({value}) for component interaction."
@@ -321,6 +681,64 @@ pub async fn handle_display_select_menu(
     Ok(())
 }
 
+pub async fn handle_tag_select_menu(
+    ctx: &Context,
+    _interaction: &Interaction,
+    component: &ComponentInteraction,
+    _framework: FrameworkContext<'_>,
+    data: &UserData,
+    values: &Vec<String>,
+) -> PoiseResult {
+    log::info!("tag select menu values: {:?}", values);
+
+    let guild_id = component
+        .guild_id
+        .ok_or("ComponentInteraction.guild_id is None")
+        .log_err()?;
+
+    let tag = values
+        .first()
+        .ok_or("no tag")
+        .log_err_msg("handle tag select menu err")?;
+
+    let tag_id = data
+        .tags_table()
+        .find_tag_id(tag)?
+        .ok_or(format!("Unable to locate tag '{tag}'"))
+        .log_err()?;
+
+    let mut paginator = db::AudioTablePaginatorBuilder::tag_template(data.db_connection(), tag)
+        .page_limit(data.config.max_page_size)
+        .build();
+
+    let response_msg = helpers::make_display_message(
+        &mut paginator,
+        guild_id,
+        helpers::DisplayType::Tag(tag_id),
+        Some(tag.clone()),
+        data.config.enable_ephemeral_controls,
+    )
+    .log_err()?;
+
+    component
+        .create_response(
+            &ctx.http(),
+            CreateInteractionResponse::Message(response_msg.into()),
+        )
+        .await
+        .log_err()?;
+
+    component
+        .create_followup(
+            &ctx.http(),
+            helpers::make_sound_controls_message(data).into(),
+        )
+        .await
+        .log_err_msg("Failed sending soundbot controls")?;
+
+    Ok(())
+}
+
 pub async fn handle_add_mp3_file_btn(
     ctx: &Context,
     _interaction: &Interaction,
@@ -403,6 +821,28 @@ pub async fn handle_add_mp3_file_btn(
         return Err("Could not locate file attachment".into());
     };
 
+    // check quotas before user fills out form & audio download starts
+    let guild_id = component
+        .guild_id
+        .ok_or("ComponentInteraction.guild_id is None")
+        .log_err()?;
+
+    if let Err(err) = data.check_upload_quota(guild_id, component.user.id, attachment.size as u64) {
+        component
+            .create_response(
+                &ctx.http(),
+                CreateInteractionResponse::Message(
+                    CreateInteractionResponseMessage::new()
+                        .content(err.to_string())
+                        .ephemeral(true),
+                ),
+            )
+            .await
+            .log_err()?;
+
+        return Ok(());
+    }
+
     // have user fill out 'add sound' modal
     let response = component
         .quick_modal(
@@ -445,30 +885,75 @@ pub async fn handle_add_mp3_file_btn(
     let sound_name = &response.inputs[0];
     let sound_tags = Tags::from(response.inputs[1].clone());
 
+    let table = data.audio_table();
+    let col = db::UniqueAudioTableCol::Name(sound_name.clone());
+    let name_taken_msg = if table.find_trashed_audio_row(&col).is_some() {
+        Some("Can't add sound. It's in the trash, use `/sounds restore` instead")
+    } else if table.find_audio_row(&col).is_some() {
+        Some("Can't add sound. It already exists")
+    } else {
+        None
+    };
+
+    if let Some(msg) = name_taken_msg {
+        log::error!("{msg}. Name: {sound_name}");
+        response
+            .interaction
+            .create_response(
+                &ctx.http(),
+                CreateInteractionResponse::Message(
+                    CreateInteractionResponseMessage::new()
+                        .content(msg)
+                        .ephemeral(true),
+                ),
+            )
+            .await
+            .log_err()?;
+
+        return Ok(());
+    }
+
     let temp_audio_file = crate::audio::download_audio_url_temp(&attachment.url)
         .await
         .log_err()?;
 
-    crate::audio::AudioFileValidator::default()
+    let track_info = crate::audio::AudioFileValidator::default()
         .max_audio_duration(data.config.max_audio_file_duration)
         .reject_uuid_files(false)
         .validate(&temp_audio_file)
         .log_err()?;
 
+    let file_size = std::fs::metadata(&temp_audio_file)
+        .log_err_msg("Failed reading audio file size")?
+        .len();
+
     // add sound track to sounds dir & update audio_table
     let audio_file = data.move_file_to_audio_dir(&temp_audio_file).log_err()?;
-    let table = data.audio_table();
+    audio_file
+        .write_waveform()
+        .log_err_msg("Failed to write waveform")
+        .ok();
     table
         .insert_audio_row(
             db::audio_table::AudioTableRowInsertBuilder::new(sound_name.clone(), audio_file)
                 .author_global_name(component.user.global_name.clone())
                 .author_id(Some(component.user.id.into()))
                 .author_name(Some(component.user.name.clone()))
+                .guild_id(Some(guild_id.into()))
+                .duration(Some(track_info.duration))
+                .file_size(Some(file_size))
                 .tags(sound_tags)
                 .build(),
         )
         .log_err()?;
 
+    helpers::write_audit_log(
+        &ctx.http,
+        data,
+        db::AuditLogEntry::new(guild_id, &component.user, db::AuditAction::Add, sound_name),
+    )
+    .await;
+
     // update message to denote sound added
     response
         .interaction
@@ -482,6 +967,7 @@ pub async fn handle_add_mp3_file_btn(
         )
         .await
         .log_err()?;
+    helpers::refresh_sound_panels(&ctx.http, data).await;
 
     Ok(())
 }
@@ -510,7 +996,7 @@ pub async fn handle_ignore_mp3_file_btn(
     Ok(())
 }
 
-pub async fn handle_play_audio_btn(
+pub async fn handle_preview_audio_btn(
     ctx: &Context,
     _interaction: &Interaction,
     component: &ComponentInteraction,
@@ -518,37 +1004,150 @@ pub async fn handle_play_audio_btn(
     data: &UserData,
     audio_track_id: i64,
 ) -> PoiseResult {
-    log::info!("Play Audio Button Pressed - '{audio_track_id}'");
+    log::info!("Preview Audio Button Pressed - '{audio_track_id}'");
+
+    // trashed tracks can't be previewed
+    let audio_row = data
+        .audio_table()
+        .find_audio_row(db::UniqueAudioTableCol::Id(audio_track_id));
+
+    let message = match audio_row {
+        Some(audio_row) => sound_info::make_sound_preview_message(&audio_row)?,
+        None => CreateInteractionResponseMessage::new()
+            .content("That sound is no longer available")
+            .ephemeral(true),
+    };
 
     component
-        .create_response(&ctx.http, CreateInteractionResponse::Acknowledge)
+        .create_response(&ctx.http, CreateInteractionResponse::Message(message))
         .await
-        .log_err_msg("Failed to create response for btn interaction")
-        .ok();
-
-    let channel_id = component.channel_id;
-    let guild_id = component
-        .guild_id
-        .ok_or("ComponentInteraction.guild_id is None")
-        .log_err()?;
+        .log_err_msg("Failed to respond with audio preview")?;
 
-    let table = data.audio_table();
+    Ok(())
+}
 
-    match table.find_audio_row(db::UniqueAudioTableCol::Id(audio_track_id)) {
+pub async fn handle_toggle_favorite_btn(
+    ctx: &Context,
+    _interaction: &Interaction,
+    component: &ComponentInteraction,
+    _framework: FrameworkContext<'_>,
+    data: &UserData,
+    audio_track_id: i64,
+) -> PoiseResult {
+    log::info!("Toggle Favorite Button Pressed - '{audio_track_id}'");
+
+    let user_id = component.user.id.into();
+    let table = data.favorites_table();
+
+    let content = match data
+        .audio_table()
+        .find_audio_row(db::UniqueAudioTableCol::Id(audio_track_id))
+    {
+        Some(audio_row) if table.is_favorite(user_id, audio_row.id)? => {
+            table.remove_favorite(user_id, audio_row.id)?;
+            format!("Removed `{}` from your favorites", audio_row.name)
+        }
         Some(audio_row) => {
-            log::info!(
-                "Found audio track. Name: {}, File: {}",
-                audio_row.name,
-                audio_row.audio_file.to_string_lossy()
-            );
+            table.add_favorite(user_id, audio_row.id)?;
+            format!("Added `{}` to your favorites", audio_row.name)
+        }
+        None => "That sound is no longer available".into(),
+    };
 
-            let manager = helpers::songbird_get(&ctx).await;
+    component
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new()
+                    .content(content)
+                    .ephemeral(true),
+            ),
+        )
+        .await
+        .log_err_msg("Failed to respond to favorite toggle")?;
+
+    Ok(())
+}
+
+pub async fn handle_play_collection_btn(
+    ctx: &Context,
+    _interaction: &Interaction,
+    component: &ComponentInteraction,
+    _framework: FrameworkContext<'_>,
+    data: &UserData,
+    name: String,
+) -> PoiseResult {
+    log::info!("Play Collection Button Pressed - '{name}'");
+
+    let guild_id = component
+        .guild_id
+        .ok_or("ComponentInteraction.guild_id is None")
+        .log_err()?;
+
+    let collection = data
+        .collections_table()
+        .find_collection(guild_id.into(), &name)
+        .ok_or(format!("Unable to locate collection '{name}'"))
+        .log_err()?;
+
+    component
+        .create_response(&ctx.http, CreateInteractionResponse::Acknowledge)
+        .await
+        .log_err_msg("Failed to create response for btn interaction")
+        .ok();
+
+    helpers::play_collection(ctx, data, guild_id, &collection, component.user.id)
+        .await
+        .log_err()?;
+
+    Ok(())
+}
+
+pub async fn handle_play_audio_btn(
+    ctx: &Context,
+    _interaction: &Interaction,
+    component: &ComponentInteraction,
+    _framework: FrameworkContext<'_>,
+    data: &UserData,
+    audio_track_id: i64,
+) -> PoiseResult {
+    log::info!("Play Audio Button Pressed - '{audio_track_id}'");
+
+    component
+        .create_response(&ctx.http, CreateInteractionResponse::Acknowledge)
+        .await
+        .log_err_msg("Failed to create response for btn interaction")
+        .ok();
+
+    let channel_id = component.channel_id;
+    let guild_id = component
+        .guild_id
+        .ok_or("ComponentInteraction.guild_id is None")
+        .log_err()?;
+
+    let table = data.audio_table();
+
+    match table.find_audio_row(db::UniqueAudioTableCol::Id(audio_track_id)) {
+        Some(audio_row) => {
+            log::info!(
+                "Found audio track. Name: {}, File: {}",
+                audio_row.name,
+                audio_row.audio_file.to_string_lossy()
+            );
+
+            helpers::auto_join_user_voice_channel(ctx, data, guild_id, component.user.id).await?;
+
+            let manager = helpers::songbird_get(&ctx).await;
             manager
                 .play_audio(guild_id, channel_id, &audio_row.audio_file)
                 .await
                 .ok();
 
-            table.increment_play_count(audio_row.id)?;
+            data.record_play(
+                db::PlayHistoryEntry::new(audio_row.id, guild_id, db::PlaySource::Button)
+                    .user_id(Some(component.user.id))
+                    .channel_id(helpers::get_bot_voice_channel_id(ctx, guild_id).await),
+            )?;
         }
         None => {
             return Err(format!("Unable to locate audio track for button custom id").into())
@@ -567,6 +1166,10 @@ pub async fn handle_paginate_btn(
     button_id: PaginateId,
 ) -> PoiseResult {
     log::info!("paginate {button_id:?}");
+    let guild_id = component
+        .guild_id
+        .ok_or("ComponentInteraction.guild_id is None")
+        .log_err()?;
     let conn = data.db_connection();
 
     let response_msg = match button_id {
@@ -581,6 +1184,7 @@ pub async fn handle_paginate_btn(
 
             helpers::make_display_message(
                 &mut paginator,
+                guild_id,
                 helpers::DisplayType::All,
                 None,
                 data.config.enable_ephemeral_controls,
@@ -598,6 +1202,7 @@ pub async fn handle_paginate_btn(
 
             helpers::make_display_message(
                 &mut paginator,
+                guild_id,
                 helpers::DisplayType::MostPlayed,
                 None,
                 data.config.enable_ephemeral_controls,
@@ -615,23 +1220,151 @@ pub async fn handle_paginate_btn(
 
             helpers::make_display_message(
                 &mut paginator,
+                guild_id,
                 helpers::DisplayType::RecentlyAdded,
                 None,
                 data.config.enable_ephemeral_controls,
             )
             .log_err()?
         }
+        PaginateId::TrendingFirstPage(offset)
+        | PaginateId::TrendingLastPage(offset)
+        | PaginateId::TrendingNextPage(offset)
+        | PaginateId::TrendingPrevPage(offset) => {
+            let mut paginator = db::AudioTablePaginatorBuilder::trending_template(conn)
+                .page_limit(data.config.max_page_size)
+                .offset(offset)
+                .build();
+
+            helpers::make_display_message(
+                &mut paginator,
+                guild_id,
+                helpers::DisplayType::Trending,
+                None,
+                data.config.enable_ephemeral_controls,
+            )
+            .log_err()?
+        }
+        PaginateId::RecentlyPlayedFirstPage(offset)
+        | PaginateId::RecentlyPlayedLastPage(offset)
+        | PaginateId::RecentlyPlayedNextPage(offset)
+        | PaginateId::RecentlyPlayedPrevPage(offset) => {
+            let mut paginator =
+                db::AudioTablePaginatorBuilder::recently_played_template(conn, guild_id.into())
+                    .page_limit(data.config.max_page_size)
+                    .offset(offset)
+                    .build();
+
+            helpers::make_display_message(
+                &mut paginator,
+                guild_id,
+                helpers::DisplayType::RecentlyPlayed,
+                None,
+                data.config.enable_ephemeral_controls,
+            )
+            .log_err()?
+        }
+        PaginateId::FavoritesFirstPage(offset)
+        | PaginateId::FavoritesLastPage(offset)
+        | PaginateId::FavoritesNextPage(offset)
+        | PaginateId::FavoritesPrevPage(offset) => {
+            // favorites are scoped to whoever clicks, not whoever opened the grid
+            let mut paginator =
+                db::AudioTablePaginatorBuilder::favorites_template(conn, component.user.id.into())
+                    .page_limit(data.config.max_page_size)
+                    .offset(offset)
+                    .build();
+
+            // favorites are private, so never show them to the rest of the channel
+            helpers::make_display_message(
+                &mut paginator,
+                guild_id,
+                helpers::DisplayType::Favorites,
+                None,
+                true,
+            )
+            .log_err()?
+        }
+        PaginateId::CollectionFirstPage(offset, ref name)
+        | PaginateId::CollectionLastPage(offset, ref name)
+        | PaginateId::CollectionNextPage(offset, ref name)
+        | PaginateId::CollectionPrevPage(offset, ref name) => {
+            let collection = data
+                .collections_table()
+                .find_collection(guild_id.into(), name)
+                .ok_or(format!("Unable to locate collection '{name}'"))
+                .log_err()?;
+
+            let mut paginator =
+                db::AudioTablePaginatorBuilder::collection_template(conn, collection.id)
+                    .page_limit(data.config.max_page_size)
+                    .offset(offset)
+                    .build();
+
+            helpers::make_display_message(
+                &mut paginator,
+                guild_id,
+                helpers::DisplayType::Collection,
+                Some(collection.name),
+                data.config.enable_ephemeral_controls,
+            )
+            .log_err()?
+        }
+        PaginateId::TagFirstPage(offset, tag_id)
+        | PaginateId::TagLastPage(offset, tag_id)
+        | PaginateId::TagNextPage(offset, tag_id)
+        | PaginateId::TagPrevPage(offset, tag_id) => {
+            let tag = data
+                .tags_table()
+                .find_tag_name(tag_id)?
+                .ok_or(format!("Unable to locate tag {tag_id}"))
+                .log_err()?;
+
+            let mut paginator = db::AudioTablePaginatorBuilder::tag_template(conn, &tag)
+                .page_limit(data.config.max_page_size)
+                .offset(offset)
+                .build();
+
+            helpers::make_display_message(
+                &mut paginator,
+                guild_id,
+                helpers::DisplayType::Tag(tag_id),
+                Some(tag),
+                data.config.enable_ephemeral_controls,
+            )
+            .log_err()?
+        }
         PaginateId::SearchFirstPage(offset, ref search)
         | PaginateId::SearchLastPage(offset, ref search)
         | PaginateId::SearchNextPage(offset, ref search)
         | PaginateId::SearchPrevPage(offset, ref search) => {
-            let mut paginator = db::AudioTablePaginatorBuilder::search_template(conn, search)
+            let paginator = match db::AudioTablePaginatorBuilder::search_template(conn, search) {
+                Ok(paginator) => paginator,
+                Err(err) => {
+                    component
+                        .create_response(
+                            &ctx.http(),
+                            CreateInteractionResponse::Message(
+                                CreateInteractionResponseMessage::new()
+                                    .content(err.to_string())
+                                    .ephemeral(true),
+                            ),
+                        )
+                        .await
+                        .log_err()?;
+
+                    return Ok(());
+                }
+            };
+
+            let mut paginator = paginator
                 .page_limit(data.config.max_page_size)
                 .offset(offset)
                 .build();
 
             helpers::make_display_message(
                 &mut paginator,
+                guild_id,
                 helpers::DisplayType::Search,
                 Some(search.clone()),
                 data.config.enable_ephemeral_controls,
@@ -649,6 +1382,7 @@ pub async fn handle_paginate_btn(
 
             helpers::make_display_message(
                 &mut paginator,
+                guild_id,
                 helpers::DisplayType::Pinned,
                 None,
                 data.config.enable_ephemeral_controls,
@@ -682,12 +1416,19 @@ pub async fn handle_display_all_menu_select(
     data: &UserData,
 ) -> PoiseResult {
     log::info!("Displaying all sounds buttons as ActionRows grid...");
+
+    let guild_id = component
+        .guild_id
+        .ok_or("ComponentInteraction.guild_id is None")
+        .log_err()?;
+
     let mut paginator = db::AudioTablePaginatorBuilder::all_template(data.db_connection())
         .page_limit(data.config.max_page_size)
         .build();
 
     let response_msg = helpers::make_display_message(
         &mut paginator,
+        guild_id,
         helpers::DisplayType::All,
         None,
         data.config.enable_ephemeral_controls,
@@ -705,7 +1446,7 @@ pub async fn handle_display_all_menu_select(
     component
         .create_followup(
             &ctx.http(),
-            helpers::make_sound_controls_message(data.config.enable_ephemeral_controls).into(),
+            helpers::make_sound_controls_message(data).into(),
         )
         .await
         .log_err_msg("Failed sending soundbot controls")?;
@@ -722,12 +1463,18 @@ pub async fn handle_display_pinned_menu_select(
 ) -> PoiseResult {
     log::info!("Displaying pinned sounds buttons as ActionRows grid...");
 
+    let guild_id = component
+        .guild_id
+        .ok_or("ComponentInteraction.guild_id is None")
+        .log_err()?;
+
     let mut paginator = db::AudioTablePaginatorBuilder::pinned_template(data.db_connection())
         .page_limit(data.config.max_page_size)
         .build();
 
     let response_msg = helpers::make_display_message(
         &mut paginator,
+        guild_id,
         helpers::DisplayType::Pinned,
         None,
         data.config.enable_ephemeral_controls,
@@ -745,7 +1492,7 @@ pub async fn handle_display_pinned_menu_select(
     component
         .create_followup(
             &ctx.http(),
-            helpers::make_sound_controls_message(data.config.enable_ephemeral_controls).into(),
+            helpers::make_sound_controls_message(data).into(),
         )
         .await
         .log_err_msg("Failed sending soundbot controls")?;
@@ -762,6 +1509,11 @@ pub async fn handle_display_recently_added_menu_select(
 ) -> PoiseResult {
     log::info!("Displaying recently added sounds buttons as ActionRows grid...");
 
+    let guild_id = component
+        .guild_id
+        .ok_or("ComponentInteraction.guild_id is None")
+        .log_err()?;
+
     let mut paginator =
         db::AudioTablePaginatorBuilder::most_recently_added_template(data.db_connection())
             .page_limit(data.config.max_page_size)
@@ -769,6 +1521,7 @@ pub async fn handle_display_recently_added_menu_select(
 
     let response_msg = helpers::make_display_message(
         &mut paginator,
+        guild_id,
         helpers::DisplayType::RecentlyAdded,
         None,
         data.config.enable_ephemeral_controls,
@@ -786,7 +1539,152 @@ pub async fn handle_display_recently_added_menu_select(
     component
         .create_followup(
             &ctx.http(),
-            helpers::make_sound_controls_message(data.config.enable_ephemeral_controls).into(),
+            helpers::make_sound_controls_message(data).into(),
+        )
+        .await
+        .log_err_msg("Failed sending soundbot controls")?;
+
+    Ok(())
+}
+
+pub async fn handle_display_recently_played_menu_select(
+    ctx: &Context,
+    _interaction: &Interaction,
+    component: &ComponentInteraction,
+    _framework: FrameworkContext<'_>,
+    data: &UserData,
+) -> PoiseResult {
+    log::info!("Displaying recently played sounds buttons as ActionRows grid...");
+
+    let guild_id = component
+        .guild_id
+        .ok_or("ComponentInteraction.guild_id is None")
+        .log_err()?;
+
+    let mut paginator = db::AudioTablePaginatorBuilder::recently_played_template(
+        data.db_connection(),
+        guild_id.into(),
+    )
+    .page_limit(data.config.max_page_size)
+    .build();
+
+    let response_msg = helpers::make_display_message(
+        &mut paginator,
+        guild_id,
+        helpers::DisplayType::RecentlyPlayed,
+        None,
+        data.config.enable_ephemeral_controls,
+    )
+    .log_err()?;
+
+    component
+        .create_response(
+            &ctx.http(),
+            CreateInteractionResponse::Message(response_msg.into()),
+        )
+        .await
+        .log_err()?;
+
+    component
+        .create_followup(
+            &ctx.http(),
+            helpers::make_sound_controls_message(data).into(),
+        )
+        .await
+        .log_err_msg("Failed sending soundbot controls")?;
+
+    Ok(())
+}
+
+pub async fn handle_display_favorites_menu_select(
+    ctx: &Context,
+    _interaction: &Interaction,
+    component: &ComponentInteraction,
+    _framework: FrameworkContext<'_>,
+    data: &UserData,
+) -> PoiseResult {
+    log::info!("Displaying favorite sounds buttons as ActionRows grid...");
+
+    let guild_id = component
+        .guild_id
+        .ok_or("ComponentInteraction.guild_id is None")
+        .log_err()?;
+
+    let mut paginator = db::AudioTablePaginatorBuilder::favorites_template(
+        data.db_connection(),
+        component.user.id.into(),
+    )
+    .page_limit(data.config.max_page_size)
+    .build();
+
+    // favorites are private, so never show them to the rest of the channel
+    let response_msg = helpers::make_display_message(
+        &mut paginator,
+        guild_id,
+        helpers::DisplayType::Favorites,
+        None,
+        true,
+    )
+    .log_err()?;
+
+    component
+        .create_response(
+            &ctx.http(),
+            CreateInteractionResponse::Message(response_msg.into()),
+        )
+        .await
+        .log_err()?;
+
+    component
+        .create_followup(
+            &ctx.http(),
+            helpers::make_sound_controls_message(data).into(),
+        )
+        .await
+        .log_err_msg("Failed sending soundbot controls")?;
+
+    Ok(())
+}
+
+pub async fn handle_display_trending_menu_select(
+    ctx: &Context,
+    _interaction: &Interaction,
+    component: &ComponentInteraction,
+    _framework: FrameworkContext<'_>,
+    data: &UserData,
+) -> PoiseResult {
+    log::info!("Displaying trending sounds buttons as ActionRows grid...");
+
+    let guild_id = component
+        .guild_id
+        .ok_or("ComponentInteraction.guild_id is None")
+        .log_err()?;
+
+    let mut paginator = db::AudioTablePaginatorBuilder::trending_template(data.db_connection())
+        .page_limit(data.config.max_page_size)
+        .build();
+
+    let response_msg = helpers::make_display_message(
+        &mut paginator,
+        guild_id,
+        helpers::DisplayType::Trending,
+        None,
+        data.config.enable_ephemeral_controls,
+    )
+    .log_err()?;
+
+    component
+        .create_response(
+            &ctx.http(),
+            CreateInteractionResponse::Message(response_msg.into()),
+        )
+        .await
+        .log_err()?;
+
+    component
+        .create_followup(
+            &ctx.http(),
+            helpers::make_sound_controls_message(data).into(),
         )
         .await
         .log_err_msg("Failed sending soundbot controls")?;
@@ -803,12 +1701,18 @@ pub async fn handle_display_most_played_menu_select(
 ) -> PoiseResult {
     log::info!("Displaying most played sounds buttons as ActionRows grid...");
 
+    let guild_id = component
+        .guild_id
+        .ok_or("ComponentInteraction.guild_id is None")
+        .log_err()?;
+
     let mut paginator = db::AudioTablePaginatorBuilder::most_played_template(data.db_connection())
         .page_limit(data.config.max_page_size)
         .build();
 
     let response_msg = helpers::make_display_message(
         &mut paginator,
+        guild_id,
         helpers::DisplayType::MostPlayed,
         None,
         data.config.enable_ephemeral_controls,
@@ -826,7 +1730,7 @@ pub async fn handle_display_most_played_menu_select(
     component
         .create_followup(
             &ctx.http(),
-            helpers::make_sound_controls_message(data.config.enable_ephemeral_controls).into(),
+            helpers::make_sound_controls_message(data).into(),
         )
         .await
         .log_err_msg("Failed sending soundbot controls")?;
@@ -866,11 +1770,19 @@ pub async fn handle_play_random_btn(
                 )
                 .await?;
 
+            helpers::auto_join_user_voice_channel(ctx, data, guild_id, component.user.id).await?;
+
             let manager = helpers::songbird_get(&ctx).await;
             manager
                 .play_audio(guild_id, channel_id, &audio_row.audio_file)
                 .await
                 .ok();
+
+            data.record_play(
+                db::PlayHistoryEntry::new(audio_row.id, guild_id, db::PlaySource::Random)
+                    .user_id(Some(component.user.id))
+                    .channel_id(helpers::get_bot_voice_channel_id(ctx, guild_id).await),
+            )?;
         }
         None => {
             component
@@ -897,6 +1809,12 @@ pub async fn handle_search_btn(
     data: &UserData,
 ) -> PoiseResult {
     log::info!("Search button Pressed, creating search modal");
+
+    let guild_id = component
+        .guild_id
+        .ok_or("ComponentInteraction.guild_id is None")
+        .log_err()?;
+
     let response = component
         .quick_modal(
             &ctx,
@@ -919,13 +1837,32 @@ pub async fn handle_search_btn(
         let search = &inputs[0];
         let search = search.trim();
 
-        let mut paginator =
-            db::AudioTablePaginatorBuilder::search_template(data.db_connection(), search)
-                .page_limit(data.config.max_page_size)
-                .build();
+        let paginator =
+            match db::AudioTablePaginatorBuilder::search_template(data.db_connection(), search) {
+                Ok(paginator) => paginator,
+                Err(err) => {
+                    response
+                        .interaction
+                        .create_response(
+                            &ctx.http(),
+                            CreateInteractionResponse::Message(
+                                CreateInteractionResponseMessage::new()
+                                    .content(err.to_string())
+                                    .ephemeral(true),
+                            ),
+                        )
+                        .await
+                        .log_err()?;
+
+                    return Ok(());
+                }
+            };
+
+        let mut paginator = paginator.page_limit(data.config.max_page_size).build();
 
         let response_msg = helpers::make_display_message(
             &mut paginator,
+            guild_id,
             helpers::DisplayType::Search,
             Some(search.into()),
             data.config.enable_ephemeral_controls,
@@ -944,7 +1881,7 @@ pub async fn handle_search_btn(
         component
             .create_followup(
                 &ctx.http(),
-                helpers::make_sound_controls_message(data.config.enable_ephemeral_controls).into(),
+                helpers::make_sound_controls_message(data).into(),
             )
             .await
             .log_err_msg("Failed sending soundbot controls")?;
@@ -955,3 +1892,68 @@ pub async fn handle_search_btn(
 
     Ok(())
 }
+
+pub async fn handle_audit_log_page_btn(
+    ctx: &Context,
+    _interaction: &Interaction,
+    component: &ComponentInteraction,
+    _framework: FrameworkContext<'_>,
+    data: &UserData,
+    page_id: AuditLogPageId,
+) -> PoiseResult {
+    log::info!("audit log page {page_id:?}");
+
+    let guild_id = component
+        .guild_id
+        .ok_or("ComponentInteraction.guild_id is None")
+        .log_err()?;
+
+    let filter = db::AuditLogFilter {
+        guild_id: guild_id.into(),
+        action: page_id.action,
+        actor_id: page_id.actor_id,
+    };
+
+    let response_msg = helpers::make_audit_log_message(data, &filter, page_id.offset).log_err()?;
+
+    component
+        .create_response(
+            &ctx.http(),
+            CreateInteractionResponse::UpdateMessage(response_msg.into()),
+        )
+        .await
+        .log_err()?;
+
+    Ok(())
+}
+
+pub async fn handle_stats_btn(
+    ctx: &Context,
+    _interaction: &Interaction,
+    component: &ComponentInteraction,
+    _framework: FrameworkContext<'_>,
+    data: &UserData,
+    window: stats::StatsWindow,
+) -> PoiseResult {
+    log::info!("stats window {window:?}");
+
+    let guild_id = component
+        .guild_id
+        .ok_or("ComponentInteraction.guild_id is None")
+        .log_err()?;
+
+    let guild_stats =
+        stats::GuildStats::fetch(data, guild_id.into(), window, chrono::Utc::now()).log_err()?;
+
+    component
+        .create_response(
+            &ctx.http(),
+            CreateInteractionResponse::UpdateMessage(
+                stats::make_stats_message(&guild_stats).log_err()?.into(),
+            ),
+        )
+        .await
+        .log_err()?;
+
+    Ok(())
+}
@@ -0,0 +1,81 @@
+use crate::vars;
+
+/// Similarity of `query` to `text`, from 0 (nothing alike) to 1 (same, ignoring case).
+/// Text containing the query scores highly, otherwise the best Jaro-Winkler similarity of the
+/// query to the whole text or any of its words is used. ie: `vadr` is similar to `darth vader`
+pub fn similarity(query: impl AsRef<str>, text: impl AsRef<str>) -> f64 {
+    let query = query.as_ref().trim().to_lowercase();
+    let text = text.as_ref().trim().to_lowercase();
+
+    if query.is_empty() || text.is_empty() {
+        return 0.0;
+    }
+
+    if query == text {
+        return 1.0;
+    }
+
+    if text.starts_with(&query) {
+        return 0.98;
+    }
+
+    if text.contains(&query) {
+        return 0.95;
+    }
+
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| strsim::jaro_winkler(&query, word))
+        .fold(strsim::jaro_winkler(&query, &text), f64::max)
+        // the text contains the query, and is scored above, so keep a near miss below that
+        .min(0.94)
+}
+
+/// Similarity of `query` to an audio track, by its name or tags. Tags weigh less than names
+pub fn track_similarity(query: impl AsRef<str>, name: impl AsRef<str>, tags: &[String]) -> f64 {
+    let query = query.as_ref();
+
+    tags.iter()
+        .map(|tag| similarity(query, tag) * vars::FUZZY_TAG_WEIGHT)
+        .fold(similarity(query, name), f64::max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn similarity_test() {
+        assert_eq!(similarity("Yoda", "yoda"), 1.0);
+        assert_eq!(similarity("", "yoda"), 0.0);
+        assert_eq!(similarity("yo", "yoda"), 0.98);
+        assert_eq!(similarity("vader", "darth vader"), 0.95);
+
+        // misspellings
+        assert!(similarity("vadr", "darth vader") > 0.85);
+        assert!(similarity("airhonr", "airhorn") > 0.9);
+        assert!(similarity("darht vader", "darth vader") > 0.9);
+
+        // near misses rank below matches
+        assert!(similarity("airhonr", "airhorn") < similarity("airh", "airhorn"));
+
+        // unrelated
+        assert!(similarity("jaws", "darth vader") < vars::FUZZY_MIN_SCORE);
+        assert!(similarity("xyz", "bruh") < vars::FUZZY_MIN_SCORE);
+    }
+
+    #[test]
+    fn track_similarity_test() {
+        let tags = vec!["star-wars".to_string(), "sith".to_string()];
+
+        assert_eq!(track_similarity("vader", "vader", &tags), 1.0);
+        assert_eq!(
+            track_similarity("sith", "vader", &tags),
+            vars::FUZZY_TAG_WEIGHT
+        );
+        assert!(
+            track_similarity("stih", "vader", &tags) < track_similarity("sith", "vader", &tags)
+        );
+        assert!(track_similarity("jaws", "vader", &tags) < vars::FUZZY_MIN_SCORE);
+    }
+}
@@ -2,22 +2,24 @@ use core::fmt;
 use std::num::ParseIntError;
 use std::sync::Arc;
 
-use poise::CreateReply;
+use poise::{ChoiceParameter, CreateReply};
 use serenity::all::{
-    ChannelId, CreateActionRow, CreateButton, CreateInteractionResponseFollowup,
-    CreateInteractionResponseMessage, CreateMessage, CreateSelectMenuOption, GuildId, ReactionType,
+    ChannelId, CreateActionRow, CreateAllowedMentions, CreateButton,
+    CreateInteractionResponseFollowup, CreateInteractionResponseMessage, CreateMessage,
+    CreateSelectMenuOption, EditMessage, GuildId, Http, Message, MessageId, ReactionType, UserId,
 };
 use serenity::async_trait;
 use serenity::client::Context;
 use songbird::tracks::TrackHandle;
-use songbird::{Songbird, SongbirdKey};
+use songbird::{Songbird, SongbirdKey, TrackEvent};
 
 use crate::audio::TrackHandleHelper;
-use crate::commands::{PoiseContext, PoiseError, PoiseResult};
-use crate::common::LogResult;
+use crate::commands::{PoiseContext, PoiseError, PoiseResult, TrackErrorNotifier};
+use crate::common::{LogResult, UserData};
 use crate::db::paginators::PaginateInfo;
-use crate::db::AudioTableRow;
+use crate::db::{AudioTableRow, AuditAction, AuditLogEntry, AuditLogFilter, PanelRow};
 use crate::errors::AudioError;
+use crate::stats::StatsWindow;
 use crate::vars;
 use crate::{audio, db};
 
@@ -57,6 +59,30 @@ pub async fn is_bot_alone_in_voice_channel(
     Ok(false)
 }
 
+/// Number of non-bot members in voice channel, per the cache
+pub fn count_voice_channel_listeners(
+    ctx: &Context,
+    guild_id: GuildId,
+    channel_id: ChannelId,
+) -> usize {
+    let Some(guild) = ctx.cache.guild(guild_id) else {
+        return 0;
+    };
+
+    guild
+        .voice_states
+        .values()
+        .filter(|voice_state| voice_state.channel_id == Some(channel_id))
+        .filter(|voice_state| {
+            voice_state
+                .member
+                .as_ref()
+                .or_else(|| guild.members.get(&voice_state.user_id))
+                .is_some_and(|member| !member.user.bot)
+        })
+        .count()
+}
+
 pub async fn get_bot_voice_channel_id(ctx: &Context, guild_id: GuildId) -> Option<ChannelId> {
     let user = ctx.cache.current_user();
     let bot_id = user.id;
@@ -76,6 +102,9 @@ pub enum DisplayMenuItemCustomId {
     DisplayPinned,
     DisplayMostPlayed,
     DisplayRecentlyAdded,
+    DisplayRecentlyPlayed,
+    DisplayTrending,
+    DisplayFavorites,
     Unknown(String),
 }
 
@@ -83,6 +112,8 @@ impl DisplayMenuItemCustomId {
     pub const CUSTOM_ID: &'static str = "sound_bot_display_menu";
 }
 
+pub const TAG_MENU_CUSTOM_ID: &str = "sound_bot_tag_menu";
+
 impl From<&String> for DisplayMenuItemCustomId {
     fn from(value: &String) -> Self {
         match value.as_str() {
@@ -90,6 +121,9 @@ impl From<&String> for DisplayMenuItemCustomId {
             "sound_bot_display_menu_item_all" => Self::DisplayAll,
             "sound_bot_display_menu_item_most_played" => Self::DisplayMostPlayed,
             "sound_bot_display_menu_item_recently_added" => Self::DisplayRecentlyAdded,
+            "sound_bot_display_menu_item_recently_played" => Self::DisplayRecentlyPlayed,
+            "sound_bot_display_menu_item_trending" => Self::DisplayTrending,
+            "sound_bot_display_menu_item_favorites" => Self::DisplayFavorites,
             _ => Self::Unknown(value.clone()),
         }
     }
@@ -112,6 +146,15 @@ impl From<DisplayMenuItemCustomId> for String {
             DisplayMenuItemCustomId::DisplayRecentlyAdded => {
                 format!("sound_bot_display_menu_item_recently_added")
             }
+            DisplayMenuItemCustomId::DisplayRecentlyPlayed => {
+                "sound_bot_display_menu_item_recently_played".to_string()
+            }
+            DisplayMenuItemCustomId::DisplayTrending => {
+                "sound_bot_display_menu_item_trending".to_string()
+            }
+            DisplayMenuItemCustomId::DisplayFavorites => {
+                "sound_bot_display_menu_item_favorites".into()
+            }
             DisplayMenuItemCustomId::Unknown(val) => val,
         }
     }
@@ -123,6 +166,18 @@ pub enum PaginateId {
     RecentlyAddedLastPage(u64),
     RecentlyAddedNextPage(u64),
     RecentlyAddedPrevPage(u64),
+    RecentlyPlayedFirstPage(u64),
+    RecentlyPlayedLastPage(u64),
+    RecentlyPlayedNextPage(u64),
+    RecentlyPlayedPrevPage(u64),
+    TrendingFirstPage(u64),
+    TrendingLastPage(u64),
+    TrendingNextPage(u64),
+    TrendingPrevPage(u64),
+    FavoritesFirstPage(u64),
+    FavoritesLastPage(u64),
+    FavoritesNextPage(u64),
+    FavoritesPrevPage(u64),
     AllFirstPage(u64),
     AllLastPage(u64),
     AllNextPage(u64),
@@ -135,6 +190,14 @@ pub enum PaginateId {
     SearchLastPage(u64, String),
     SearchNextPage(u64, String),
     SearchPrevPage(u64, String),
+    CollectionFirstPage(u64, String),
+    CollectionLastPage(u64, String),
+    CollectionNextPage(u64, String),
+    CollectionPrevPage(u64, String),
+    TagFirstPage(u64, i64),
+    TagLastPage(u64, i64),
+    TagNextPage(u64, i64),
+    TagPrevPage(u64, i64),
     PinnedFirstPage(u64),
     PinnedLastPage(u64),
     PinnedNextPage(u64),
@@ -154,6 +217,14 @@ impl TryFrom<&String> for PaginateId {
                 .log_err_op(|e| format!("Parse error on button page offset value: '{value}' - {e}"))
         };
 
+        // tags are keyed by row id, since tag names can exceed discord's custom_id length limit
+        let parse_tag_id_fn = |val: Option<&&str>| -> Result<i64, String> {
+            val.ok_or("Missing tag id".to_string())?
+                .parse()
+                .map_err(|e: ParseIntError| e.to_string())
+                .log_err_op(|e| format!("Parse error on button tag id value: '{value}' - {e}"))
+        };
+
         match parts[0] {
             "recently_added_first_page" => Ok(PaginateId::RecentlyAddedFirstPage(parse_offset_fn(
                 parts[1],
@@ -167,6 +238,28 @@ impl TryFrom<&String> for PaginateId {
             "recently_added_prev_page" => Ok(PaginateId::RecentlyAddedPrevPage(parse_offset_fn(
                 parts[1],
             )?)),
+            "recently_played_first_page" => Ok(PaginateId::RecentlyPlayedFirstPage(
+                parse_offset_fn(parts[1])?,
+            )),
+            "recently_played_last_page" => Ok(PaginateId::RecentlyPlayedLastPage(parse_offset_fn(
+                parts[1],
+            )?)),
+            "recently_played_next_page" => Ok(PaginateId::RecentlyPlayedNextPage(parse_offset_fn(
+                parts[1],
+            )?)),
+            "recently_played_prev_page" => Ok(PaginateId::RecentlyPlayedPrevPage(parse_offset_fn(
+                parts[1],
+            )?)),
+            "trending_first_page" => Ok(PaginateId::TrendingFirstPage(parse_offset_fn(parts[1])?)),
+            "trending_last_page" => Ok(PaginateId::TrendingLastPage(parse_offset_fn(parts[1])?)),
+            "trending_next_page" => Ok(PaginateId::TrendingNextPage(parse_offset_fn(parts[1])?)),
+            "trending_prev_page" => Ok(PaginateId::TrendingPrevPage(parse_offset_fn(parts[1])?)),
+            "favorites_first_page" => {
+                Ok(PaginateId::FavoritesFirstPage(parse_offset_fn(parts[1])?))
+            }
+            "favorites_last_page" => Ok(PaginateId::FavoritesLastPage(parse_offset_fn(parts[1])?)),
+            "favorites_next_page" => Ok(PaginateId::FavoritesNextPage(parse_offset_fn(parts[1])?)),
+            "favorites_prev_page" => Ok(PaginateId::FavoritesPrevPage(parse_offset_fn(parts[1])?)),
             "all_first_page" => Ok(PaginateId::AllFirstPage(parse_offset_fn(parts[1])?)),
             "all_last_page" => Ok(PaginateId::AllLastPage(parse_offset_fn(parts[1])?)),
             "all_next_page" => Ok(PaginateId::AllNextPage(parse_offset_fn(parts[1])?)),
@@ -199,6 +292,38 @@ impl TryFrom<&String> for PaginateId {
                 parse_offset_fn(parts[1])?,
                 parts[2..].join("").into(),
             )),
+            "collection_first_page" => Ok(PaginateId::CollectionFirstPage(
+                parse_offset_fn(parts[1])?,
+                parts[2..].join(""),
+            )),
+            "collection_last_page" => Ok(PaginateId::CollectionLastPage(
+                parse_offset_fn(parts[1])?,
+                parts[2..].join(""),
+            )),
+            "collection_next_page" => Ok(PaginateId::CollectionNextPage(
+                parse_offset_fn(parts[1])?,
+                parts[2..].join(""),
+            )),
+            "collection_prev_page" => Ok(PaginateId::CollectionPrevPage(
+                parse_offset_fn(parts[1])?,
+                parts[2..].join(""),
+            )),
+            "tag_first_page" => Ok(PaginateId::TagFirstPage(
+                parse_offset_fn(parts[1])?,
+                parse_tag_id_fn(parts.get(2))?,
+            )),
+            "tag_last_page" => Ok(PaginateId::TagLastPage(
+                parse_offset_fn(parts[1])?,
+                parse_tag_id_fn(parts.get(2))?,
+            )),
+            "tag_next_page" => Ok(PaginateId::TagNextPage(
+                parse_offset_fn(parts[1])?,
+                parse_tag_id_fn(parts.get(2))?,
+            )),
+            "tag_prev_page" => Ok(PaginateId::TagPrevPage(
+                parse_offset_fn(parts[1])?,
+                parse_tag_id_fn(parts.get(2))?,
+            )),
             val => Ok(Self::Unknown(val.into())),
         }
     }
@@ -235,6 +360,24 @@ impl From<&PaginateId> for String {
             PaginateId::RecentlyAddedPrevPage(val) => {
                 format!("recently_added_prev_page::{val}")
             }
+            PaginateId::RecentlyPlayedFirstPage(val) => {
+                format!("recently_played_first_page::{val}")
+            }
+            PaginateId::RecentlyPlayedLastPage(val) => format!("recently_played_last_page::{val}"),
+            PaginateId::RecentlyPlayedNextPage(val) => {
+                format!("recently_played_next_page::{val}")
+            }
+            PaginateId::RecentlyPlayedPrevPage(val) => {
+                format!("recently_played_prev_page::{val}")
+            }
+            PaginateId::TrendingFirstPage(val) => format!("trending_first_page::{val}"),
+            PaginateId::TrendingLastPage(val) => format!("trending_last_page::{val}"),
+            PaginateId::TrendingNextPage(val) => format!("trending_next_page::{val}"),
+            PaginateId::TrendingPrevPage(val) => format!("trending_prev_page::{val}"),
+            PaginateId::FavoritesFirstPage(val) => format!("favorites_first_page::{val}"),
+            PaginateId::FavoritesLastPage(val) => format!("favorites_last_page::{val}"),
+            PaginateId::FavoritesNextPage(val) => format!("favorites_next_page::{val}"),
+            PaginateId::FavoritesPrevPage(val) => format!("favorites_prev_page::{val}"),
             PaginateId::PinnedFirstPage(val) => format!("pinned_first_page::{val}"),
             PaginateId::PinnedLastPage(val) => format!("pinned_last_page::{val}"),
             PaginateId::PinnedNextPage(val) => format!("pinned_next_page::{val}"),
@@ -249,6 +392,22 @@ impl From<&PaginateId> for String {
             PaginateId::SearchPrevPage(val, search) => {
                 format!("search_prev_page::{val}::{search}")
             }
+            PaginateId::CollectionFirstPage(val, name) => {
+                format!("collection_first_page::{val}::{name}")
+            }
+            PaginateId::CollectionLastPage(val, name) => {
+                format!("collection_last_page::{val}::{name}")
+            }
+            PaginateId::CollectionNextPage(val, name) => {
+                format!("collection_next_page::{val}::{name}")
+            }
+            PaginateId::CollectionPrevPage(val, name) => {
+                format!("collection_prev_page::{val}::{name}")
+            }
+            PaginateId::TagFirstPage(val, tag_id) => format!("tag_first_page::{val}::{tag_id}"),
+            PaginateId::TagLastPage(val, tag_id) => format!("tag_last_page::{val}::{tag_id}"),
+            PaginateId::TagNextPage(val, tag_id) => format!("tag_next_page::{val}::{tag_id}"),
+            PaginateId::TagPrevPage(val, tag_id) => format!("tag_prev_page::{val}::{tag_id}"),
 
             PaginateId::Unknown(val) => val.clone(),
         }
@@ -268,14 +427,69 @@ impl fmt::Display for PaginateId {
     }
 }
 
+/// Audit log page offset & filters
+#[derive(Debug)]
+pub struct AuditLogPageId {
+    pub offset: u64,
+    pub action: Option<AuditAction>,
+    pub actor_id: Option<u64>,
+}
+
+impl TryFrom<&[&str]> for AuditLogPageId {
+    type Error = String;
+
+    fn try_from(parts: &[&str]) -> Result<Self, Self::Error> {
+        let parse_err_fn = |e: ParseIntError| e.to_string();
+
+        let offset = parts
+            .first()
+            .ok_or("Missing audit log page offset")?
+            .parse()
+            .map_err(parse_err_fn)?;
+
+        let action = match parts.get(1) {
+            Some(val) if !val.is_empty() => Some(AuditAction::try_from(*val)?),
+            _ => None,
+        };
+
+        let actor_id = match parts.get(2) {
+            Some(val) if !val.is_empty() => Some(val.parse().map_err(parse_err_fn)?),
+            _ => None,
+        };
+
+        Ok(Self {
+            offset,
+            action,
+            actor_id,
+        })
+    }
+}
+
+impl fmt::Display for AuditLogPageId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let action = self.action.map(|action| action.as_str()).unwrap_or("");
+        let actor_id = self
+            .actor_id
+            .map(|actor_id| actor_id.to_string())
+            .unwrap_or_default();
+
+        write!(f, "{}::{action}::{actor_id}", self.offset)
+    }
+}
+
 #[derive(Debug)]
 pub enum ButtonCustomId {
     PlayAudio(i64),
+    PreviewAudio(i64),
+    ToggleFavorite(i64),
+    PlayCollection(String),
     PlayRandom,
     Search,
     Paginate(PaginateId),
     AddMp3File,
     IgnoreMp3File,
+    AuditLogPage(AuditLogPageId),
+    Stats(StatsWindow),
     Unknown(String),
 }
 
@@ -293,6 +507,23 @@ impl TryFrom<&String> for ButtonCustomId {
                     .log_err_op(|e| format!("Parse error on button custom id '{value}' - {e}"))?;
                 Ok(ButtonCustomId::PlayAudio(id))
             }
+            "sound_bot_preview" => {
+                let id: i64 = parts[1]
+                    .parse()
+                    .map_err(|e: ParseIntError| e.to_string())
+                    .log_err_op(|e| format!("Parse error on button custom id '{value}' - {e}"))?;
+                Ok(ButtonCustomId::PreviewAudio(id))
+            }
+            "sound_bot_favorite" => {
+                let id: i64 = parts[1]
+                    .parse()
+                    .map_err(|e: ParseIntError| e.to_string())
+                    .log_err_op(|e| format!("Parse error on button custom id '{value}' - {e}"))?;
+                Ok(ButtonCustomId::ToggleFavorite(id))
+            }
+            "sound_bot_play_collection" => {
+                Ok(ButtonCustomId::PlayCollection(parts[1..].join("::")))
+            }
             "sound_bot_play_random" => Ok(ButtonCustomId::PlayRandom),
             "sound_bot_search" => Ok(ButtonCustomId::Search),
             "sound_bot_paginate" => Ok(ButtonCustomId::Paginate(PaginateId::try_from(
@@ -300,6 +531,14 @@ impl TryFrom<&String> for ButtonCustomId {
             )?)),
             "sound_bot_add_mp3_file" => Ok(ButtonCustomId::AddMp3File),
             "sound_bot_ignore_mp3_file" => Ok(ButtonCustomId::IgnoreMp3File),
+            "sound_bot_audit_log_page" => Ok(ButtonCustomId::AuditLogPage(
+                AuditLogPageId::try_from(&parts[1..])
+                    .log_err_op(|e| format!("Parse error on button custom id '{value}' - {e}"))?,
+            )),
+            "sound_bot_stats" => Ok(ButtonCustomId::Stats(
+                StatsWindow::try_from(parts.get(1).copied().unwrap_or_default())
+                    .log_err_op(|e| format!("Parse error on button custom id '{value}' - {e}"))?,
+            )),
             _ => Ok(ButtonCustomId::Unknown(value.clone())),
         }
     }
@@ -317,11 +556,16 @@ impl From<ButtonCustomId> for String {
     fn from(value: ButtonCustomId) -> Self {
         match value {
             ButtonCustomId::PlayAudio(val) => format!("sound_bot_play::{val}"),
+            ButtonCustomId::PreviewAudio(val) => format!("sound_bot_preview::{val}"),
+            ButtonCustomId::ToggleFavorite(val) => format!("sound_bot_favorite::{val}"),
+            ButtonCustomId::PlayCollection(val) => format!("sound_bot_play_collection::{val}"),
             ButtonCustomId::PlayRandom => format!("sound_bot_play_random"),
             ButtonCustomId::Search => format!("sound_bot_search"),
             ButtonCustomId::Paginate(val) => format!("sound_bot_paginate::{val}"),
             ButtonCustomId::AddMp3File => format!("sound_bot_add_mp3_file"),
             ButtonCustomId::IgnoreMp3File => format!("sound_bot_ignore_mp3_file"),
+            ButtonCustomId::AuditLogPage(val) => format!("sound_bot_audit_log_page::{val}"),
+            ButtonCustomId::Stats(val) => format!("sound_bot_stats::{}", val.as_str()),
             ButtonCustomId::Unknown(val) => val,
         }
     }
@@ -373,6 +617,225 @@ pub fn get_author_voice_channel(ctx: &PoiseContext) -> Result<(GuildId, ChannelI
     }
 }
 
+/// Voice channel user is in, if any
+pub fn get_user_voice_channel_id(
+    ctx: &Context,
+    guild_id: GuildId,
+    user_id: UserId,
+) -> Option<ChannelId> {
+    let guild = ctx.cache.guild(guild_id)?;
+    let voice_state = guild.voice_states.get(&user_id)?;
+    voice_state.channel_id
+}
+
+/// True if songbird has a call connected to a voice channel of the guild
+pub async fn is_bot_in_voice_channel(ctx: &Context, guild_id: GuildId) -> bool {
+    match songbird_get(ctx).await.get(guild_id) {
+        Some(handler_lock) => handler_lock.lock().await.current_channel().is_some(),
+        None => false,
+    }
+}
+
+/// Parses emoji typed in a command option. ie: a unicode emoji, or a custom emoji as `<:name:id>`
+pub fn parse_emoji(text: impl AsRef<str>) -> Result<ReactionType, PoiseError> {
+    let text = text.as_ref().trim();
+    let emoji = ReactionType::try_from(text).map_err(|_| format!("`{text}` isn't an emoji"))?;
+
+    // any text parses as a unicode emoji, so only accept known emojis
+    if let ReactionType::Unicode(ref val) = emoji {
+        if emojis::get(val).is_none() {
+            return Err(format!("`{text}` isn't an emoji").into());
+        }
+    }
+
+    Ok(emoji)
+}
+
+/// Joins, or moves to, voice channel without playing the join audio
+pub async fn connect_voice_channel(
+    ctx: &Context,
+    data: &UserData,
+    guild_id: GuildId,
+    channel_id: ChannelId,
+) -> PoiseResult {
+    log::info!("Bot will join Guild ID: {guild_id}, Voice Channel: {channel_id}");
+    let manager = songbird_get(ctx).await;
+
+    let handler_lock = manager
+        .join(guild_id, channel_id)
+        .await
+        .log_err_msg(format!(
+            "Bot failed to join Guild ID: {guild_id}, Voice Channel ID: {channel_id}"
+        ))?;
+
+    {
+        let mut handler = handler_lock.lock().await;
+        // calls are reused when rejoining, so don't stack up handlers
+        handler.remove_all_global_events();
+        // Attach an event handler to see notifications of all track errors
+        handler.add_global_event(TrackEvent::Error.into(), TrackErrorNotifier);
+        // Track playback, to leave once idle
+        handler.add_global_event(
+            TrackEvent::Play.into(),
+            data.playback_activity.notifier(guild_id),
+        );
+    }
+    data.playback_activity.touch(guild_id, chrono::Utc::now());
+    log::info!("Bot joined Guild ID: {guild_id}, Voice Channel ID: {channel_id}");
+
+    Ok(())
+}
+
+/// Joins voice channel, then plays the join audio if set. `user_id` is who had the bot join, if anyone
+pub async fn join_voice_channel(
+    ctx: &Context,
+    data: &UserData,
+    guild_id: GuildId,
+    channel_id: ChannelId,
+    user_id: Option<UserId>,
+) -> PoiseResult {
+    connect_voice_channel(ctx, data, guild_id, channel_id).await?;
+    let manager = songbird_get(ctx).await;
+
+    if let Ok(settings) = data.settings_table().get_settings().log_err() {
+        if let Some(ref join_audio) = settings.join_audio {
+            log::info!("Detected join audio: {join_audio}. Attempting to play.");
+            match data
+                .audio_table()
+                .find_audio_row(db::UniqueAudioTableCol::Name(join_audio.clone()))
+            {
+                Some(row) => {
+                    log::debug!("bot join audio playing: {}", row.name);
+                    manager
+                        .play_audio(guild_id, channel_id, &row.audio_file)
+                        .await
+                        .log_err()
+                        .ok();
+
+                    data.record_play(
+                        db::PlayHistoryEntry::new(row.id, guild_id, db::PlaySource::Join)
+                            .user_id(user_id)
+                            .channel_id(Some(channel_id)),
+                    )
+                    .log_err()
+                    .ok();
+                }
+                None => log::error!("Couldn't locate join audio"),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Plays the leave audio, if set, to its end then leaves the guild's voice channel
+pub async fn leave_voice_channel(
+    manager: &Songbird,
+    data: &UserData,
+    guild_id: GuildId,
+    channel_id: ChannelId,
+) -> PoiseResult {
+    if let Ok(settings) = data.settings_table().get_settings().log_err() {
+        if let Some(ref leave_audio) = settings.leave_audio {
+            log::info!("Detected leave audio: {leave_audio}. Attempting to play.");
+            match data
+                .audio_table()
+                .find_audio_row(db::UniqueAudioTableCol::Name(leave_audio.clone()))
+            {
+                Some(row) => {
+                    log::debug!("bot leave audio playing: {}", row.name);
+                    manager
+                        .play_audio_to_end(guild_id, channel_id, &row.audio_file)
+                        .await
+                        .log_err()
+                        .ok();
+                }
+                None => log::error!("Couldn't locate leave audio"),
+            }
+        }
+    }
+
+    data.playback_activity.remove(guild_id);
+    manager
+        .remove(guild_id)
+        .await
+        .log_err_msg(format!("Bot failed to leave Guild ID: {guild_id}"))?;
+
+    Ok(())
+}
+
+/// Joins the voice channel of user playing a sound, if the bot isn't in a voice channel of the
+/// guild yet. Users not in a voice channel are left to the usual not in voice channel error
+pub async fn auto_join_user_voice_channel(
+    ctx: &Context,
+    data: &UserData,
+    guild_id: GuildId,
+    user_id: UserId,
+) -> PoiseResult {
+    if is_bot_in_voice_channel(ctx, guild_id).await {
+        return Ok(());
+    }
+
+    match get_user_voice_channel_id(ctx, guild_id, user_id) {
+        Some(channel_id) => {
+            log::info!("Auto joining voice channel of user: {user_id}");
+            join_voice_channel(ctx, data, guild_id, channel_id, Some(user_id)).await
+        }
+        None => Ok(()),
+    }
+}
+
+/// Queues collection's audio tracks to play one after another, in collection order.
+/// Returns number of tracks queued, without waiting for them to play
+pub async fn play_collection(
+    ctx: &Context,
+    data: &UserData,
+    guild_id: GuildId,
+    collection: &db::CollectionRow,
+    user_id: UserId,
+) -> Result<usize, PoiseError> {
+    log::info!("Playing collection `{}`", collection.name);
+    auto_join_user_voice_channel(ctx, data, guild_id, user_id).await?;
+
+    let mut audio_rows = vec![];
+    for page in
+        db::AudioTablePaginatorBuilder::collection_template(data.db_connection(), collection.id)
+            .build()
+    {
+        audio_rows.extend(page?);
+    }
+
+    let handler_lock = songbird_get(ctx)
+        .await
+        .get(guild_id)
+        .ok_or(AudioError::NotInVoiceChannel)?;
+    let bot_channel_id = get_bot_voice_channel_id(ctx, guild_id).await;
+    {
+        let mut handler = handler_lock.lock().await;
+        for audio_row in audio_rows.iter() {
+            let audio_input = songbird::input::File::new(audio_row.audio_file.as_path_buf());
+            handler.enqueue_input(audio_input.into()).await;
+        }
+    }
+
+    for audio_row in audio_rows.iter() {
+        data.record_play(
+            db::PlayHistoryEntry::new(audio_row.id, guild_id, db::PlaySource::Collection)
+                .user_id(Some(user_id))
+                .channel_id(bot_channel_id),
+        )
+        .log_err()
+        .ok();
+    }
+
+    log::info!(
+        "Queued {} track(s) of collection `{}`",
+        audio_rows.len(),
+        collection.name
+    );
+    Ok(audio_rows.len())
+}
+
 #[async_trait]
 pub trait SongbirdHelper {
     /// Begins play audio track and returns handle to track
@@ -473,19 +936,37 @@ impl<'a> PoiseContextHelper<'a> for PoiseContext<'a> {
     }
 }
 
-pub fn make_action_row(audio_rows: &[AudioTableRow]) -> CreateActionRow {
+/// Row of sound buttons, showing the emojis bound to the sounds in guild
+pub fn make_action_row(
+    conn: &rusqlite::Connection,
+    guild_id: GuildId,
+    audio_rows: &[AudioTableRow],
+) -> CreateActionRow {
+    let emojis = db::EmojiBindingsTable::guild_emojis(conn, guild_id.into())
+        .log_err_msg("Failed listing guild emojis for sound buttons")
+        .unwrap_or_default();
+
     let buttons: Vec<_> = audio_rows
         .iter()
         .map(|track| {
-            let style = if track.pinned {
-                serenity::all::ButtonStyle::Success
-            } else {
-                serenity::all::ButtonStyle::Primary
+            let style = match track.button_style {
+                Some(style) => style.into(),
+                None if track.pinned => serenity::all::ButtonStyle::Success,
+                None => serenity::all::ButtonStyle::Primary,
             };
 
-            CreateButton::new(ButtonCustomId::PlayAudio(track.id))
+            let button = CreateButton::new(ButtonCustomId::PlayAudio(track.id))
                 .label(track.name.to_button_label())
-                .style(style)
+                .style(style);
+
+            // an invalid emoji would make discord reject the whole message, so drop it
+            match emojis
+                .get(&track.id)
+                .and_then(|emoji| parse_emoji(emoji).ok())
+            {
+                Some(emoji) => button.emoji(emoji),
+                None => button,
+            }
         })
         .collect();
 
@@ -525,6 +1006,14 @@ impl Into<CreateMessage> for SoundDisplayMessage {
     }
 }
 
+impl From<SoundDisplayMessage> for EditMessage {
+    fn from(val: SoundDisplayMessage) -> Self {
+        EditMessage::new()
+            .content(val.content)
+            .components(val.components)
+    }
+}
+
 impl Into<CreateReply> for SoundDisplayMessage {
     fn into(self) -> CreateReply {
         CreateReply::default()
@@ -545,6 +1034,7 @@ impl Into<CreateInteractionResponseFollowup> for SoundDisplayMessage {
 
 pub fn make_display_message(
     paginator: &mut db::AudioTablePaginator,
+    guild_id: GuildId,
     display_type: DisplayType,
     search: Option<String>,
     ephemeral: bool,
@@ -552,10 +1042,10 @@ pub fn make_display_message(
     let paginate_info: PaginateInfo = paginator.pageinate_info()?;
 
     let title = make_display_title(display_type, &paginate_info, search.clone());
-    let btn_grid: Vec<_> = paginator
-        .next_page()?
+    let audio_rows = paginator.next_page()?;
+    let btn_grid: Vec<_> = audio_rows
         .chunks(5)
-        .map(make_action_row)
+        .map(|rows| make_action_row(paginator.connection(), guild_id, rows))
         .collect();
     let paginate_ctrls = make_paginate_controls(display_type, &paginate_info, search.clone());
 
@@ -566,14 +1056,158 @@ pub fn make_display_message(
     Ok(SoundDisplayMessage::new(title, components, ephemeral))
 }
 
-pub fn make_sound_controls_message(ephemeral: bool) -> SoundDisplayMessage {
+/// First page of all sounds, as shown on soundboard panels. Never ephemeral, since everyone shares the panel
+pub fn make_panel_message(
+    data: &UserData,
+    guild_id: GuildId,
+) -> Result<SoundDisplayMessage, String> {
+    let mut paginator = db::AudioTablePaginatorBuilder::all_template(data.db_connection())
+        .page_limit(data.config.max_page_size)
+        .build();
+
+    make_display_message(&mut paginator, guild_id, DisplayType::All, None, false)
+}
+
+/// Posts guild's soundboard panel in channel, replacing any previous panel
+pub async fn post_sound_panel(
+    http: &Http,
+    data: &UserData,
+    guild_id: GuildId,
+    channel_id: ChannelId,
+    author_id: Option<u64>,
+) -> Result<Message, PoiseError> {
+    log::info!("Posting soundboard panel. Guild id: {guild_id}, Channel id: {channel_id}");
+
+    let message = channel_id
+        .send_message(http, make_panel_message(data, guild_id)?.into())
+        .await
+        .log_err_msg("Failed posting soundboard panel")?;
+
+    data.panels_table().upsert_panel(
+        guild_id.into(),
+        channel_id.into(),
+        message.id.into(),
+        author_id,
+    )?;
+
+    Ok(message)
+}
+
+fn is_not_found_err(err: &serenity::Error) -> bool {
+    match err {
+        serenity::Error::Http(err) => {
+            err.status_code() == Some(serenity::http::StatusCode::NOT_FOUND)
+        }
+        _ => false,
+    }
+}
+
+/// Edits guild's soundboard panel in place to show the current sounds. Re-posts the panel if its message
+/// was deleted, and forgets it if its channel was deleted
+pub async fn refresh_sound_panel(
+    http: &Http,
+    data: &UserData,
+    panel: &PanelRow,
+) -> Result<(), PoiseError> {
+    let guild_id = GuildId::new(panel.guild_id);
+    let channel_id = ChannelId::new(panel.channel_id);
+    let message_id = MessageId::new(panel.message_id);
+
+    let err = match channel_id
+        .edit_message(http, message_id, make_panel_message(data, guild_id)?.into())
+        .await
+    {
+        Ok(_) => return Ok(()),
+        Err(err) if is_not_found_err(&err) => err,
+        Err(err) => return Err(err.into()),
+    };
+
+    log::warn!(
+        "Soundboard panel message not found, re-posting it. Guild id: {}, Message id: {message_id} - {err}",
+        panel.guild_id
+    );
+
+    match channel_id
+        .send_message(http, make_panel_message(data, guild_id)?.into())
+        .await
+    {
+        Ok(message) => data
+            .panels_table()
+            .update_panel_message(panel.guild_id, message.id.into()),
+        Err(err) if is_not_found_err(&err) => {
+            log::warn!(
+                "Soundboard panel channel not found, removing panel. Guild id: {}, Channel id: {channel_id}",
+                panel.guild_id
+            );
+            data.panels_table().delete_panel(panel.guild_id)?;
+            Ok(())
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Refreshes every guild's soundboard panel. Sounds are shared between guilds, so any change shows on all panels
+pub async fn refresh_sound_panels(http: &Http, data: &UserData) {
+    let Ok(panels) = data.panels_table().list_panels() else {
+        return;
+    };
+
+    for panel in panels.iter() {
+        refresh_sound_panel(http, data, panel)
+            .await
+            .log_err_msg(format!(
+                "Failed refreshing soundboard panel. Guild id: {}",
+                panel.guild_id
+            ))
+            .ok();
+    }
+}
+
+/// Max number of tags in the soundbot controls tag menu. ie: discord's select menu option limit
+pub const TAG_MENU_MAX_TAGS: usize = 25;
+
+pub fn make_sound_controls_message(data: &UserData) -> SoundDisplayMessage {
+    let tags = data
+        .tags_table()
+        .list_tags(Some(TAG_MENU_MAX_TAGS))
+        .log_err_msg("Failed listing tags for soundbot controls")
+        .unwrap_or_default();
+
+    let mut components = make_soundbot_control_components(None);
+    components.extend(make_tag_select_menu(&tags));
+
     SoundDisplayMessage::new(
         "**Soundbot Controls**".into(),
-        make_soundbot_control_components(None),
-        ephemeral,
+        components,
+        data.config.enable_ephemeral_controls,
     )
 }
 
+/// Select menu of tags that displays the sounds tagged with the selected tag.
+/// None if there are no tags, since discord requires at least one option
+pub fn make_tag_select_menu(tags: &[(String, u64)]) -> Option<CreateActionRow> {
+    if tags.is_empty() {
+        return None;
+    }
+
+    let options = tags
+        .iter()
+        .take(TAG_MENU_MAX_TAGS)
+        .map(|(tag, count)| {
+            CreateSelectMenuOption::new(format!("{tag} ({count})"), tag)
+                .emoji(ReactionType::Unicode("🏷️".into()))
+        })
+        .collect();
+
+    Some(CreateActionRow::SelectMenu(
+        serenity::builder::CreateSelectMenu::new(
+            TAG_MENU_CUSTOM_ID,
+            serenity::builder::CreateSelectMenuKind::String { options },
+        )
+        .placeholder("Display Sounds by Tag"),
+    ))
+}
+
 pub fn make_soundbot_control_components(
     default_selected_menu_item: Option<DisplayMenuItemCustomId>,
 ) -> Vec<CreateActionRow> {
@@ -618,6 +1252,33 @@ pub fn make_soundbot_control_components(
                             default_selected_menu_item
                                 == Some(DisplayMenuItemCustomId::DisplayMostPlayed),
                         ),
+                        CreateSelectMenuOption::new(
+                            "Recently Played Sounds",
+                            DisplayMenuItemCustomId::DisplayRecentlyPlayed,
+                        )
+                        .emoji(ReactionType::Unicode("📋".into()))
+                        .default_selection(
+                            default_selected_menu_item
+                                == Some(DisplayMenuItemCustomId::DisplayRecentlyPlayed),
+                        ),
+                        CreateSelectMenuOption::new(
+                            "Trending Sounds",
+                            DisplayMenuItemCustomId::DisplayTrending,
+                        )
+                        .emoji(ReactionType::Unicode("📋".into()))
+                        .default_selection(
+                            default_selected_menu_item
+                                == Some(DisplayMenuItemCustomId::DisplayTrending),
+                        ),
+                        CreateSelectMenuOption::new(
+                            "My Favorites",
+                            DisplayMenuItemCustomId::DisplayFavorites,
+                        )
+                        .emoji(ReactionType::Unicode("⭐".into()))
+                        .default_selection(
+                            default_selected_menu_item
+                                == Some(DisplayMenuItemCustomId::DisplayFavorites),
+                        ),
                     ],
                 },
             )
@@ -641,7 +1302,7 @@ pub async fn autocomplete_audio_track_name<'a>(
     partial: &'a str,
 ) -> impl futures::stream::Stream<Item = String> + 'a {
     let table = ctx.data().audio_table();
-    let track_names = table.fts_autocomplete_track_names(partial, Some(5));
+    let track_names = table.autocomplete_track_names(partial, Some(5));
     futures::stream::iter(track_names)
 }
 
@@ -650,12 +1311,58 @@ pub async fn autocomplete_opt_audio_track_name<'a>(
     partial: &'a str,
 ) -> impl futures::stream::Stream<Item = String> + 'a {
     let table = ctx.data().audio_table();
-    let mut track_names = table.fts_autocomplete_track_names(partial, Some(5));
+    let mut track_names = table.autocomplete_track_names(partial, Some(5));
     track_names.insert(0, "NONE".into());
 
     futures::stream::iter(track_names)
 }
 
+pub async fn autocomplete_trashed_audio_track_name<'a>(
+    ctx: PoiseContext<'_>,
+    partial: &'a str,
+) -> impl futures::stream::Stream<Item = String> + 'a {
+    let table = ctx.data().audio_table();
+    let track_names = table.autocomplete_trashed_track_names(partial, Some(5));
+    futures::stream::iter(track_names)
+}
+
+pub async fn autocomplete_favorite_audio_track_name<'a>(
+    ctx: PoiseContext<'_>,
+    partial: &'a str,
+) -> impl futures::stream::Stream<Item = String> + 'a {
+    let table = ctx.data().favorites_table();
+    let track_names =
+        table.autocomplete_favorite_track_names(ctx.author().id.into(), partial, Some(5));
+    futures::stream::iter(track_names)
+}
+
+pub async fn autocomplete_collection_name<'a>(
+    ctx: PoiseContext<'_>,
+    partial: &'a str,
+) -> impl futures::stream::Stream<Item = String> + 'a {
+    let collection_names = match ctx.guild_id() {
+        Some(guild_id) => ctx
+            .data()
+            .collections_table()
+            .autocomplete_collection_names(guild_id.into(), partial, Some(5)),
+        None => vec![],
+    };
+
+    futures::stream::iter(collection_names)
+}
+
+pub async fn autocomplete_tag_name<'a>(
+    ctx: PoiseContext<'_>,
+    partial: &'a str,
+) -> impl futures::stream::Stream<Item = String> + 'a {
+    let tag_names = ctx
+        .data()
+        .tags_table()
+        .autocomplete_tag_names(partial, Some(5));
+
+    futures::stream::iter(tag_names)
+}
+
 pub fn uuid_v4_str() -> String {
     // Create uuid audio file in /tmp directory
     let uuid = uuid::Uuid::new_v4();
@@ -667,9 +1374,15 @@ pub fn uuid_v4_str() -> String {
 pub enum DisplayType {
     All,
     RecentlyAdded,
+    RecentlyPlayed,
+    Trending,
+    Favorites,
     MostPlayed,
     Pinned,
     Search,
+    Collection,
+    /// Tag row id
+    Tag(i64),
 }
 
 impl From<DisplayType> for DisplayMenuItemCustomId {
@@ -678,8 +1391,13 @@ impl From<DisplayType> for DisplayMenuItemCustomId {
             DisplayType::All => DisplayMenuItemCustomId::DisplayAll,
             DisplayType::MostPlayed => DisplayMenuItemCustomId::DisplayMostPlayed,
             DisplayType::RecentlyAdded => DisplayMenuItemCustomId::DisplayRecentlyAdded,
+            DisplayType::RecentlyPlayed => DisplayMenuItemCustomId::DisplayRecentlyPlayed,
+            DisplayType::Trending => DisplayMenuItemCustomId::DisplayTrending,
+            DisplayType::Favorites => DisplayMenuItemCustomId::DisplayFavorites,
             DisplayType::Pinned => DisplayMenuItemCustomId::DisplayPinned,
-            DisplayType::Search => DisplayMenuItemCustomId::Unknown("".into()),
+            DisplayType::Search | DisplayType::Collection | DisplayType::Tag(_) => {
+                DisplayMenuItemCustomId::Unknown("".into())
+            }
         }
     }
 }
@@ -690,6 +1408,9 @@ impl From<DisplayMenuItemCustomId> for DisplayType {
             DisplayMenuItemCustomId::DisplayAll => Self::All,
             DisplayMenuItemCustomId::DisplayMostPlayed => Self::MostPlayed,
             DisplayMenuItemCustomId::DisplayRecentlyAdded => Self::RecentlyAdded,
+            DisplayMenuItemCustomId::DisplayRecentlyPlayed => Self::RecentlyPlayed,
+            DisplayMenuItemCustomId::DisplayTrending => Self::Trending,
+            DisplayMenuItemCustomId::DisplayFavorites => Self::Favorites,
             DisplayMenuItemCustomId::DisplayPinned => Self::Pinned,
             DisplayMenuItemCustomId::Unknown(_) => Self::All,
         }
@@ -701,6 +1422,17 @@ pub fn make_paginate_controls(
     paginate_info: &PaginateInfo,
     search: Option<String>,
 ) -> CreateActionRow {
+    let play_collection_btn = match (display_type, search.as_ref()) {
+        (DisplayType::Collection, Some(name)) => Some(
+            CreateButton::new(ButtonCustomId::PlayCollection(name.clone()))
+                .label("Play collection")
+                .style(serenity::all::ButtonStyle::Success)
+                .emoji(ReactionType::Unicode("▶️".into()))
+                .disabled(paginate_info.total_row_count == 0),
+        ),
+        _ => None,
+    };
+
     let (first_btn, prev_btn, next_btn, last_btn) = match display_type {
         DisplayType::All => {
             let first_btn = CreateButton::new(ButtonCustomId::Paginate(PaginateId::AllFirstPage(
@@ -771,6 +1503,75 @@ pub fn make_paginate_controls(
 
             (first_btn, prev_btn, next_btn, last_btn)
         }
+        DisplayType::RecentlyPlayed => {
+            let first_btn = CreateButton::new(ButtonCustomId::Paginate(
+                PaginateId::RecentlyPlayedFirstPage(paginate_info.first_page_offset.unwrap_or(0)),
+            ))
+            .disabled(paginate_info.first_page_offset.is_none());
+
+            let last_btn = CreateButton::new(ButtonCustomId::Paginate(
+                PaginateId::RecentlyPlayedLastPage(paginate_info.last_page_offset.unwrap_or(0)),
+            ))
+            .disabled(paginate_info.last_page_offset.is_none());
+
+            let prev_btn = CreateButton::new(ButtonCustomId::Paginate(
+                PaginateId::RecentlyPlayedPrevPage(paginate_info.prev_page_offset.unwrap_or(0)),
+            ))
+            .disabled(paginate_info.prev_page_offset.is_none());
+
+            let next_btn = CreateButton::new(ButtonCustomId::Paginate(
+                PaginateId::RecentlyPlayedNextPage(paginate_info.next_page_offset.unwrap_or(0)),
+            ))
+            .disabled(paginate_info.next_page_offset.is_none());
+
+            (first_btn, prev_btn, next_btn, last_btn)
+        }
+        DisplayType::Trending => {
+            let first_btn = CreateButton::new(ButtonCustomId::Paginate(
+                PaginateId::TrendingFirstPage(paginate_info.first_page_offset.unwrap_or(0)),
+            ))
+            .disabled(paginate_info.first_page_offset.is_none());
+
+            let last_btn = CreateButton::new(ButtonCustomId::Paginate(
+                PaginateId::TrendingLastPage(paginate_info.last_page_offset.unwrap_or(0)),
+            ))
+            .disabled(paginate_info.last_page_offset.is_none());
+
+            let prev_btn = CreateButton::new(ButtonCustomId::Paginate(
+                PaginateId::TrendingPrevPage(paginate_info.prev_page_offset.unwrap_or(0)),
+            ))
+            .disabled(paginate_info.prev_page_offset.is_none());
+
+            let next_btn = CreateButton::new(ButtonCustomId::Paginate(
+                PaginateId::TrendingNextPage(paginate_info.next_page_offset.unwrap_or(0)),
+            ))
+            .disabled(paginate_info.next_page_offset.is_none());
+
+            (first_btn, prev_btn, next_btn, last_btn)
+        }
+        DisplayType::Favorites => {
+            let first_btn = CreateButton::new(ButtonCustomId::Paginate(
+                PaginateId::FavoritesFirstPage(paginate_info.first_page_offset.unwrap_or(0)),
+            ))
+            .disabled(paginate_info.first_page_offset.is_none());
+
+            let last_btn = CreateButton::new(ButtonCustomId::Paginate(
+                PaginateId::FavoritesLastPage(paginate_info.last_page_offset.unwrap_or(0)),
+            ))
+            .disabled(paginate_info.last_page_offset.is_none());
+
+            let prev_btn = CreateButton::new(ButtonCustomId::Paginate(
+                PaginateId::FavoritesPrevPage(paginate_info.prev_page_offset.unwrap_or(0)),
+            ))
+            .disabled(paginate_info.prev_page_offset.is_none());
+
+            let next_btn = CreateButton::new(ButtonCustomId::Paginate(
+                PaginateId::FavoritesNextPage(paginate_info.next_page_offset.unwrap_or(0)),
+            ))
+            .disabled(paginate_info.next_page_offset.is_none());
+
+            (first_btn, prev_btn, next_btn, last_btn)
+        }
         DisplayType::Pinned => {
             let first_btn = CreateButton::new(ButtonCustomId::Paginate(
                 PaginateId::PinnedFirstPage(paginate_info.first_page_offset.unwrap_or(0)),
@@ -822,6 +1623,64 @@ pub fn make_paginate_controls(
             )))
             .disabled(paginate_info.next_page_offset.is_none());
 
+            (first_btn, prev_btn, next_btn, last_btn)
+        }
+        DisplayType::Collection => {
+            let name = search.clone().unwrap_or_default();
+
+            let first_btn =
+                CreateButton::new(ButtonCustomId::Paginate(PaginateId::CollectionFirstPage(
+                    paginate_info.first_page_offset.unwrap_or(0),
+                    name.clone(),
+                )))
+                .disabled(paginate_info.first_page_offset.is_none());
+
+            let last_btn =
+                CreateButton::new(ButtonCustomId::Paginate(PaginateId::CollectionLastPage(
+                    paginate_info.last_page_offset.unwrap_or(0),
+                    name.clone(),
+                )))
+                .disabled(paginate_info.last_page_offset.is_none());
+
+            let prev_btn =
+                CreateButton::new(ButtonCustomId::Paginate(PaginateId::CollectionPrevPage(
+                    paginate_info.prev_page_offset.unwrap_or(0),
+                    name.clone(),
+                )))
+                .disabled(paginate_info.prev_page_offset.is_none());
+
+            let next_btn = CreateButton::new(ButtonCustomId::Paginate(
+                PaginateId::CollectionNextPage(paginate_info.next_page_offset.unwrap_or(0), name),
+            ))
+            .disabled(paginate_info.next_page_offset.is_none());
+
+            (first_btn, prev_btn, next_btn, last_btn)
+        }
+        DisplayType::Tag(tag_id) => {
+            let first_btn = CreateButton::new(ButtonCustomId::Paginate(PaginateId::TagFirstPage(
+                paginate_info.first_page_offset.unwrap_or(0),
+                tag_id,
+            )))
+            .disabled(paginate_info.first_page_offset.is_none());
+
+            let last_btn = CreateButton::new(ButtonCustomId::Paginate(PaginateId::TagLastPage(
+                paginate_info.last_page_offset.unwrap_or(0),
+                tag_id,
+            )))
+            .disabled(paginate_info.last_page_offset.is_none());
+
+            let prev_btn = CreateButton::new(ButtonCustomId::Paginate(PaginateId::TagPrevPage(
+                paginate_info.prev_page_offset.unwrap_or(0),
+                tag_id,
+            )))
+            .disabled(paginate_info.prev_page_offset.is_none());
+
+            let next_btn = CreateButton::new(ButtonCustomId::Paginate(PaginateId::TagNextPage(
+                paginate_info.next_page_offset.unwrap_or(0),
+                tag_id,
+            )))
+            .disabled(paginate_info.next_page_offset.is_none());
+
             (first_btn, prev_btn, next_btn, last_btn)
         }
     };
@@ -839,7 +1698,10 @@ pub fn make_paginate_controls(
         .style(serenity::all::ButtonStyle::Secondary)
         .emoji(ReactionType::Unicode("⏭️".into()));
 
-    CreateActionRow::Buttons(vec![first_btn, prev_btn, next_btn, last_btn])
+    let mut buttons = vec![first_btn, prev_btn, next_btn, last_btn];
+    buttons.extend(play_collection_btn);
+
+    CreateActionRow::Buttons(buttons)
 }
 
 pub fn make_display_title(
@@ -858,6 +1720,27 @@ pub fn make_display_title(
         DisplayType::RecentlyAdded => {
             format!("### Recently Added Sounds (page {cur_page} of {total_pages})...")
         }
+        DisplayType::RecentlyPlayed => {
+            format!("### Recently Played Sounds (page {cur_page} of {total_pages})...")
+        }
+        DisplayType::Trending => {
+            format!("### Trending Sounds (page {cur_page} of {total_pages})...")
+        }
+        DisplayType::Favorites => {
+            format!("### My Favorites (page {cur_page} of {total_pages})...")
+        }
+        DisplayType::Collection => {
+            format!(
+                "### Collection `{}` (page {cur_page} of {total_pages})...",
+                search.unwrap_or_default()
+            )
+        }
+        DisplayType::Tag(_) => {
+            format!(
+                "### Sounds Tagged `{}` (page {cur_page} of {total_pages})...",
+                search.unwrap_or_default()
+            )
+        }
         DisplayType::Search => {
             format!(
                 "### Search Results `{}` (page {cur_page} of {total_pages})...",
@@ -869,3 +1752,133 @@ pub fn make_display_title(
         }
     }
 }
+
+/// Records entry in audit log, and mirrors it to the guild's audit log channel if it has one.
+/// Failures are logged, never returned, so auditing can't break the change being audited
+pub async fn write_audit_log(http: &Http, data: &UserData, entry: AuditLogEntry) {
+    data.audit_log_table().insert_entry(&entry).ok();
+
+    let channel_id = data
+        .guild_settings_table()
+        .get_guild_settings(entry.guild_id)
+        .ok()
+        .and_then(|settings| settings.audit_log_channel_id);
+
+    if let Some(channel_id) = channel_id {
+        let line = format_audit_log_line(
+            &chrono::Utc::now(),
+            entry.actor_id,
+            entry.action,
+            &entry.target,
+            entry.before.as_deref(),
+            entry.after.as_deref(),
+        );
+
+        ChannelId::new(channel_id)
+            .send_message(
+                http,
+                CreateMessage::new()
+                    .content(line)
+                    .allowed_mentions(CreateAllowedMentions::new()),
+            )
+            .await
+            .log_err_msg("Failed mirroring audit log entry to audit log channel")
+            .ok();
+    }
+}
+
+pub fn format_audit_log_line(
+    created_at: &chrono::DateTime<chrono::Utc>,
+    actor_id: u64,
+    action: AuditAction,
+    target: &str,
+    before: Option<&str>,
+    after: Option<&str>,
+) -> String {
+    let before = before.map(truncate_audit_log_value);
+    let after = after.map(truncate_audit_log_value);
+    let change = match (before, after) {
+        (Some(before), Some(after)) => format!(" `{before}` → `{after}`"),
+        (None, Some(after)) => format!(" → `{after}`"),
+        (Some(before), None) => format!(" `{before}` → none"),
+        (None, None) => String::new(),
+    };
+
+    format!(
+        "<t:{}:f> <@{actor_id}> **{}** `{target}`{change}",
+        created_at.timestamp(),
+        action.name()
+    )
+}
+
+/// Shortens values so a page of audit log entries fits in a message
+fn truncate_audit_log_value(value: &str) -> String {
+    if value.chars().count() > vars::AUDIT_LOG_VALUE_MAX_LEN {
+        let value: String = value
+            .chars()
+            .take(vars::AUDIT_LOG_VALUE_MAX_LEN - 3)
+            .collect();
+        format!("{value}...")
+    } else {
+        value.to_string()
+    }
+}
+
+pub fn make_audit_log_message(
+    data: &UserData,
+    filter: &AuditLogFilter,
+    offset: u64,
+) -> Result<SoundDisplayMessage, PoiseError> {
+    let page_size = vars::AUDIT_LOG_PAGE_SIZE;
+    let table = data.audit_log_table();
+    let total = table.count_entries(filter)?;
+    let rows = table.list_entries(filter, page_size, offset)?;
+
+    let total_pages = total.div_ceil(page_size);
+    let cur_page = if total > 0 { offset / page_size + 1 } else { 0 };
+
+    let mut content = format!("### Audit Log (page {cur_page} of {total_pages})...\n");
+    if rows.is_empty() {
+        content.push_str("No audit log entries found");
+    }
+
+    for row in rows.iter() {
+        let line = format_audit_log_line(
+            &row.created_at,
+            row.actor_id,
+            row.action,
+            &row.target,
+            row.before.as_deref(),
+            row.after.as_deref(),
+        );
+
+        // discord message content limit
+        if content.len() + line.len() + 1 > 2000 {
+            break;
+        }
+        content.push_str(&line);
+        content.push('\n');
+    }
+
+    let page_btn_fn = |offset: u64| {
+        CreateButton::new(ButtonCustomId::AuditLogPage(AuditLogPageId {
+            offset,
+            action: filter.action,
+            actor_id: filter.actor_id,
+        }))
+        .style(serenity::all::ButtonStyle::Secondary)
+    };
+
+    let prev_btn = page_btn_fn(offset.saturating_sub(page_size))
+        .emoji(ReactionType::Unicode("◀️".into()))
+        .disabled(offset == 0);
+    let next_btn = page_btn_fn(offset + page_size)
+        .emoji(ReactionType::Unicode("▶️".into()))
+        .disabled(offset + page_size >= total);
+
+    Ok(SoundDisplayMessage::new(
+        content,
+        vec![CreateActionRow::Buttons(vec![prev_btn, next_btn])],
+        true,
+    ))
+}
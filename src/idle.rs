@@ -0,0 +1,104 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use serenity::{all::GuildId, async_trait};
+use songbird::{Event, EventContext, EventHandler as VoiceEventHandler};
+
+/// Last time each guild's voice call joined or played a track. Used to leave calls left idle
+#[derive(Debug, Clone, Default)]
+pub struct PlaybackActivity(Arc<Mutex<HashMap<GuildId, chrono::DateTime<chrono::Utc>>>>);
+
+impl PlaybackActivity {
+    pub fn touch(&self, guild_id: GuildId, at: chrono::DateTime<chrono::Utc>) {
+        self.0
+            .lock()
+            .expect("Playback activity lock poisoned")
+            .insert(guild_id, at);
+    }
+
+    pub fn remove(&self, guild_id: GuildId) {
+        self.0
+            .lock()
+            .expect("Playback activity lock poisoned")
+            .remove(&guild_id);
+    }
+
+    pub fn guild_ids(&self) -> Vec<GuildId> {
+        self.0
+            .lock()
+            .expect("Playback activity lock poisoned")
+            .keys()
+            .copied()
+            .collect()
+    }
+
+    /// True if guild's call has had no activity for `timeout` as of `now`.
+    /// Guilds without any recorded activity aren't idle, since they aren't in a call
+    pub fn is_idle(
+        &self,
+        guild_id: GuildId,
+        timeout: chrono::Duration,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> bool {
+        match self
+            .0
+            .lock()
+            .expect("Playback activity lock poisoned")
+            .get(&guild_id)
+        {
+            Some(last_activity) => now - *last_activity >= timeout,
+            None => false,
+        }
+    }
+
+    /// Voice event handler recording guild activity whenever its call plays a track
+    pub fn notifier(&self, guild_id: GuildId) -> PlaybackActivityNotifier {
+        PlaybackActivityNotifier {
+            guild_id,
+            activity: self.clone(),
+        }
+    }
+}
+
+pub struct PlaybackActivityNotifier {
+    guild_id: GuildId,
+    activity: PlaybackActivity,
+}
+
+#[async_trait]
+impl VoiceEventHandler for PlaybackActivityNotifier {
+    async fn act(&self, _ctx: &EventContext<'_>) -> Option<Event> {
+        self.activity.touch(self.guild_id, chrono::Utc::now());
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_idle_test() {
+        let activity = PlaybackActivity::default();
+        let guild_id = GuildId::new(1);
+        let timeout = chrono::Duration::minutes(10);
+        let now = chrono::Utc::now();
+
+        assert!(!activity.is_idle(guild_id, timeout, now));
+
+        activity.touch(guild_id, now - chrono::Duration::minutes(5));
+        assert!(!activity.is_idle(guild_id, timeout, now));
+        assert!(activity.is_idle(guild_id, timeout, now + chrono::Duration::minutes(5)));
+
+        // playing again resets the timeout
+        activity.touch(guild_id, now);
+        assert!(!activity.is_idle(guild_id, timeout, now + chrono::Duration::minutes(5)));
+
+        assert_eq!(activity.guild_ids(), vec![guild_id]);
+        activity.remove(guild_id);
+        assert!(activity.guild_ids().is_empty());
+        assert!(!activity.is_idle(guild_id, timeout, now + chrono::Duration::hours(1)));
+    }
+}
@@ -13,13 +13,23 @@ use serenity::{
 use songbird::SerenityInit;
 
 mod audio;
+mod charts;
 mod commands;
 mod common;
 mod config;
 mod db;
 mod errors;
 mod event_handlers;
+mod fuzzy;
 mod helpers;
+mod idle;
+mod popularity;
+mod quotas;
+mod scheduler;
+mod sound_info;
+mod stats;
+mod tasks;
+mod triggers;
 mod vars;
 
 use crate::commands::PoiseError;
@@ -38,6 +48,8 @@ async fn main() -> anyhow::Result<()> {
     // framework configuration
     let token = config.token.clone();
     let cmd_prefix = config.command_prefix.clone();
+    let enable_prefix_commands = config.enable_prefix_commands;
+    let enable_text_triggers = config.enable_text_triggers;
     let application_id = config.application_id;
     let sqlite_db_file = config.sqlite_db_file.clone();
     let db_manager = SqliteConnectionManager::file(sqlite_db_file);
@@ -48,7 +60,8 @@ async fn main() -> anyhow::Result<()> {
         poise::Framework::<UserData, PoiseError>::builder()
             .options(poise::FrameworkOptions {
                 prefix_options: poise::PrefixFrameworkOptions {
-                    prefix: Some(cmd_prefix),
+                    prefix: enable_prefix_commands.then_some(cmd_prefix),
+                    mention_as_prefix: enable_prefix_commands,
                     ..Default::default()
                 },
                 commands: vec![
@@ -56,6 +69,19 @@ async fn main() -> anyhow::Result<()> {
                     commands::leave(),
                     commands::sounds(),
                     commands::play(),
+                    commands::audit(),
+                    commands::audit_channel(),
+                    commands::stats(),
+                    commands::collections(),
+                    commands::tags(),
+                    commands::intro(),
+                    commands::outro(),
+                    commands::auto_join(),
+                    commands::idle_timeout(),
+                    commands::move_to(),
+                    commands::schedule(),
+                    commands::triggers(),
+                    commands::panel(),
                     commands::register(),
                 ],
                 event_handler: |ctx, event, framework, data| {
@@ -63,21 +89,32 @@ async fn main() -> anyhow::Result<()> {
                 },
                 ..Default::default()
             })
-            .setup(|_ctx, _ready, _framework| {
+            .setup(move |ctx, _ready, framework| {
                 Box::pin(async move {
+                    if !enable_prefix_commands {
+                        // no prefix `register` command to register them with
+                        log::info!("Registering application commands globally...");
+                        poise::builtins::register_globally(ctx, &framework.options().commands)
+                            .await?;
+                    }
+
                     Ok(UserData {
                         config: config,
                         db_pool: db_pool,
+                        playback_activity: Default::default(),
+                        trigger_regex_cache: Default::default(),
                     })
                 })
             })
             .build();
 
     // client setup
-    let intents = GatewayIntents::non_privileged()
-        | GatewayIntents::MESSAGE_CONTENT
+    let mut intents = GatewayIntents::non_privileged()
         | GatewayIntents::GUILD_VOICE_STATES
         | GatewayIntents::GUILDS;
+    if enable_prefix_commands || enable_text_triggers {
+        intents |= GatewayIntents::MESSAGE_CONTENT;
+    }
 
     log::info!("Creating client...");
     let mut client = Client::builder(&token, intents)
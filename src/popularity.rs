@@ -0,0 +1,161 @@
+use std::collections::HashMap;
+
+use crate::{
+    commands::PoiseError,
+    db::{AudioTable, PlayHistoryTable},
+    vars,
+};
+
+/// Score of a single play. Worth 1 when played `now`, halving every half life after
+pub fn play_score(
+    played_at: chrono::DateTime<chrono::Utc>,
+    now: chrono::DateTime<chrono::Utc>,
+) -> f64 {
+    let age_secs = (now - played_at).num_seconds().max(0) as f64;
+    let half_life_secs = vars::POPULARITY_HALF_LIFE_DAYS * 24.0 * 60.0 * 60.0;
+
+    0.5_f64.powf(age_secs / half_life_secs)
+}
+
+/// Sums decayed play scores per audio track id
+pub fn popularity_scores(
+    plays: &[(i64, chrono::DateTime<chrono::Utc>)],
+    now: chrono::DateTime<chrono::Utc>,
+) -> HashMap<i64, f64> {
+    let mut scores = HashMap::new();
+
+    for (audio_id, played_at) in plays.iter() {
+        *scores.entry(*audio_id).or_insert(0.0) += play_score(*played_at, now);
+    }
+
+    scores
+}
+
+/// Oldest play that still counts towards popularity. Older plays have decayed to ~nothing
+fn popularity_window_start(now: chrono::DateTime<chrono::Utc>) -> chrono::DateTime<chrono::Utc> {
+    now - chrono::Duration::days(vars::POPULARITY_WINDOW_DAYS)
+}
+
+/// Recomputes popularity of every audio track from play history
+pub fn refresh_popularity(
+    audio_table: &AudioTable,
+    history_table: &PlayHistoryTable,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Result<usize, PoiseError> {
+    let plays = history_table.list_plays_since(popularity_window_start(now), None)?;
+    let scores = popularity_scores(&plays, now);
+
+    audio_table.update_popularity(&scores)?;
+    Ok(scores.len())
+}
+
+/// Recomputes popularity of a single audio track from play history
+pub fn refresh_track_popularity(
+    audio_table: &AudioTable,
+    history_table: &PlayHistoryTable,
+    audio_id: i64,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Result<(), PoiseError> {
+    let plays = history_table.list_plays_since(popularity_window_start(now), Some(audio_id))?;
+    let score = popularity_scores(&plays, now)
+        .remove(&audio_id)
+        .unwrap_or(0.0);
+
+    audio_table.update_audio_row_popularity(audio_id, score)
+}
+
+#[cfg(test)]
+mod tests {
+    use r2d2_sqlite::SqliteConnectionManager;
+
+    use crate::{
+        audio::AudioFile,
+        db::{
+            audio_table::AudioTableRowInsertBuilder, PlayHistoryEntry, PlaySource, Table,
+            UniqueAudioTableCol,
+        },
+        helpers::uuid_v4_str,
+    };
+
+    use super::*;
+
+    fn days_ago(now: chrono::DateTime<chrono::Utc>, days: f64) -> chrono::DateTime<chrono::Utc> {
+        now - chrono::Duration::seconds((days * 24.0 * 60.0 * 60.0) as i64)
+    }
+
+    #[test]
+    fn play_score_test() {
+        let now = chrono::Utc::now();
+        let half_life = vars::POPULARITY_HALF_LIFE_DAYS;
+
+        assert_eq!(play_score(now, now), 1.0);
+        assert!((play_score(days_ago(now, half_life), now) - 0.5).abs() < 1e-6);
+        assert!((play_score(days_ago(now, half_life * 2.0), now) - 0.25).abs() < 1e-6);
+
+        // plays in the future don't count extra
+        assert_eq!(play_score(now + chrono::Duration::hours(1), now), 1.0);
+    }
+
+    #[test]
+    fn popularity_scores_test() {
+        let now = chrono::Utc::now();
+        let half_life = vars::POPULARITY_HALF_LIFE_DAYS;
+
+        // many old plays lose to a few recent ones
+        let mut plays = vec![];
+        plays.extend((0..10).map(|_| (1, days_ago(now, half_life * 5.0))));
+        plays.extend((0..3).map(|_| (2, days_ago(now, 0.5))));
+
+        let scores = popularity_scores(&plays, now);
+        assert_eq!(scores.len(), 2);
+        assert!(scores[&2] > scores[&1]);
+        assert!((scores[&1] - 10.0 / 32.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn refresh_popularity_test() {
+        let db_manager = SqliteConnectionManager::memory();
+        let db_pool = r2d2::Pool::new(db_manager).unwrap();
+        let audio_table = AudioTable::new(db_pool.get().unwrap());
+        let history_table = PlayHistoryTable::new(db_pool.get().unwrap());
+        audio_table.create_table();
+        history_table.create_table();
+
+        for name in ["first", "second"] {
+            let audio_file = AudioFile::new(format!("/tmp/{}.mp3", uuid_v4_str()).into());
+            audio_table
+                .insert_audio_row(AudioTableRowInsertBuilder::new(name, audio_file).build())
+                .unwrap();
+        }
+
+        let now = chrono::Utc::now();
+        let old_play = days_ago(now, vars::POPULARITY_WINDOW_DAYS as f64 + 1.0);
+        history_table
+            .insert_entry_at(&PlayHistoryEntry::new(1, 1u64, PlaySource::Slash), old_play)
+            .unwrap();
+        history_table
+            .insert_entry_at(&PlayHistoryEntry::new(2, 1u64, PlaySource::Slash), now)
+            .unwrap();
+
+        let popularity_fn = |name: &str| {
+            audio_table
+                .find_audio_row(UniqueAudioTableCol::Name(name.into()))
+                .unwrap()
+                .popularity
+        };
+
+        audio_table.update_audio_row_popularity(1, 5.0).unwrap();
+        assert_eq!(
+            refresh_popularity(&audio_table, &history_table, now).unwrap(),
+            1
+        );
+        assert_eq!(popularity_fn("first"), 0.0);
+        assert_eq!(popularity_fn("second"), 1.0);
+
+        history_table
+            .insert_entry_at(&PlayHistoryEntry::new(1, 1u64, PlaySource::Button), now)
+            .unwrap();
+        refresh_track_popularity(&audio_table, &history_table, 1, now).unwrap();
+        assert_eq!(popularity_fn("first"), 1.0);
+    }
+}
@@ -0,0 +1,129 @@
+use crate::{commands::PoiseError, config::Config, db::AudioTable, errors::QuotaError};
+
+/// Current upload usage of a user within a guild
+pub struct QuotaUsage {
+    pub user_sound_count: u64,
+    pub guild_library_size: u64,
+    pub user_uploads_today: u64,
+}
+
+impl QuotaUsage {
+    pub fn fetch(
+        audio_table: &AudioTable,
+        guild_id: u64,
+        user_id: u64,
+    ) -> Result<Self, PoiseError> {
+        let since = chrono::Utc::now() - chrono::Duration::days(1);
+
+        Ok(Self {
+            user_sound_count: audio_table.count_audio_rows_by_author(guild_id, user_id)?,
+            guild_library_size: audio_table.guild_library_size(guild_id)?,
+            user_uploads_today: audio_table
+                .count_audio_rows_by_author_since(guild_id, user_id, since)?,
+        })
+    }
+
+    /// Checks usage against configured quotas.
+    /// `incoming_bytes` is the size of the audio file about to be added, or 0 if not yet known
+    pub fn check(&self, config: &Config, incoming_bytes: u64) -> Result<(), QuotaError> {
+        if let Some(max) = config.max_sounds_per_user {
+            if self.user_sound_count >= max {
+                return Err(QuotaError::SoundsPerUser { max });
+            }
+        }
+
+        if let Some(max) = config.max_uploads_per_day {
+            if self.user_uploads_today >= max {
+                return Err(QuotaError::UploadsPerDay { max });
+            }
+        }
+
+        if let Some(max) = config.max_library_bytes_per_guild {
+            // with an unknown incoming size, a library already at its limit is full
+            let library_size = self.guild_library_size + incoming_bytes;
+            if library_size > max || (incoming_bytes == 0 && library_size >= max) {
+                return Err(QuotaError::LibrarySize { max });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Format quota usage against optional limit. ie: `3 / 10` or `3 / unlimited`
+pub fn format_usage(used: impl ToString, limit: Option<impl ToString>) -> String {
+    let limit = limit
+        .map(|val| val.to_string())
+        .unwrap_or("unlimited".into());
+
+    format!("{} / {limit}", used.to_string())
+}
+
+/// Human readable byte size. ie: `1.50 MB`
+pub fn format_bytes(bytes: u64) -> String {
+    const KB: f64 = 1_000.0;
+    const MB: f64 = KB * 1_000.0;
+    const GB: f64 = MB * 1_000.0;
+
+    let bytes_f = bytes as f64;
+    if bytes_f >= GB {
+        format!("{:.2} GB", bytes_f / GB)
+    } else if bytes_f >= MB {
+        format!("{:.2} MB", bytes_f / MB)
+    } else if bytes_f >= KB {
+        format!("{:.2} KB", bytes_f / KB)
+    } else {
+        format!("{bytes} B")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_usage(sounds: u64, bytes: u64, today: u64) -> QuotaUsage {
+        QuotaUsage {
+            user_sound_count: sounds,
+            guild_library_size: bytes,
+            user_uploads_today: today,
+        }
+    }
+
+    #[test]
+    fn quota_check_test() {
+        let mut config = Config::default();
+        assert!(make_usage(100, 100_000_000, 100).check(&config, 0).is_ok());
+
+        config.max_sounds_per_user = Some(3);
+        config.max_uploads_per_day = Some(2);
+        config.max_library_bytes_per_guild = Some(1_000);
+
+        assert!(make_usage(2, 500, 1).check(&config, 0).is_ok());
+
+        assert!(matches!(
+            make_usage(3, 500, 1).check(&config, 0),
+            Err(QuotaError::SoundsPerUser { max: 3 })
+        ));
+        assert!(matches!(
+            make_usage(2, 500, 2).check(&config, 0),
+            Err(QuotaError::UploadsPerDay { max: 2 })
+        ));
+        assert!(matches!(
+            make_usage(2, 1_000, 1).check(&config, 0),
+            Err(QuotaError::LibrarySize { max: 1_000 })
+        ));
+        assert!(matches!(
+            make_usage(2, 500, 1).check(&config, 600),
+            Err(QuotaError::LibrarySize { max: 1_000 })
+        ));
+    }
+
+    #[test]
+    fn format_test() {
+        assert_eq!(format_bytes(999), "999 B");
+        assert_eq!(format_bytes(1_500), "1.50 KB");
+        assert_eq!(format_bytes(2_000_000), "2.00 MB");
+        assert_eq!(format_usage(3, Some(10)), "3 / 10");
+        assert_eq!(format_usage(3, None::<u64>), "3 / unlimited");
+    }
+}
@@ -0,0 +1,275 @@
+use std::str::FromStr;
+
+use crate::{commands::PoiseError, db::ScheduleRow};
+
+/// Source of the current time, so scheduling can be tested without waiting on the real clock
+pub trait Clock: Send + Sync {
+    fn now(&self) -> chrono::DateTime<chrono::Utc>;
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> chrono::DateTime<chrono::Utc> {
+        chrono::Utc::now()
+    }
+}
+
+/// Parses 5 field cron expression `minute hour day-of-month month day-of-week`, in UTC.
+/// ie: `0 * * * *` every hour, `0 21 * * FRI` 9pm on Fridays
+pub fn parse_cron(expr: impl AsRef<str>) -> Result<cron::Schedule, PoiseError> {
+    let expr = expr.as_ref().trim();
+    let fields: Vec<_> = expr.split_whitespace().collect();
+    if fields.len() != 5 {
+        return Err(format!(
+            "Cron expression `{expr}` must have 5 fields: minute hour day-of-month month day-of-week"
+        )
+        .into());
+    }
+
+    let day_of_week = map_day_of_week(fields[4])
+        .map_err(|err| format!("Invalid cron expression `{expr}` - {err}"))?;
+
+    // cron crate expects a leading seconds field
+    cron::Schedule::from_str(&format!("0 {} {day_of_week}", fields[..4].join(" ")))
+        .map_err(|err| format!("Invalid cron expression `{expr}` - {err}").into())
+}
+
+/// Maps standard cron day-of-week numbers, 0-7 with Sunday as 0 & 7, to the cron crate's 1-7 with Sunday as 1.
+/// Day names & steps are left as is
+fn map_day_of_week(field: &str) -> Result<String, String> {
+    let day = |day: &str| match day.parse::<u32>() {
+        Ok(7) => Ok(1),
+        Ok(day) if day < 7 => Ok(day + 1),
+        _ => Err(format!("Day-of-week `{day}` must be 0-7 or a day name")),
+    };
+
+    field
+        .split(',')
+        .map(|item| {
+            let (days, step) = match item.split_once('/') {
+                Some((days, step)) => (days, Some(step)),
+                None => (item, None),
+            };
+
+            let days = match days.split_once('-') {
+                _ if days == "*" || days.chars().any(|c| c.is_alphabetic()) => days.to_string(),
+                Some((start, end)) => match (day(start)?, end, step) {
+                    // Sunday as 7 would wrap around to the start of the week
+                    (1, "7", None) => "1-7".to_string(),
+                    (start, "7", None) => format!("{start}-7,1"),
+                    (_, "7", Some(_)) => {
+                        return Err(
+                            "Day-of-week ranges with steps must end at 6. ie: `0-6/2`".to_string()
+                        )
+                    }
+                    (start, end, _) => format!("{start}-{}", day(end)?),
+                },
+                None => day(days)?.to_string(),
+            };
+
+            Ok(match step {
+                Some(step) => format!("{days}/{step}"),
+                None => days,
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(|items| items.join(","))
+}
+
+/// Next time cron expression is due after `after`
+pub fn next_run(
+    expr: impl AsRef<str>,
+    after: chrono::DateTime<chrono::Utc>,
+) -> Result<Option<chrono::DateTime<chrono::Utc>>, PoiseError> {
+    Ok(parse_cron(expr)?.after(&after).next())
+}
+
+/// Finds schedules due since it last ticked
+pub struct Scheduler<C: Clock> {
+    clock: C,
+    last_tick: chrono::DateTime<chrono::Utc>,
+}
+
+impl<C: Clock> Scheduler<C> {
+    pub fn new(clock: C) -> Self {
+        let last_tick = clock.now();
+        Self { clock, last_tick }
+    }
+
+    /// Schedules due between the last tick and now. Schedules due several times since the last tick are
+    /// only returned once, so a stalled scheduler doesn't replay a backlog of sounds
+    pub fn tick<'a>(&mut self, schedules: &'a [ScheduleRow]) -> Vec<&'a ScheduleRow> {
+        let now = self.clock.now();
+        let since = self.last_tick;
+        self.last_tick = now;
+
+        schedules
+            .iter()
+            .filter(|schedule| match next_run(&schedule.cron, since) {
+                Ok(Some(next_run)) => next_run <= now,
+                Ok(None) => false,
+                Err(err) => {
+                    log::error!("Schedule {} has invalid cron - {err}", schedule.id);
+                    false
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[derive(Clone)]
+    struct MockClock(Arc<Mutex<chrono::DateTime<chrono::Utc>>>);
+
+    impl MockClock {
+        fn new(now: chrono::DateTime<chrono::Utc>) -> Self {
+            Self(Arc::new(Mutex::new(now)))
+        }
+
+        fn advance(&self, duration: chrono::Duration) {
+            *self.0.lock().unwrap() += duration;
+        }
+    }
+
+    impl Clock for MockClock {
+        fn now(&self) -> chrono::DateTime<chrono::Utc> {
+            *self.0.lock().unwrap()
+        }
+    }
+
+    fn schedule_row(id: i64, cron: &str) -> ScheduleRow {
+        ScheduleRow {
+            id,
+            guild_id: 1,
+            audio_id: 1,
+            cron: cron.into(),
+            channel_id: None,
+            author_id: None,
+            created_at: chrono::Utc::now(),
+        }
+    }
+
+    fn due_ids(scheduler: &mut Scheduler<MockClock>, schedules: &[ScheduleRow]) -> Vec<i64> {
+        scheduler
+            .tick(schedules)
+            .iter()
+            .map(|schedule| schedule.id)
+            .collect()
+    }
+
+    #[test]
+    fn parse_cron_test() {
+        assert!(parse_cron("0 * * * *").is_ok());
+        assert!(parse_cron("0 21 * * FRI").is_ok());
+        assert!(parse_cron("*/15 9-17 * * MON-FRI").is_ok());
+
+        assert!(parse_cron("").is_err());
+        assert!(parse_cron("0 * * *").is_err());
+        assert!(parse_cron("0 0 * * * *").is_err());
+        assert!(parse_cron("61 * * * *").is_err());
+        assert!(parse_cron("hourly please").is_err());
+        assert!(parse_cron("0 0 * * 8").is_err());
+
+        // 2024-01-05 is a Friday
+        let after = chrono::Utc.with_ymd_and_hms(2024, 1, 1, 12, 30, 0).unwrap();
+        assert_eq!(
+            next_run("0 21 * * FRI", after).unwrap(),
+            Some(chrono::Utc.with_ymd_and_hms(2024, 1, 5, 21, 0, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn parse_cron_day_of_week_numbers_test() {
+        // 2024-01-01 is a Monday
+        let after = chrono::Utc.with_ymd_and_hms(2024, 1, 1, 12, 30, 0).unwrap();
+        let next_day = |expr: &str| {
+            next_run(expr, after)
+                .unwrap()
+                .unwrap()
+                .format("%a")
+                .to_string()
+        };
+        let next_days = |expr: &str| {
+            parse_cron(expr)
+                .unwrap()
+                .after(&after)
+                .take(7)
+                .map(|run| run.format("%a").to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            next_run("0 21 * * 5", after).unwrap(),
+            Some(chrono::Utc.with_ymd_and_hms(2024, 1, 5, 21, 0, 0).unwrap())
+        );
+        assert_eq!(next_day("0 21 * * 0"), "Sun");
+        assert_eq!(next_day("0 21 * * 7"), "Sun");
+        assert_eq!(next_day("0 21 * * 6"), "Sat");
+        assert_eq!(
+            next_days("0 21 * * 1-5"),
+            ["Mon", "Tue", "Wed", "Thu", "Fri", "Mon", "Tue"]
+        );
+        assert_eq!(
+            next_days("0 21 * * 5-7"),
+            ["Fri", "Sat", "Sun", "Fri", "Sat", "Sun", "Fri"]
+        );
+        assert_eq!(
+            next_days("0 21 * * 0,3"),
+            ["Wed", "Sun", "Wed", "Sun", "Wed", "Sun", "Wed"]
+        );
+        assert_eq!(
+            next_days("0 21 * * 0-6/2"),
+            ["Tue", "Thu", "Sat", "Sun", "Tue", "Thu", "Sat"]
+        );
+        assert_eq!(
+            next_days("0 21 * * SAT,SUN"),
+            ["Sat", "Sun", "Sat", "Sun", "Sat", "Sun", "Sat"]
+        );
+        assert!(parse_cron("0 21 * * 1-7/2").is_err());
+    }
+
+    #[test]
+    fn scheduler_tick_test() {
+        let clock = MockClock::new(
+            chrono::Utc
+                .with_ymd_and_hms(2024, 1, 5, 20, 59, 50)
+                .unwrap(),
+        );
+        let mut scheduler = Scheduler::new(clock.clone());
+        let schedules = vec![
+            schedule_row(1, "0 * * * *"),
+            schedule_row(2, "0 21 * * FRI"),
+            schedule_row(3, "30 * * * *"),
+            schedule_row(4, "not a cron"),
+        ];
+
+        assert!(due_ids(&mut scheduler, &schedules).is_empty());
+
+        clock.advance(chrono::Duration::seconds(5));
+        assert!(due_ids(&mut scheduler, &schedules).is_empty());
+
+        // 21:00:05
+        clock.advance(chrono::Duration::seconds(10));
+        assert_eq!(due_ids(&mut scheduler, &schedules), vec![1, 2]);
+
+        // not due again on the next tick
+        clock.advance(chrono::Duration::seconds(15));
+        assert!(due_ids(&mut scheduler, &schedules).is_empty());
+
+        // 21:30:20
+        clock.advance(chrono::Duration::minutes(30));
+        assert_eq!(due_ids(&mut scheduler, &schedules), vec![3]);
+
+        // due many times while stalled, only returned once
+        clock.advance(chrono::Duration::hours(5));
+        assert_eq!(due_ids(&mut scheduler, &schedules), vec![1, 3]);
+    }
+}
@@ -0,0 +1,175 @@
+use poise::CreateReply;
+use serenity::all::{
+    CreateActionRow, CreateAttachment, CreateButton, CreateEmbed, CreateEmbedFooter,
+    CreateInteractionResponseMessage, ReactionType,
+};
+
+use crate::{
+    commands::PoiseError, common::LogResult, db::AudioTableRow, helpers::ButtonCustomId, quotas,
+};
+
+pub const WAVEFORM_FILENAME: &str = "waveform.png";
+
+/// Details of a single audio track, with its waveform preview
+pub struct SoundInfoMessage {
+    embed: CreateEmbed,
+    waveform: Option<CreateAttachment>,
+    components: Vec<CreateActionRow>,
+}
+
+impl From<SoundInfoMessage> for CreateReply {
+    fn from(value: SoundInfoMessage) -> Self {
+        let reply = CreateReply::default()
+            .embed(value.embed)
+            .components(value.components);
+
+        match value.waveform {
+            Some(waveform) => reply.attachment(waveform),
+            None => reply,
+        }
+    }
+}
+
+impl From<SoundInfoMessage> for CreateInteractionResponseMessage {
+    fn from(value: SoundInfoMessage) -> Self {
+        CreateInteractionResponseMessage::new()
+            .embed(value.embed)
+            .files(value.waveform)
+            .components(value.components)
+    }
+}
+
+pub fn make_sound_info_message(row: &AudioTableRow) -> SoundInfoMessage {
+    // waveform is a nice to have, don't fail the whole message over it
+    let waveform = row
+        .audio_file
+        .read_waveform()
+        .log_err_msg(format!("Failed to read waveform for `{}`", row.name))
+        .ok()
+        .flatten()
+        .map(|png| CreateAttachment::bytes(png, WAVEFORM_FILENAME));
+
+    let mut embed = CreateEmbed::new()
+        .title(&row.name)
+        .field("Duration", format_track_duration(row.duration_ms), true)
+        .field(
+            "Size",
+            row.file_size
+                .map(quotas::format_bytes)
+                .unwrap_or("Unknown".into()),
+            true,
+        )
+        .field("Uploader", format_uploader(row), true)
+        .field("Plays", row.play_count.to_string(), true)
+        .field("Tags", format_tags(row), false)
+        .field(
+            "Created",
+            format!("<t:{}:f>", row.created_at.timestamp()),
+            true,
+        )
+        .footer(CreateEmbedFooter::new(match row.pinned {
+            true => "📌 Pinned",
+            false => "Not pinned",
+        }));
+
+    if !row.aliases.is_empty() {
+        embed = embed.field("Aliases", row.aliases.join(", "), false);
+    }
+
+    if waveform.is_some() {
+        embed = embed.image(format!("attachment://{WAVEFORM_FILENAME}"));
+    }
+
+    SoundInfoMessage {
+        embed,
+        waveform,
+        components: vec![make_sound_info_buttons(row)],
+    }
+}
+
+pub fn make_sound_info_buttons(row: &AudioTableRow) -> CreateActionRow {
+    CreateActionRow::Buttons(vec![
+        CreateButton::new(ButtonCustomId::PreviewAudio(row.id))
+            .label("Preview")
+            .emoji(ReactionType::Unicode("🎧".into()))
+            .style(serenity::all::ButtonStyle::Secondary),
+        // toggles the favorite of whoever clicks it
+        CreateButton::new(ButtonCustomId::ToggleFavorite(row.id))
+            .label("Favorite")
+            .emoji(ReactionType::Unicode("⭐".into()))
+            .style(serenity::all::ButtonStyle::Secondary),
+    ])
+}
+
+/// Ephemeral reply with the audio file attached, so only the requesting user hears it
+pub fn make_sound_preview_message(
+    row: &AudioTableRow,
+) -> Result<CreateInteractionResponseMessage, PoiseError> {
+    let audio = std::fs::read(row.audio_file.as_path())
+        .log_err_msg(format!("Failed to read audio file for `{}`", row.name))?;
+
+    Ok(CreateInteractionResponseMessage::new()
+        .content(format!("Preview of `{}`", row.name))
+        .add_file(CreateAttachment::bytes(audio, preview_filename(&row.name)))
+        .ephemeral(true))
+}
+
+/// Attachment filename for sound name. ie: `Air Horn!` -> `Air_Horn_.mp3`
+fn preview_filename(name: &str) -> String {
+    let stem: String = name
+        .chars()
+        .map(|c| match c.is_ascii_alphanumeric() || c == '-' {
+            true => c,
+            false => '_',
+        })
+        .collect();
+
+    format!("{stem}.mp3")
+}
+
+/// Track duration with hundredths of a second. ie: `2.35s`
+fn format_track_duration(duration_ms: Option<u64>) -> String {
+    match duration_ms {
+        Some(ms) => format!("{:.2}s", ms as f64 / 1000.0),
+        None => "Unknown".into(),
+    }
+}
+
+fn format_uploader(row: &AudioTableRow) -> String {
+    match (
+        row.author_id,
+        row.author_global_name.as_ref(),
+        row.author_name.as_ref(),
+    ) {
+        (Some(author_id), _, _) => format!("<@{author_id}>"),
+        (None, Some(name), _) | (None, None, Some(name)) => name.clone(),
+        (None, None, None) => "Unknown".into(),
+    }
+}
+
+fn format_tags(row: &AudioTableRow) -> String {
+    match row.tags.is_empty() {
+        true => "None".into(),
+        false => row
+            .tags
+            .iter()
+            .map(|tag| format!("`{tag}`"))
+            .collect::<Vec<_>>()
+            .join(" "),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_test() {
+        assert_eq!(format_track_duration(Some(2340)), "2.34s");
+        assert_eq!(format_track_duration(Some(500)), "0.50s");
+        assert_eq!(format_track_duration(None), "Unknown");
+
+        assert_eq!(preview_filename("Air Horn!"), "Air_Horn_.mp3");
+        assert_eq!(preview_filename("bruh-2"), "bruh-2.mp3");
+    }
+}
@@ -0,0 +1,348 @@
+use poise::CreateReply;
+use serenity::all::{
+    CreateActionRow, CreateAttachment, CreateButton, CreateEmbed, CreateEmbedFooter,
+    CreateInteractionResponseMessage,
+};
+
+use crate::{charts, commands::PoiseError, common::UserData, helpers::ButtonCustomId, quotas};
+
+pub const STATS_TOP_LIMIT: u64 = 5;
+pub const STATS_CHART_TOP_LIMIT: u64 = 10;
+pub const PLAYS_PER_DAY_CHART_FILENAME: &str = "plays_per_day.png";
+pub const TOP_SOUNDS_CHART_FILENAME: &str = "top_sounds.png";
+pub const STATS_PLAYS_PER_DAY_DAYS: u64 = 30;
+
+/// Time window stats are computed over
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StatsWindow {
+    Day,
+    Week,
+    Month,
+    AllTime,
+}
+
+impl StatsWindow {
+    pub const ALL: [StatsWindow; 4] = [Self::Day, Self::Week, Self::Month, Self::AllTime];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Day => "day",
+            Self::Week => "week",
+            Self::Month => "month",
+            Self::AllTime => "all",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Day => "Past Day",
+            Self::Week => "Past Week",
+            Self::Month => "Past Month",
+            Self::AllTime => "All Time",
+        }
+    }
+
+    /// Start of window, or None for all time
+    pub fn since(
+        &self,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> Option<chrono::DateTime<chrono::Utc>> {
+        match self {
+            Self::Day => Some(now - chrono::Duration::days(1)),
+            Self::Week => Some(now - chrono::Duration::weeks(1)),
+            Self::Month => Some(now - chrono::Duration::days(30)),
+            Self::AllTime => None,
+        }
+    }
+}
+
+impl TryFrom<&str> for StatsWindow {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "day" => Ok(Self::Day),
+            "week" => Ok(Self::Week),
+            "month" => Ok(Self::Month),
+            "all" => Ok(Self::AllTime),
+            val => Err(format!("Unknown stats window: {val}")),
+        }
+    }
+}
+
+/// Usage statistics of a guild over a time window
+pub struct GuildStats {
+    pub window: StatsWindow,
+    pub top_tracks: Vec<(String, u64)>,
+    pub chart_top_tracks: Vec<(String, u64)>,
+    pub top_players: Vec<(u64, u64)>,
+    pub top_uploaders: Vec<(u64, u64)>,
+    pub plays_per_day: Vec<(chrono::NaiveDate, u64)>,
+    pub library_count: u64,
+    pub library_bytes: u64,
+    pub library_duration: std::time::Duration,
+}
+
+impl GuildStats {
+    pub fn fetch(
+        data: &UserData,
+        guild_id: u64,
+        window: StatsWindow,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Self, PoiseError> {
+        let audio_table = data.audio_table();
+        let history_table = data.play_history_table();
+        let since = window.since(now);
+        let (library_count, library_duration) = audio_table.guild_library_duration(guild_id)?;
+
+        Ok(Self {
+            window,
+            top_tracks: history_table.top_tracks(guild_id, since, STATS_TOP_LIMIT)?,
+            chart_top_tracks: history_table.top_tracks(guild_id, since, STATS_CHART_TOP_LIMIT)?,
+            top_players: history_table.top_players(guild_id, since, STATS_TOP_LIMIT)?,
+            top_uploaders: audio_table.top_uploaders(guild_id, since, STATS_TOP_LIMIT)?,
+            plays_per_day: history_table.plays_per_day(
+                guild_id,
+                now.date_naive(),
+                STATS_PLAYS_PER_DAY_DAYS,
+            )?,
+            library_count,
+            library_bytes: audio_table.guild_library_size(guild_id)?,
+            library_duration,
+        })
+    }
+}
+
+pub struct StatsMessage {
+    embeds: Vec<CreateEmbed>,
+    attachments: Vec<CreateAttachment>,
+    components: Vec<CreateActionRow>,
+}
+
+impl From<StatsMessage> for CreateReply {
+    fn from(value: StatsMessage) -> Self {
+        let reply = CreateReply::default().components(value.components);
+        let reply = value
+            .embeds
+            .into_iter()
+            .fold(reply, |reply, embed| reply.embed(embed));
+
+        value
+            .attachments
+            .into_iter()
+            .fold(reply, |reply, attachment| reply.attachment(attachment))
+    }
+}
+
+impl From<StatsMessage> for CreateInteractionResponseMessage {
+    fn from(value: StatsMessage) -> Self {
+        // replace charts of previously selected window
+        CreateInteractionResponseMessage::new()
+            .embeds(value.embeds)
+            .files(value.attachments)
+            .components(value.components)
+    }
+}
+
+/// Renders plays per day as a line chart and top sounds as a bar chart
+pub fn make_stats_charts(stats: &GuildStats) -> Result<Vec<CreateAttachment>, PoiseError> {
+    let plays_per_day: Vec<_> = stats
+        .plays_per_day
+        .iter()
+        .map(|(_, count)| *count)
+        .collect();
+    let top_sounds: Vec<_> = stats
+        .chart_top_tracks
+        .iter()
+        .map(|(_, plays)| *plays)
+        .collect();
+
+    Ok(vec![
+        CreateAttachment::bytes(
+            charts::line_chart(&plays_per_day).to_png()?,
+            PLAYS_PER_DAY_CHART_FILENAME,
+        ),
+        CreateAttachment::bytes(
+            charts::bar_chart(&top_sounds).to_png()?,
+            TOP_SOUNDS_CHART_FILENAME,
+        ),
+    ])
+}
+
+pub fn make_stats_message(stats: &GuildStats) -> Result<StatsMessage, PoiseError> {
+    let window = stats.window;
+
+    let top_tracks = format_leaderboard(
+        stats
+            .top_tracks
+            .iter()
+            .map(|(name, plays)| format!("`{name}` - {plays} plays")),
+    );
+    let top_players = format_leaderboard(
+        stats
+            .top_players
+            .iter()
+            .map(|(user_id, plays)| format!("<@{user_id}> - {plays} plays")),
+    );
+    let top_uploaders = format_leaderboard(
+        stats
+            .top_uploaders
+            .iter()
+            .map(|(user_id, uploads)| format!("<@{user_id}> - {uploads} sounds")),
+    );
+
+    let counts: Vec<_> = stats
+        .plays_per_day
+        .iter()
+        .map(|(_, count)| *count)
+        .collect();
+    let plays_per_day = format!(
+        "`{}`\n{} plays, busiest day {}",
+        sparkline(&counts),
+        counts.iter().sum::<u64>(),
+        counts.iter().max().copied().unwrap_or(0)
+    );
+
+    let library = format!(
+        "{} sounds · {} · {}",
+        stats.library_count,
+        quotas::format_bytes(stats.library_bytes),
+        format_duration(stats.library_duration)
+    );
+
+    let embed = CreateEmbed::new()
+        .title(format!("Soundbot Stats - {}", window.label()))
+        .field("Top Sounds", top_tracks, true)
+        .field("Top Players", top_players, true)
+        .field("Top Uploaders", top_uploaders, true)
+        .field(
+            format!("Plays Per Day (last {STATS_PLAYS_PER_DAY_DAYS} days)"),
+            plays_per_day,
+            false,
+        )
+        .field("Library", library, false)
+        .image(format!("attachment://{PLAYS_PER_DAY_CHART_FILENAME}"))
+        .footer(CreateEmbedFooter::new(
+            "Sounds, players & uploaders are ranked within the time window",
+        ));
+
+    let chart_embed = CreateEmbed::new()
+        .title(format!(
+            "Top {STATS_CHART_TOP_LIMIT} Sounds - {}",
+            window.label()
+        ))
+        .description(format_leaderboard(
+            stats
+                .chart_top_tracks
+                .iter()
+                .map(|(name, plays)| format!("`{name}` - {plays} plays")),
+        ))
+        .image(format!("attachment://{TOP_SOUNDS_CHART_FILENAME}"));
+
+    Ok(StatsMessage {
+        embeds: vec![embed, chart_embed],
+        attachments: make_stats_charts(stats)?,
+        components: vec![make_stats_window_buttons(window)],
+    })
+}
+
+pub fn make_stats_window_buttons(selected: StatsWindow) -> CreateActionRow {
+    let buttons = StatsWindow::ALL
+        .iter()
+        .map(|window| {
+            let style = if *window == selected {
+                serenity::all::ButtonStyle::Primary
+            } else {
+                serenity::all::ButtonStyle::Secondary
+            };
+
+            CreateButton::new(ButtonCustomId::Stats(*window))
+                .label(window.label())
+                .style(style)
+                .disabled(*window == selected)
+        })
+        .collect();
+
+    CreateActionRow::Buttons(buttons)
+}
+
+fn format_leaderboard(lines: impl Iterator<Item = String>) -> String {
+    let lines: Vec<_> = lines
+        .enumerate()
+        .map(|(i, line)| format!("{}. {line}", i + 1))
+        .collect();
+
+    if lines.is_empty() {
+        "Nothing yet".into()
+    } else {
+        lines.join("\n")
+    }
+}
+
+/// Renders counts as unicode block characters scaled to the largest count. ie: `▁▃█▁`
+pub fn sparkline(counts: &[u64]) -> String {
+    const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let max = counts.iter().max().copied().unwrap_or(0);
+
+    counts
+        .iter()
+        .map(|count| match max {
+            0 => BLOCKS[0],
+            _ => BLOCKS[((*count * (BLOCKS.len() as u64 - 1)) / max) as usize],
+        })
+        .collect()
+}
+
+/// Human readable duration. ie: `1h 2m 3s`
+pub fn format_duration(duration: std::time::Duration) -> String {
+    let secs = duration.as_secs();
+    let (hours, mins, secs) = (secs / 3600, (secs % 3600) / 60, secs % 60);
+
+    match (hours, mins) {
+        (0, 0) => format!("{secs}s"),
+        (0, _) => format!("{mins}m {secs}s"),
+        _ => format!("{hours}h {mins}m {secs}s"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stats_window_test() {
+        for window in StatsWindow::ALL {
+            assert_eq!(StatsWindow::try_from(window.as_str()), Ok(window));
+        }
+
+        let now = chrono::Utc::now();
+        assert_eq!(StatsWindow::AllTime.since(now), None);
+        assert_eq!(
+            StatsWindow::Week.since(now),
+            Some(now - chrono::Duration::days(7))
+        );
+    }
+
+    #[test]
+    fn format_test() {
+        assert_eq!(sparkline(&[0, 0]), "▁▁");
+        assert_eq!(sparkline(&[0, 7, 14]), "▁▄█");
+        assert_eq!(sparkline(&[]), "");
+
+        assert_eq!(format_duration(std::time::Duration::from_secs(5)), "5s");
+        assert_eq!(
+            format_duration(std::time::Duration::from_secs(125)),
+            "2m 5s"
+        );
+        assert_eq!(
+            format_duration(std::time::Duration::from_secs(3723)),
+            "1h 2m 3s"
+        );
+
+        assert_eq!(format_leaderboard(std::iter::empty()), "Nothing yet");
+        assert_eq!(
+            format_leaderboard(vec!["a".to_string(), "b".to_string()].into_iter()),
+            "1. a\n2. b"
+        );
+    }
+}
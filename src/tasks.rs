@@ -0,0 +1,433 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use serenity::all::{ChannelId, Context, GuildId};
+use songbird::Songbird;
+
+use crate::{
+    audio::{self, TrackHandleHelper},
+    commands::PoiseResult,
+    common::{LogResult, UserData},
+    db::{self, AudioTable, PlayHistoryTable, ScheduleRow},
+    helpers::{self, SongbirdHelper},
+    popularity,
+    scheduler::{Scheduler, SystemClock},
+};
+
+/// Guards against restarting tasks when discord re-sends the ready event on reconnects
+static TASKS_STARTED: AtomicBool = AtomicBool::new(false);
+
+const TRASH_PURGE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+const POPULARITY_REFRESH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+const IDLE_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+const SCHEDULE_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(15);
+
+pub fn start_background_tasks(ctx: &Context, data: &UserData, manager: Arc<Songbird>) {
+    if TASKS_STARTED.swap(true, Ordering::SeqCst) {
+        log::debug!("Background tasks already started");
+        return;
+    }
+
+    log::info!("Starting background tasks...");
+    spawn_trash_purge_task(data);
+    spawn_popularity_refresh_task(data);
+    spawn_duration_backfill_task(data);
+    spawn_file_size_backfill_task(data);
+    spawn_waveform_backfill_task(data);
+    spawn_idle_timeout_task(data, manager.clone());
+    spawn_schedule_task(ctx, data, manager);
+}
+
+/// Periodically purges sounds that have been in the trash longer than the retention period
+fn spawn_trash_purge_task(data: &UserData) {
+    let db_pool = data.db_pool.clone();
+    let retention = chrono::Duration::days(data.config.trash_retention_days as i64);
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(TRASH_PURGE_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            let conn = match db_pool.get() {
+                Ok(conn) => conn,
+                Err(err) => {
+                    log::error!("Trash purge task failed to get db connection - {err}");
+                    continue;
+                }
+            };
+
+            let before = chrono::Utc::now() - retention;
+            if let Ok(count) = AudioTable::new(conn)
+                .purge_trashed_rows(before)
+                .log_err_msg("Trash purge task failed")
+            {
+                log::info!("Trash purge task purged {count} audio track(s)");
+            }
+        }
+    });
+}
+
+/// Periodically recomputes popularity so tracks that stop being played decay out of trending
+fn spawn_popularity_refresh_task(data: &UserData) {
+    let db_pool = data.db_pool.clone();
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(POPULARITY_REFRESH_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            let (audio_conn, history_conn) = match (db_pool.get(), db_pool.get()) {
+                (Ok(audio_conn), Ok(history_conn)) => (audio_conn, history_conn),
+                (Err(err), _) | (_, Err(err)) => {
+                    log::error!("Popularity refresh task failed to get db connection - {err}");
+                    continue;
+                }
+            };
+
+            if let Ok(count) = popularity::refresh_popularity(
+                &AudioTable::new(audio_conn),
+                &PlayHistoryTable::new(history_conn),
+                chrono::Utc::now(),
+            )
+            .log_err_msg("Popularity refresh task failed")
+            {
+                log::info!("Popularity refresh task scored {count} audio track(s)");
+            }
+        }
+    });
+}
+
+/// Probes durations of audio tracks added before durations were recorded at ingest
+fn spawn_duration_backfill_task(data: &UserData) {
+    let db_pool = data.db_pool.clone();
+
+    tokio::task::spawn_blocking(move || {
+        let table = match db_pool.get() {
+            Ok(conn) => AudioTable::new(conn),
+            Err(err) => {
+                log::error!("Duration backfill task failed to get db connection - {err}");
+                return;
+            }
+        };
+
+        let rows = match table
+            .list_rows_missing_duration()
+            .log_err_msg("Duration backfill task failed")
+        {
+            Ok(rows) => rows,
+            Err(_) => return,
+        };
+
+        let mut count = 0;
+        for row in rows.iter() {
+            if let Ok(track_info) = audio::probe_audio_track(row.audio_file.as_path_buf()) {
+                if table
+                    .update_audio_row_duration(row.id, track_info.duration)
+                    .is_ok()
+                {
+                    count += 1;
+                }
+            }
+        }
+
+        log::info!("Duration backfill task updated {count} audio track(s)");
+    });
+}
+
+/// Records file sizes of audio tracks added before they were recorded, for library size quotas
+fn spawn_file_size_backfill_task(data: &UserData) {
+    let db_pool = data.db_pool.clone();
+
+    tokio::task::spawn_blocking(move || {
+        let table = match db_pool.get() {
+            Ok(conn) => AudioTable::new(conn),
+            Err(err) => {
+                log::error!("File size backfill task failed to get db connection - {err}");
+                return;
+            }
+        };
+
+        let rows = match table
+            .list_rows_missing_file_size()
+            .log_err_msg("File size backfill task failed")
+        {
+            Ok(rows) => rows,
+            Err(_) => return,
+        };
+
+        let mut count = 0;
+        for row in rows.iter() {
+            if let Ok(metadata) = std::fs::metadata(row.audio_file.as_path()) {
+                if table
+                    .update_audio_row_file_size(row.id, metadata.len())
+                    .is_ok()
+                {
+                    count += 1;
+                }
+            }
+        }
+
+        log::info!("File size backfill task updated {count} audio track(s)");
+    });
+}
+
+/// Renders waveforms of audio tracks added before waveforms were rendered at ingest
+fn spawn_waveform_backfill_task(data: &UserData) {
+    let db_pool = data.db_pool.clone();
+
+    tokio::task::spawn_blocking(move || {
+        let conn = match db_pool.get() {
+            Ok(conn) => conn,
+            Err(err) => {
+                log::error!("Waveform backfill task failed to get db connection - {err}");
+                return;
+            }
+        };
+
+        let mut count = 0;
+        for page in db::AudioTablePaginatorBuilder::new(conn).build() {
+            let Ok(rows) = page.log_err_msg("Waveform backfill task failed") else {
+                return;
+            };
+
+            for row in rows.iter() {
+                if row.audio_file.waveform_path().exists() {
+                    continue;
+                }
+
+                if row
+                    .audio_file
+                    .write_waveform()
+                    .log_err_msg(format!("Failed to write waveform for `{}`", row.name))
+                    .is_ok()
+                {
+                    count += 1;
+                }
+            }
+        }
+
+        log::info!("Waveform backfill task rendered {count} waveform(s)");
+    });
+}
+
+/// Periodically leaves voice channels nothing has played in for longer than the guild's idle timeout
+fn spawn_idle_timeout_task(data: &UserData, manager: Arc<Songbird>) {
+    let data = data.clone();
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(IDLE_CHECK_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            for guild_id in data.playback_activity.guild_ids() {
+                let channel_id = match manager.get(guild_id) {
+                    Some(handler_lock) => handler_lock.lock().await.current_channel(),
+                    None => None,
+                };
+
+                // left some other way. ie: bot alone in voice channel
+                let Some(channel_id) = channel_id else {
+                    data.playback_activity.remove(guild_id);
+                    continue;
+                };
+
+                let timeout = match data
+                    .guild_settings_table()
+                    .get_guild_settings(guild_id.into())
+                    .log_err_msg("Idle timeout task failed getting guild settings")
+                {
+                    Ok(settings) => settings.idle_timeout(),
+                    Err(_) => continue,
+                };
+
+                let Some(timeout) = timeout else {
+                    continue;
+                };
+
+                if data
+                    .playback_activity
+                    .is_idle(guild_id, timeout, chrono::Utc::now())
+                {
+                    log::info!("Voice channel idle. Bot is leaving. Guild ID: {guild_id}");
+                    helpers::leave_voice_channel(
+                        &manager,
+                        &data,
+                        guild_id,
+                        ChannelId::new(channel_id.0.get()),
+                    )
+                    .await
+                    .log_err_msg("Idle timeout task failed leaving voice channel")
+                    .ok();
+                }
+            }
+        }
+    });
+}
+
+/// Plays scheduled sounds when their cron expressions come due
+fn spawn_schedule_task(ctx: &Context, data: &UserData, manager: Arc<Songbird>) {
+    let ctx = ctx.clone();
+    let data = data.clone();
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(SCHEDULE_CHECK_INTERVAL);
+        let mut scheduler = Scheduler::new(SystemClock);
+
+        loop {
+            interval.tick().await;
+
+            let schedules = match data
+                .schedules_table()
+                .list_all_schedules()
+                .log_err_msg("Schedule task failed listing schedules")
+            {
+                Ok(schedules) => schedules,
+                Err(_) => continue,
+            };
+
+            // a guild's due schedules play one at a time, since sounds played away from the
+            // bot's voice channel move the bot until they end
+            let mut due_by_guild: HashMap<u64, Vec<ScheduleRow>> = HashMap::new();
+            for schedule in scheduler.tick(&schedules) {
+                due_by_guild
+                    .entry(schedule.guild_id)
+                    .or_default()
+                    .push(schedule.clone());
+            }
+
+            for due in due_by_guild.into_values() {
+                let (ctx, data, manager) = (ctx.clone(), data.clone(), manager.clone());
+
+                tokio::spawn(async move {
+                    for schedule in due.iter() {
+                        play_scheduled_sound(&ctx, &data, &manager, schedule)
+                            .await
+                            .log_err_msg(format!(
+                                "Schedule task failed playing schedule {}",
+                                schedule.id
+                            ))
+                            .ok();
+                    }
+                });
+            }
+        }
+    });
+}
+
+/// Plays scheduled sound in its voice channel, or whichever voice channel the bot is in.
+/// Sounds scheduled for another voice channel are skipped if nobody is in it, otherwise the bot
+/// goes back to where it was once the sound ends
+async fn play_scheduled_sound(
+    ctx: &Context,
+    data: &UserData,
+    manager: &Songbird,
+    schedule: &ScheduleRow,
+) -> PoiseResult {
+    let guild_id = GuildId::new(schedule.guild_id);
+    let row = data
+        .audio_table()
+        .find_audio_row(db::UniqueAudioTableCol::Id(schedule.audio_id))
+        .ok_or(format!(
+            "Unable to locate audio track {}. It may be in the trash",
+            schedule.audio_id
+        ))?;
+
+    let current_channel_id = match manager.get(guild_id) {
+        Some(handler_lock) => handler_lock
+            .lock()
+            .await
+            .current_channel()
+            .map(|channel_id| ChannelId::new(channel_id.0.get())),
+        None => None,
+    };
+
+    let Some(channel_id) = schedule
+        .channel_id
+        .map(ChannelId::new)
+        .or(current_channel_id)
+    else {
+        log::info!(
+            "Skipping schedule {}. Bot isn't in a voice channel. Guild ID: {guild_id}",
+            schedule.id
+        );
+        return Ok(());
+    };
+
+    if current_channel_id == Some(channel_id) {
+        log::info!(
+            "Playing scheduled sound: {}. Guild ID: {guild_id}",
+            row.name
+        );
+        manager
+            .play_audio(guild_id, channel_id, &row.audio_file)
+            .await?;
+        record_scheduled_play(data, &row, guild_id, channel_id);
+        return Ok(());
+    }
+
+    if helpers::count_voice_channel_listeners(ctx, guild_id, channel_id) == 0 {
+        log::info!(
+            "Skipping schedule {}. Nobody is in voice channel {channel_id}. Guild ID: {guild_id}",
+            schedule.id
+        );
+        return Ok(());
+    }
+
+    helpers::connect_voice_channel(ctx, data, guild_id, channel_id).await?;
+
+    log::info!(
+        "Playing scheduled sound: {}. Guild ID: {guild_id}",
+        row.name
+    );
+    // wait without holding the call's lock, so sounds can still be played manually
+    let played = manager
+        .play_audio(guild_id, channel_id, &row.audio_file)
+        .await;
+    if let Ok(track_handle) = played.as_ref() {
+        record_scheduled_play(data, &row, guild_id, channel_id);
+        track_handle.wait_for_end().await;
+    }
+
+    match current_channel_id {
+        Some(previous_channel_id) => {
+            log::info!("Returning to voice channel {previous_channel_id} after schedule {}. Guild ID: {guild_id}", schedule.id);
+            helpers::connect_voice_channel(ctx, data, guild_id, previous_channel_id).await?;
+        }
+        None => {
+            log::info!(
+                "Leaving voice channel after schedule {}. Guild ID: {guild_id}",
+                schedule.id
+            );
+            data.playback_activity.remove(guild_id);
+            manager
+                .remove(guild_id)
+                .await
+                .log_err_msg(format!("Bot failed to leave Guild ID: {guild_id}"))?;
+        }
+    }
+
+    played?;
+    Ok(())
+}
+
+fn record_scheduled_play(
+    data: &UserData,
+    row: &db::AudioTableRow,
+    guild_id: GuildId,
+    channel_id: ChannelId,
+) {
+    data.record_play(
+        db::PlayHistoryEntry::new(row.id, guild_id, db::PlaySource::Schedule)
+            .channel_id(Some(channel_id)),
+    )
+    .log_err()
+    .ok();
+}
@@ -0,0 +1,228 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use regex::{Regex, RegexBuilder};
+
+use crate::{
+    commands::PoiseError,
+    db::{TriggerKind, TriggerRow},
+    vars,
+};
+
+/// Compiles text trigger pattern to a regex matching messages, ignoring case.
+/// Errors on empty, overly long, or overly complex patterns
+pub fn compile_pattern(kind: TriggerKind, pattern: impl AsRef<str>) -> Result<Regex, PoiseError> {
+    let pattern = pattern.as_ref().trim();
+
+    if pattern.is_empty() {
+        return Err("Trigger pattern can't be empty".into());
+    }
+
+    let max_len = vars::TRIGGER_PATTERN_MAX_LEN;
+    if pattern.chars().count() > max_len {
+        return Err(format!("Trigger pattern can't be longer than {max_len} characters").into());
+    }
+
+    let expr = match kind {
+        TriggerKind::Word => {
+            // `\b` doesn't match next to non-word characters, so words like `:)` need whitespace around them
+            let is_word_char = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric() || c == '_');
+            let start = match is_word_char(pattern.chars().next()) {
+                true => r"\b",
+                false => r"(?:^|\s)",
+            };
+            let end = match is_word_char(pattern.chars().last()) {
+                true => r"\b",
+                false => r"(?:$|\s)",
+            };
+            format!("{start}{}{end}", regex::escape(pattern))
+        }
+        TriggerKind::Phrase => regex::escape(pattern),
+        TriggerKind::Regex => pattern.to_string(),
+    };
+
+    RegexBuilder::new(&expr)
+        .case_insensitive(true)
+        .size_limit(vars::TRIGGER_REGEX_SIZE_LIMIT)
+        .dfa_size_limit(vars::TRIGGER_REGEX_SIZE_LIMIT)
+        .build()
+        .map_err(|err| format!("Invalid trigger pattern `{pattern}` - {err}").into())
+}
+
+/// Kind & pattern a regex was compiled from, so reused trigger ids aren't matched by stale patterns
+type CachedPattern = (TriggerKind, String, Option<Regex>);
+
+/// Trigger patterns compiled by trigger id, so messages aren't matched by recompiling every pattern.
+/// Invalid patterns are cached as `None`, so their errors are only logged once
+#[derive(Debug, Clone, Default)]
+pub struct TriggerRegexCache(Arc<Mutex<HashMap<i64, CachedPattern>>>);
+
+impl TriggerRegexCache {
+    /// Trigger's compiled pattern. Recompiled if the trigger's id was reused by a different pattern
+    pub fn get(&self, trigger: &TriggerRow) -> Option<Regex> {
+        let mut cache = self.0.lock().expect("Trigger regex cache lock poisoned");
+
+        match cache.get(&trigger.id) {
+            Some((kind, pattern, re)) if *kind == trigger.kind && *pattern == trigger.pattern => {
+                re.clone()
+            }
+            _ => {
+                let re = compile_pattern(trigger.kind, &trigger.pattern)
+                    .map_err(|err| {
+                        log::error!("Trigger {} has invalid pattern - {err}", trigger.id)
+                    })
+                    .ok();
+                cache.insert(
+                    trigger.id,
+                    (trigger.kind, trigger.pattern.clone(), re.clone()),
+                );
+                re
+            }
+        }
+    }
+
+    pub fn remove(&self, trigger_id: i64) {
+        self.0
+            .lock()
+            .expect("Trigger regex cache lock poisoned")
+            .remove(&trigger_id);
+    }
+
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.0.lock().unwrap().len()
+    }
+}
+
+/// First trigger matching message content that isn't on cooldown
+pub fn find_match<'a>(
+    cache: &TriggerRegexCache,
+    triggers: &'a [TriggerRow],
+    content: impl AsRef<str>,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Option<&'a TriggerRow> {
+    let content = content.as_ref();
+
+    triggers
+        .iter()
+        .filter(|trigger| !trigger.is_on_cooldown(now))
+        .find(|trigger| cache.get(trigger).is_some_and(|re| re.is_match(content)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trigger_row(id: i64, kind: TriggerKind, pattern: &str) -> TriggerRow {
+        TriggerRow {
+            id,
+            guild_id: 1,
+            channel_id: 100,
+            kind,
+            pattern: pattern.into(),
+            audio_id: 1,
+            cooldown_secs: 30,
+            last_triggered_at: None,
+            author_id: None,
+            created_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn compile_pattern_test() {
+        let word = compile_pattern(TriggerKind::Word, "bruh").unwrap();
+        assert!(word.is_match("bruh"));
+        assert!(word.is_match("ok BRUH."));
+        assert!(!word.is_match("bruhh"));
+
+        let word = compile_pattern(TriggerKind::Word, ":)").unwrap();
+        assert!(word.is_match("nice :)"));
+        assert!(!word.is_match("nice :))"));
+
+        let phrase = compile_pattern(TriggerKind::Phrase, "good night").unwrap();
+        assert!(phrase.is_match("Good night everyone"));
+        assert!(!phrase.is_match("good  night"));
+
+        // phrases & words aren't regexes
+        let phrase = compile_pattern(TriggerKind::Phrase, "a.c").unwrap();
+        assert!(!phrase.is_match("abc"));
+
+        let re = compile_pattern(TriggerKind::Regex, "^l+o+l+$").unwrap();
+        assert!(re.is_match("LOOOL"));
+        assert!(!re.is_match("lol what"));
+        assert!(compile_pattern(TriggerKind::Regex, r"^\w+ (?:says|said) \w+!+$").is_ok());
+
+        assert!(compile_pattern(TriggerKind::Word, "  ").is_err());
+        assert!(compile_pattern(TriggerKind::Regex, "(unclosed").is_err());
+        assert!(compile_pattern(TriggerKind::Phrase, "a".repeat(101)).is_err());
+        // too large once compiled
+        assert!(compile_pattern(TriggerKind::Regex, r"(?:\w{50}){20}").is_err());
+    }
+
+    #[test]
+    fn find_match_test() {
+        let cache = TriggerRegexCache::default();
+        let now = chrono::Utc::now();
+        let mut triggers = vec![
+            trigger_row(1, TriggerKind::Word, "bruh"),
+            trigger_row(2, TriggerKind::Phrase, "bruh moment"),
+            trigger_row(3, TriggerKind::Regex, "(unclosed"),
+        ];
+
+        assert!(find_match(&cache, &triggers, "hello", now).is_none());
+        assert_eq!(
+            find_match(&cache, &triggers, "bruh moment", now)
+                .unwrap()
+                .id,
+            1
+        );
+
+        // triggers on cooldown are skipped
+        triggers[0].last_triggered_at = Some(now - chrono::Duration::seconds(10));
+        assert_eq!(
+            find_match(&cache, &triggers, "bruh moment", now)
+                .unwrap()
+                .id,
+            2
+        );
+        assert!(find_match(&cache, &triggers, "bruh", now).is_none());
+        assert_eq!(
+            find_match(
+                &cache,
+                &triggers,
+                "bruh",
+                now + chrono::Duration::seconds(20)
+            )
+            .unwrap()
+            .id,
+            1
+        );
+    }
+
+    #[test]
+    fn trigger_regex_cache_test() {
+        let cache = TriggerRegexCache::default();
+        let mut trigger = trigger_row(1, TriggerKind::Word, "bruh");
+
+        assert!(cache.get(&trigger).unwrap().is_match("bruh"));
+        assert!(cache.get(&trigger).unwrap().is_match("bruh"));
+        assert_eq!(cache.len(), 1);
+
+        // a new trigger reusing the id isn't matched by the old pattern
+        trigger.pattern = "oof".into();
+        assert!(cache.get(&trigger).unwrap().is_match("oof"));
+        assert!(!cache.get(&trigger).unwrap().is_match("bruh"));
+        assert_eq!(cache.len(), 1);
+
+        // invalid patterns are cached too
+        let invalid = trigger_row(2, TriggerKind::Regex, "(unclosed");
+        assert!(cache.get(&invalid).is_none());
+        assert_eq!(cache.len(), 2);
+
+        cache.remove(1);
+        cache.remove(2);
+        assert_eq!(cache.len(), 0);
+    }
+}
@@ -1,2 +1,21 @@
 pub const BTN_LABEL_MAX_LEN: usize = 80;
+pub const AUDIT_LOG_PAGE_SIZE: u64 = 10;
+pub const AUDIT_LOG_VALUE_MAX_LEN: usize = 100;
+pub const POPULARITY_HALF_LIFE_DAYS: f64 = 3.0;
+pub const POPULARITY_WINDOW_DAYS: i64 = 30;
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+/// Minimum fuzzy similarity of a sound to be suggested. See `fuzzy::similarity`
+pub const FUZZY_MIN_SCORE: f64 = 0.8;
+/// Minimum fuzzy similarity of a sound for `/play` to play it in place of a missing exact match
+pub const FUZZY_PLAY_MIN_SCORE: f64 = 0.92;
+/// Weight of tag similarity relative to name similarity
+pub const FUZZY_TAG_WEIGHT: f64 = 0.9;
+/// Longest a member's intro plays for, unless changed by the guild
+pub const DEFAULT_MAX_INTRO_DURATION_MS: u64 = 5_000;
+/// Least time between a member's intros, unless changed by the guild. Stops reconnect loops spamming
+pub const DEFAULT_INTRO_COOLDOWN_SECS: u64 = 5 * 60;
+/// Least time between plays of a text trigger, unless set when adding it
+pub const DEFAULT_TRIGGER_COOLDOWN_SECS: u64 = 30;
+pub const TRIGGER_PATTERN_MAX_LEN: usize = 100;
+/// Largest compiled size in bytes of a text trigger's regex. Stops patterns that are slow to match
+pub const TRIGGER_REGEX_SIZE_LIMIT: usize = 1024 * 1024;